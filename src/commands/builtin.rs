@@ -0,0 +1,247 @@
+// src/commands/builtin.rs
+
+use std::path::Path;
+
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers};
+use ropey::Rope;
+
+use crate::commands::keymap::KEYBINDINGS_TEMPLATE;
+use crate::commands::registry::{Command, CommandContext, CommandHandler, CommandRegistry};
+use crate::core::editor::TextEditor;
+use crate::core::line_ending::LineEnding;
+use crate::core::settings::WORKSPACE_SETTINGS_TEMPLATE;
+use crate::ui::app::MyApp;
+use crate::ui::encoding_picker::EncodingPickerMode;
+use crate::ui::find_bar::FindAction;
+
+/// Registra os comandos embutidos com os atalhos padrão.
+pub fn register_builtin_commands(registry: &mut CommandRegistry) {
+    use CommandContext::{Editor, FindBar, Global};
+    let shortcut = |modifiers: Modifiers, key: Key| Some(KeyboardShortcut::new(modifiers, key));
+    let command_shift = Modifiers::COMMAND | Modifiers::SHIFT;
+
+    let commands: [(&'static str, &'static str, CommandContext, Option<KeyboardShortcut>, CommandHandler); 42] = [
+        // Arquivos e janela
+        ("file.save", "Arquivo: Salvar", Global, shortcut(Modifiers::COMMAND, Key::S), |app, ctx| {
+            if app.current_tab_mut().is_some_and(|tab| tab.is_modified) {
+                app.save_current_tab(ctx);
+            } else {
+                eprintln!("Arquivo não modificado, não há o que salvar.");
+            }
+        }),
+        ("file.reopen_with_encoding", "Arquivo: Reabrir com codificação...", Global, None, |app, _| {
+            open_encoding_picker(app, EncodingPickerMode::Reopen);
+        }),
+        ("file.save_with_encoding", "Arquivo: Salvar com codificação...", Global, None, |app, _| {
+            open_encoding_picker(app, EncodingPickerMode::Save);
+        }),
+        ("file.line_endings_lf", "Arquivo: Converter fins de linha para LF", Global, None, |app, _| {
+            app.convert_current_tab_line_endings(LineEnding::Lf);
+        }),
+        ("file.line_endings_crlf", "Arquivo: Converter fins de linha para CRLF", Global, None, |app, _| {
+            app.convert_current_tab_line_endings(LineEnding::Crlf);
+        }),
+        ("file.quick_open", "Arquivo: Abrir rápido...", Global, shortcut(Modifiers::COMMAND, Key::P), |app, ctx| {
+            match app.current_dir.clone() {
+                Some(dir) if !app.quick_open.is_open => app.quick_open.open(ctx, &dir),
+                _ => app.quick_open.close(),
+            }
+        }),
+        ("view.command_palette", "Exibir: Paleta de comandos", Global, shortcut(command_shift, Key::P), |app, _| app.command_palette.toggle()),
+        ("view.toggle_terminal", "Exibir: Mostrar/ocultar terminal", Global, None, |app, ctx| {
+            app.terminals.toggle(ctx, app.current_dir.clone());
+        }),
+        ("view.toggle_problems", "Exibir: Mostrar/ocultar problemas", Global, None, |app, _| app.show_problems = !app.show_problems),
+        ("view.toggle_project_search", "Exibir: Buscar em arquivos", Global, None, |app, _| app.show_search = !app.show_search),
+        ("view.reload_themes", "Exibir: Recarregar temas", Global, None, |app, _| app.highlighter.reload_themes()),
+        ("view.reload_syntaxes", "Exibir: Recarregar sintaxes", Global, None, |app, _| app.highlighter.reload_syntaxes()),
+        ("tasks.reload", "Tarefas: Recarregar tarefas", Global, None, |app, _| {
+            let dir = app.current_dir.clone();
+            app.tasks.reload(dir.as_deref());
+        }),
+        ("prefs.open_keybindings", "Preferências: Abrir arquivo de atalhos", Global, None, |app, _| open_keybindings_file(app)),
+        ("prefs.reload_keybindings", "Preferências: Recarregar atalhos", Global, None, |app, _| app.keymap.reload(&app.commands)),
+        ("prefs.open_settings", "Preferências: Configurações", Global, shortcut(Modifiers::COMMAND, Key::Comma), |app, _| {
+            app.settings_window.open(&app.settings);
+        }),
+        ("prefs.open_settings_file", "Preferências: Abrir arquivo de configurações", Global, None, |app, _| open_user_settings_file(app)),
+        ("prefs.open_workspace_settings", "Preferências: Abrir configurações do projeto", Global, None, |app, _| open_workspace_settings_file(app)),
+        ("prefs.reload_settings", "Preferências: Recarregar configurações", Global, None, |app, _| app.settings.reload()),
+        // Busca no arquivo
+        ("find.find", "Buscar: Buscar no arquivo", Global, shortcut(Modifiers::COMMAND, Key::F), |app, _| open_find_bar(app, false)),
+        ("find.replace", "Buscar: Substituir no arquivo", Global, shortcut(Modifiers::COMMAND, Key::H), |app, _| open_find_bar(app, true)),
+        ("find.next", "Buscar: Próxima ocorrência", FindBar, shortcut(Modifiers::NONE, Key::F3), |app, _| run_find_action(app, FindAction::Next)),
+        ("find.previous", "Buscar: Ocorrência anterior", FindBar, shortcut(Modifiers::SHIFT, Key::F3), |app, _| run_find_action(app, FindAction::Previous)),
+        ("find.close", "Buscar: Fechar barra de busca", FindBar, shortcut(Modifiers::NONE, Key::Escape), |app, _| run_find_action(app, FindAction::Close)),
+        // Edição
+        ("edit.undo", "Editar: Desfazer", Editor, shortcut(Modifiers::COMMAND, Key::Z), |app, _| {
+            edit_current_tab(app, |editor, content| editor.undo(content));
+        }),
+        ("edit.redo", "Editar: Refazer", Editor, shortcut(command_shift, Key::Z), |app, _| {
+            edit_current_tab(app, |editor, content| editor.redo(content));
+        }),
+        ("edit.copy", "Editar: Copiar", Editor, shortcut(Modifiers::COMMAND, Key::C), |app, ctx| copy_selection(app, ctx, false)),
+        ("edit.cut", "Editar: Recortar", Editor, shortcut(Modifiers::COMMAND, Key::X), |app, ctx| copy_selection(app, ctx, true)),
+        ("edit.delete_left", "Editar: Apagar caractere anterior", Editor, shortcut(Modifiers::NONE, Key::Backspace), |app, _| {
+            edit_current_tab(app, |editor, content| {
+                editor.delete_char_before_cursor(content);
+                true
+            });
+        }),
+        ("edit.delete_right", "Editar: Apagar caractere seguinte", Editor, shortcut(Modifiers::NONE, Key::Delete), |app, _| {
+            edit_current_tab(app, |editor, content| {
+                editor.delete_char_after_cursor(content);
+                true
+            });
+        }),
+        ("edit.new_line", "Editar: Nova linha", Editor, shortcut(Modifiers::NONE, Key::Enter), |app, _| {
+            edit_current_tab(app, |editor, content| {
+                editor.new_line(content);
+                true
+            });
+        }),
+        ("edit.toggle_vim", "Editar: Ativar/desativar modo Vim", Global, None, |app, _| toggle_vim(app)),
+        // Cursores
+        ("cursor.left", "Cursor: Esquerda", Editor, shortcut(Modifiers::NONE, Key::ArrowLeft), |app, _| move_cursor(app, TextEditor::move_cursor_left, false)),
+        ("cursor.right", "Cursor: Direita", Editor, shortcut(Modifiers::NONE, Key::ArrowRight), |app, _| move_cursor(app, TextEditor::move_cursor_right, false)),
+        ("cursor.up", "Cursor: Acima", Editor, shortcut(Modifiers::NONE, Key::ArrowUp), |app, _| move_cursor(app, TextEditor::move_cursor_up, false)),
+        ("cursor.down", "Cursor: Abaixo", Editor, shortcut(Modifiers::NONE, Key::ArrowDown), |app, _| move_cursor(app, TextEditor::move_cursor_down, false)),
+        ("cursor.select_left", "Cursor: Selecionar à esquerda", Editor, shortcut(Modifiers::SHIFT, Key::ArrowLeft), |app, _| move_cursor(app, TextEditor::move_cursor_left, true)),
+        ("cursor.select_right", "Cursor: Selecionar à direita", Editor, shortcut(Modifiers::SHIFT, Key::ArrowRight), |app, _| move_cursor(app, TextEditor::move_cursor_right, true)),
+        ("cursor.select_up", "Cursor: Selecionar acima", Editor, shortcut(Modifiers::SHIFT, Key::ArrowUp), |app, _| move_cursor(app, TextEditor::move_cursor_up, true)),
+        ("cursor.select_down", "Cursor: Selecionar abaixo", Editor, shortcut(Modifiers::SHIFT, Key::ArrowDown), |app, _| move_cursor(app, TextEditor::move_cursor_down, true)),
+        ("cursor.add_next_occurrence", "Cursor: Adicionar próxima ocorrência", Editor, shortcut(Modifiers::COMMAND, Key::D), |app, _| {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.editor_state.add_next_occurrence(&tab.content);
+            }
+        }),
+        ("cursor.clear_extra", "Cursor: Manter só o cursor principal", Editor, shortcut(Modifiers::NONE, Key::Escape), |app, _| {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.editor_state.clear_extra_carets();
+            }
+        }),
+    ];
+
+    for (id, title, context, keybinding, handler) in commands {
+        registry.register(Command { id, title, context, keybinding, handler });
+    }
+}
+
+/// Aplica uma edição à aba selecionada; `edit` retorna se o texto mudou.
+fn edit_current_tab(app: &mut MyApp, edit: impl FnOnce(&mut TextEditor, &mut Rope) -> bool) {
+    if let Some(tab) = app.current_tab_mut()
+        && edit(&mut tab.editor_state, &mut tab.content)
+    {
+        tab.is_modified = true;
+    }
+}
+
+/// Move os cursores; com `select`, estende a seleção (Shift+seta).
+fn move_cursor(app: &mut MyApp, step: fn(&mut TextEditor, &Rope), select: bool) {
+    let Some(tab) = app.current_tab_mut() else {
+        return;
+    };
+    step(&mut tab.editor_state, &tab.content);
+    if select {
+        tab.editor_state.extend_selection();
+    } else {
+        tab.editor_state.clear_selection();
+    }
+}
+
+/// Copia (ou recorta) a seleção; com vários cursores, um trecho por linha.
+fn copy_selection(app: &mut MyApp, ctx: &egui::Context, cut: bool) {
+    edit_current_tab(app, |editor, content| {
+        let selected_texts = editor.selected_texts(content);
+        if selected_texts.is_empty() {
+            return false;
+        }
+        ctx.copy_text(selected_texts.join("\n"));
+        if cut {
+            editor.delete_selected_text(content);
+        }
+        cut
+    });
+}
+
+/// Liga ou desliga o modo Vim, voltando ao modo normal.
+fn toggle_vim(app: &mut MyApp) {
+    app.vim_enabled = !app.vim_enabled;
+    if let Some(tab) = app.selected_tab_idx.and_then(|idx| app.open_tabs.get_mut(idx)) {
+        app.vim.reset(&mut tab.editor_state);
+    }
+    eprintln!("Modo Vim {}", if app.vim_enabled { "ativado" } else { "desativado" });
+}
+
+/// Abre a escolha de codificação para a aba selecionada.
+fn open_encoding_picker(app: &mut MyApp, mode: EncodingPickerMode) {
+    if app.selected_tab_idx.is_some() {
+        app.encoding_picker.open(mode);
+    }
+}
+
+fn open_find_bar(app: &mut MyApp, replace: bool) {
+    if let Some(tab) = app.current_tab_mut() {
+        tab.find.open(replace, &tab.content, &tab.editor_state);
+    }
+}
+
+fn run_find_action(app: &mut MyApp, action: FindAction) {
+    if let Some(tab) = app.current_tab_mut()
+        && tab.find.is_open
+    {
+        tab.find.apply(action, &mut tab.content, &mut tab.editor_state);
+    }
+}
+
+/// Abre o arquivo de atalhos do usuário em uma aba, criando-o com um modelo comentado
+/// se ele ainda não existe.
+fn open_keybindings_file(app: &mut MyApp) {
+    let Some(path) = app.keymap.path().map(Path::to_path_buf) else {
+        eprintln!("Pasta de configuração do usuário não encontrada.");
+        return;
+    };
+    if !path.exists() {
+        let created = path.parent().map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, KEYBINDINGS_TEMPLATE));
+        if let Err(e) = created {
+            eprintln!("Falha ao criar {}: {}", path.display(), e);
+            return;
+        }
+    }
+    app.open_file(&path);
+}
+
+/// Abre o arquivo de configurações do usuário em uma aba, criando-o com os valores
+/// atuais se ainda não existir.
+fn open_user_settings_file(app: &mut MyApp) {
+    let Some(path) = app.settings.user_path().map(Path::to_path_buf) else {
+        eprintln!("Pasta de configuração do usuário não encontrada.");
+        return;
+    };
+    if !path.exists() {
+        let user = app.settings.user.clone();
+        if let Err(e) = app.settings.save_user(&user) {
+            eprintln!("Falha ao criar {}: {}", path.display(), e);
+            return;
+        }
+    }
+    app.open_file(&path);
+}
+
+/// Abre `.lcode/settings.toml` do projeto aberto, criando-o com um modelo comentado.
+fn open_workspace_settings_file(app: &mut MyApp) {
+    let Some(path) = app.settings.workspace_path().map(Path::to_path_buf) else {
+        eprintln!("Nenhum diretório aberto.");
+        return;
+    };
+    if !path.exists() {
+        let created = path.parent().map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, WORKSPACE_SETTINGS_TEMPLATE));
+        if let Err(e) = created {
+            eprintln!("Falha ao criar {}: {}", path.display(), e);
+            return;
+        }
+    }
+    app.open_file(&path);
+}
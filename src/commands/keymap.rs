@@ -0,0 +1,550 @@
+// src/commands/keymap.rs
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers};
+use serde::Deserialize;
+
+use crate::commands::registry::{CommandContext, CommandRegistry};
+use crate::core::paths::user_config_dir;
+
+/// Intervalo entre as verificações de alteração do arquivo de atalhos.
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Tempo máximo entre as teclas de uma sequência; depois dele a sequência é abandonada.
+pub const SEQUENCE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Contextos na ordem de preferência: atalhos mais específicos vencem os globais.
+const CONTEXT_PRIORITY: [CommandContext; 4] = [
+    CommandContext::FindBar,
+    CommandContext::Editor,
+    CommandContext::Terminal,
+    CommandContext::Global,
+];
+
+/// Atalhos globais que continuam valendo com o foco no terminal. Os demais (Ctrl+P,
+/// Ctrl+F, Ctrl+S...) chegam ao shell como teclas.
+const TERMINAL_GLOBAL_COMMANDS: [&str; 2] = ["view.command_palette", "view.toggle_terminal"];
+
+/// Modelo gravado quando o usuário abre o arquivo de atalhos pela primeira vez.
+pub const KEYBINDINGS_TEMPLATE: &str = r#"# Atalhos do lcode. O arquivo é relido automaticamente ao ser salvo.
+#
+# [[bindings]]
+# keys = "ctrl+k ctrl+c"      # Sequências separadas por espaço
+# command = "edit.copy"       # Id do comando (veja a paleta, Ctrl+Shift+P)
+# when = "editor"             # global, editor, terminal ou find_bar (padrão: o do comando)
+#
+# Um "-" antes do id remove o atalho padrão do comando:
+# [[bindings]]
+# keys = "ctrl+d"
+# command = "-cursor.add_next_occurrence"
+"#;
+
+/// Caminho do arquivo de atalhos do usuário: `~/.config/lcode/keybindings.toml`.
+pub fn user_keybindings_path() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join("keybindings.toml"))
+}
+
+/// Conteúdo de `keybindings.toml`.
+#[derive(Debug, Default, Deserialize)]
+struct KeybindingsFile {
+    #[serde(default)]
+    bindings: Vec<BindingDefinition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BindingDefinition {
+    keys: Option<String>, // Opcional só para remoções: sem ele, todos os atalhos do comando saem
+    command: String,
+    when: Option<String>,
+}
+
+/// Atalho (de uma ou mais teclas) associado a um comando em um contexto.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub keys: Vec<KeyboardShortcut>,
+    pub command: &'static str,
+    pub context: CommandContext,
+    pub user_defined: bool, // Veio do arquivo do usuário, não dos atalhos padrão
+}
+
+/// Onde está o foco neste frame, para escolher quais contextos valem.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FocusState {
+    pub editor: bool,
+    pub terminal: bool,
+    pub find_bar: bool, // Um campo da barra de busca da aba selecionada tem o foco
+}
+
+impl FocusState {
+    fn allows(&self, binding: &Binding) -> bool {
+        match binding.context {
+            CommandContext::Global => !self.terminal || TERMINAL_GLOBAL_COMMANDS.contains(&binding.command),
+            CommandContext::Editor => self.editor,
+            CommandContext::Terminal => self.terminal,
+            CommandContext::FindBar => self.find_bar,
+        }
+    }
+}
+
+/// Resultado de uma tecla pressionada.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyPress {
+    Run(&'static str), // A sequência completou o atalho do comando
+    Pending,           // A tecla inicia (ou continua) uma sequência; aguardando a próxima
+    Unbound,           // A sequência em andamento não leva a nenhum comando e foi descartada; a tecla segue adiante
+    Ignored,           // Nenhum atalho usa essa tecla
+}
+
+/// Mapa de atalhos: os padrões do registro de comandos mais os do arquivo do usuário.
+///
+/// Atalhos do usuário substituem os padrões com as mesmas teclas no mesmo contexto.
+/// Erros de leitura e conflitos entre atalhos ficam em `errors` e `conflicts` para a
+/// interface mostrar; o mapa continua utilizável com o que foi possível ler.
+#[derive(Debug, Default)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+    pub errors: Vec<String>,
+    pub conflicts: Vec<String>,
+    pending: Vec<KeyboardShortcut>, // Teclas já pressionadas de uma sequência
+    pending_since: Option<Instant>, // Quando a última tecla da sequência foi pressionada
+    path: Option<PathBuf>,
+    loaded_modified: Option<SystemTime>, // Data de modificação do arquivo lido
+    last_check: Option<Instant>,
+}
+
+impl Keymap {
+    /// Monta o mapa a partir do registro e de `~/.config/lcode/keybindings.toml`.
+    pub fn load(registry: &CommandRegistry) -> Self {
+        Self::load_from(registry, user_keybindings_path())
+    }
+
+    pub fn load_from(registry: &CommandRegistry, path: Option<PathBuf>) -> Self {
+        let mut keymap = Self { path, ..Self::default() };
+        keymap.reload(registry);
+        keymap
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Relê o arquivo do usuário e refaz o mapa.
+    pub fn reload(&mut self, registry: &CommandRegistry) {
+        self.errors.clear();
+        self.pending.clear();
+        self.last_check = Some(Instant::now());
+        self.loaded_modified = self.path.as_deref().and_then(modified_time);
+
+        self.bindings = registry.all().iter()
+            .filter_map(|command| Some(Binding {
+                keys: vec![command.keybinding?],
+                command: command.id,
+                context: command.context,
+                user_defined: false,
+            }))
+            .collect();
+
+        let file = match self.path.as_deref().map(read_keybindings_file) {
+            Some(Ok(file)) => file,
+            Some(Err(e)) => {
+                self.errors.push(e.to_string());
+                KeybindingsFile::default()
+            },
+            None => KeybindingsFile::default(),
+        };
+        for (idx, definition) in file.bindings.iter().enumerate() {
+            if let Err(e) = self.apply_definition(registry, definition) {
+                self.errors.push(format!("Atalho #{}: {}", idx + 1, e));
+            }
+        }
+        self.conflicts = find_conflicts(&self.bindings);
+
+        for error in self.errors.iter().chain(&self.conflicts) {
+            eprintln!("Atalhos: {}", error);
+        }
+    }
+
+    /// Relê o arquivo se ele mudou desde a última leitura. Verifica no máximo uma vez por
+    /// segundo; retorna `true` se o mapa foi refeito.
+    pub fn reload_if_changed(&mut self, registry: &CommandRegistry) -> bool {
+        if self.last_check.is_some_and(|last| last.elapsed() < RELOAD_INTERVAL) {
+            return false;
+        }
+        self.last_check = Some(Instant::now());
+        if self.path.as_deref().and_then(modified_time) == self.loaded_modified {
+            return false;
+        }
+        self.reload(registry);
+        eprintln!("Atalhos recarregados de {:?}", self.path);
+        true
+    }
+
+    fn apply_definition(&mut self, registry: &CommandRegistry, definition: &BindingDefinition) -> Result<(), String> {
+        let (removal, id) = match definition.command.strip_prefix('-') {
+            Some(id) => (true, id),
+            None => (false, definition.command.as_str()),
+        };
+        let command = registry.get(id).ok_or_else(|| format!("comando desconhecido '{}'", id))?;
+        let context = match &definition.when {
+            Some(when) => parse_context(when).ok_or_else(|| format!("contexto desconhecido '{}'", when))?,
+            None => command.context,
+        };
+        let keys = definition.keys.as_deref().map(parse_key_sequence).transpose()?;
+
+        if removal {
+            self.bindings.retain(|binding| {
+                binding.command != command.id || keys.as_ref().is_some_and(|keys| *keys != binding.keys)
+            });
+            return Ok(());
+        }
+        let keys = keys.ok_or("falta o campo 'keys'")?;
+        // O atalho do usuário substitui o padrão com as mesmas teclas
+        self.bindings.retain(|binding| binding.user_defined || binding.context != context || binding.keys != keys);
+        self.bindings.push(Binding { keys, command: command.id, context, user_defined: true });
+        Ok(())
+    }
+
+    /// Atalhos de um comando, padrões e do usuário.
+    pub fn bindings_for<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a Binding> + 'a {
+        self.bindings.iter().filter(move |binding| binding.command == id)
+    }
+
+    /// Texto do primeiro atalho do comando, ex.: "Ctrl+K Ctrl+C".
+    pub fn shortcut_label(&self, ctx: &egui::Context, id: &str) -> Option<String> {
+        self.bindings_for(id).next().map(|binding| format_sequence(ctx, &binding.keys))
+    }
+
+    /// Sequência em andamento, ex.: "Ctrl+K", para a barra de status.
+    pub fn pending_label(&self, ctx: &egui::Context) -> Option<String> {
+        (!self.pending.is_empty() && !self.pending_expired(Instant::now())).then(|| format_sequence(ctx, &self.pending))
+    }
+
+    fn pending_expired(&self, now: Instant) -> bool {
+        self.pending_since.is_some_and(|since| now.saturating_duration_since(since) > SEQUENCE_TIMEOUT)
+    }
+
+    /// Processa uma tecla pressionada, continuando a sequência em andamento, se houver.
+    ///
+    /// O contexto mais específico com algum atalho que comece pela sequência decide: um
+    /// atalho completo é executado; senão, a sequência fica aguardando a próxima tecla
+    /// por até `SEQUENCE_TIMEOUT`.
+    pub fn press(&mut self, chord: KeyboardShortcut, focus: FocusState) -> KeyPress {
+        self.press_at(chord, focus, Instant::now())
+    }
+
+    fn press_at(&mut self, chord: KeyboardShortcut, focus: FocusState, now: Instant) -> KeyPress {
+        if self.pending_expired(now) {
+            self.pending.clear();
+        }
+        let mut sequence = std::mem::take(&mut self.pending);
+        let was_pending = !sequence.is_empty();
+        sequence.push(chord);
+
+        for context in CONTEXT_PRIORITY {
+            let mut candidates = self.bindings.iter()
+                .filter(|binding| binding.context == context && focus.allows(binding) && starts_with(&binding.keys, &sequence))
+                .peekable();
+            if candidates.peek().is_none() {
+                continue;
+            }
+            let mut longer = false;
+            for binding in candidates {
+                if binding.keys.len() == sequence.len() {
+                    return KeyPress::Run(binding.command);
+                }
+                longer = true;
+            }
+            if longer {
+                self.pending = sequence;
+                self.pending_since = Some(now);
+                return KeyPress::Pending;
+            }
+        }
+        if was_pending { KeyPress::Unbound } else { KeyPress::Ignored }
+    }
+
+    /// Processa um evento de tecla. Com o foco no editor, `Event::Copy` e `Event::Cut`
+    /// contam como Ctrl+C e Ctrl+X: o egui-winit os envia no lugar do `Event::Key`.
+    pub fn press_event(&mut self, event: &egui::Event, focus: FocusState) -> KeyPress {
+        let chord = match event {
+            egui::Event::Key { key, pressed: true, modifiers, .. } => KeyboardShortcut::new(*modifiers, *key),
+            egui::Event::Copy if focus.editor => KeyboardShortcut::new(Modifiers::COMMAND, Key::C),
+            egui::Event::Cut if focus.editor => KeyboardShortcut::new(Modifiers::COMMAND, Key::X),
+            _ => return KeyPress::Ignored,
+        };
+        self.press(chord, focus)
+    }
+
+    /// Abandona a sequência em andamento.
+    pub fn cancel_pending(&mut self) {
+        self.pending.clear();
+    }
+}
+
+/// `binding` começa com as teclas pressionadas em `pressed`.
+fn starts_with(binding: &[KeyboardShortcut], pressed: &[KeyboardShortcut]) -> bool {
+    binding.len() >= pressed.len()
+        && binding.iter().zip(pressed).all(|(expected, actual)| {
+            expected.logical_key == actual.logical_key && actual.modifiers.matches_exact(expected.modifiers)
+        })
+}
+
+fn format_sequence(ctx: &egui::Context, keys: &[KeyboardShortcut]) -> String {
+    keys.iter().map(|shortcut| ctx.format_shortcut(shortcut)).collect::<Vec<_>>().join(" ")
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Lê o arquivo de atalhos. A ausência do arquivo resulta em um mapa só com os padrões.
+fn read_keybindings_file(path: &Path) -> io::Result<KeybindingsFile> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(KeybindingsFile::default()),
+        Err(e) => return Err(e),
+    };
+    toml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))
+}
+
+/// Nome do contexto no campo `when` do arquivo.
+fn context_name(context: CommandContext) -> &'static str {
+    match context {
+        CommandContext::Global => "global",
+        CommandContext::Editor => "editor",
+        CommandContext::Terminal => "terminal",
+        CommandContext::FindBar => "find_bar",
+    }
+}
+
+fn parse_context(when: &str) -> Option<CommandContext> {
+    match when.trim().to_ascii_lowercase().as_str() {
+        "global" => Some(CommandContext::Global),
+        "editor" | "editor_focus" => Some(CommandContext::Editor),
+        "terminal" | "terminal_focus" => Some(CommandContext::Terminal),
+        "find_bar" | "find_bar_open" => Some(CommandContext::FindBar),
+        _ => None,
+    }
+}
+
+/// Lê uma sequência como "ctrl+k ctrl+c": teclas separadas por espaço, modificadores
+/// separados por `+`. `ctrl` e `cmd` são o mesmo modificador (Cmd no macOS).
+pub fn parse_key_sequence(text: &str) -> Result<Vec<KeyboardShortcut>, String> {
+    let keys = text.split_whitespace().map(parse_chord).collect::<Result<Vec<_>, _>>()?;
+    if keys.is_empty() {
+        return Err("atalho vazio".to_string());
+    }
+    Ok(keys)
+}
+
+fn parse_chord(chord: &str) -> Result<KeyboardShortcut, String> {
+    let mut parts: Vec<&str> = chord.split('+').collect();
+    let key_name = parts.pop().unwrap_or_default();
+    let mut modifiers = Modifiers::NONE;
+    for part in parts {
+        modifiers |= match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" | "cmd" | "command" => Modifiers::COMMAND,
+            "alt" | "option" => Modifiers::ALT,
+            "shift" => Modifiers::SHIFT,
+            _ => return Err(format!("modificador desconhecido '{}' em '{}'", part, chord)),
+        };
+    }
+    let key = parse_key(key_name).ok_or_else(|| format!("tecla desconhecida '{}' em '{}'", key_name, chord))?;
+    Ok(KeyboardShortcut::new(modifiers, key))
+}
+
+/// Nome de tecla sem diferenciar maiúsculas: "a", "f3", "pageup", "esc", "/". Os
+/// nomes do egui são todos ASCII; qualquer outro (ex.: "é") é uma tecla desconhecida.
+fn parse_key(name: &str) -> Option<Key> {
+    if name.is_empty() || !name.is_ascii() {
+        return None;
+    }
+    let mut capitalized = name.to_ascii_lowercase();
+    capitalized[..1].make_ascii_uppercase();
+    Key::ALL.iter().copied()
+        .find(|key| key.name().eq_ignore_ascii_case(name))
+        .or_else(|| Key::from_name(name))
+        .or_else(|| Key::from_name(&capitalized))
+}
+
+/// Atalhos que se atrapalham no mesmo contexto: as mesmas teclas para comandos
+/// diferentes, ou um atalho que é o começo de outro (e torna o mais longo inalcançável).
+fn find_conflicts(bindings: &[Binding]) -> Vec<String> {
+    let describe = |binding: &Binding| {
+        let keys: Vec<String> = binding.keys.iter()
+            .map(|shortcut| shortcut.format(&egui::ModifierNames::NAMES, false))
+            .collect();
+        keys.join(" ")
+    };
+    let mut conflicts = Vec::new();
+    for (idx, first) in bindings.iter().enumerate() {
+        for second in &bindings[idx + 1..] {
+            if first.context != second.context || first.command == second.command {
+                continue;
+            }
+            let (shorter, longer) = if first.keys.len() <= second.keys.len() { (first, second) } else { (second, first) };
+            if !starts_with(&longer.keys, &shorter.keys) {
+                continue;
+            }
+            conflicts.push(if shorter.keys.len() == longer.keys.len() {
+                format!("{} está associado a '{}' e a '{}' (contexto {})", describe(first), first.command, second.command, context_name(first.context))
+            } else {
+                format!("{} ('{}') impede {} ('{}') (contexto {})", describe(shorter), shorter.command, describe(longer), longer.command, context_name(first.context))
+            });
+        }
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::builtin::register_builtin_commands;
+
+    const EDITOR: FocusState = FocusState { editor: true, terminal: false, find_bar: false };
+    const TERMINAL: FocusState = FocusState { editor: false, terminal: true, find_bar: false };
+    const FIND_BAR: FocusState = FocusState { editor: false, terminal: false, find_bar: true };
+
+    fn ctrl(key: Key) -> KeyboardShortcut {
+        KeyboardShortcut::new(Modifiers::COMMAND, key)
+    }
+
+    /// Mapa só com os atalhos padrão, mais as definições `(keys, command)` do usuário.
+    fn keymap_with(definitions: &[(&str, &str)]) -> Keymap {
+        let mut registry = CommandRegistry::new();
+        register_builtin_commands(&mut registry);
+        let mut keymap = Keymap::load_from(&registry, None);
+        for (keys, command) in definitions {
+            let definition = BindingDefinition { keys: Some(keys.to_string()), command: command.to_string(), when: None };
+            keymap.apply_definition(&registry, &definition).unwrap();
+        }
+        keymap.conflicts = find_conflicts(&keymap.bindings);
+        keymap
+    }
+
+    #[test]
+    fn copy_and_cut_events_run_in_the_editor() {
+        let mut keymap = keymap_with(&[]);
+        assert_eq!(keymap.press_event(&egui::Event::Copy, EDITOR), KeyPress::Run("edit.copy"));
+        assert_eq!(keymap.press_event(&egui::Event::Cut, EDITOR), KeyPress::Run("edit.cut"));
+        assert_eq!(keymap.press_event(&egui::Event::Copy, FocusState::default()), KeyPress::Ignored);
+    }
+
+    #[test]
+    fn copy_event_completes_a_sequence() {
+        let mut keymap = keymap_with(&[("ctrl+k ctrl+c", "edit.toggle_vim")]);
+        let ctrl_k = egui::Event::Key {
+            key: Key::K,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: Modifiers::COMMAND,
+        };
+        assert_eq!(keymap.press_event(&ctrl_k, EDITOR), KeyPress::Pending);
+        assert_eq!(keymap.press_event(&egui::Event::Copy, EDITOR), KeyPress::Run("edit.toggle_vim"));
+    }
+
+    #[test]
+    fn parses_sequences_and_modifiers() {
+        assert_eq!(parse_key_sequence("ctrl+k ctrl+c"), Ok(vec![ctrl(Key::K), ctrl(Key::C)]));
+        assert_eq!(
+            parse_key_sequence("Ctrl+Shift+P"),
+            Ok(vec![KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::P)]),
+        );
+        assert_eq!(parse_key_sequence("cmd+alt+pageup"), Ok(vec![KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::ALT, Key::PageUp)]));
+        assert_eq!(parse_key_sequence("  f3  "), Ok(vec![KeyboardShortcut::new(Modifiers::NONE, Key::F3)]));
+        assert_eq!(parse_key_sequence("esc"), Ok(vec![KeyboardShortcut::new(Modifiers::NONE, Key::Escape)]));
+        assert_eq!(parse_key_sequence("ctrl+/"), Ok(vec![ctrl(Key::Slash)]));
+    }
+
+    #[test]
+    fn rejects_invalid_sequences() {
+        assert_eq!(parse_key_sequence(" "), Err("atalho vazio".to_string()));
+        assert_eq!(parse_key_sequence("hyper+a"), Err("modificador desconhecido 'hyper' em 'hyper+a'".to_string()));
+        assert_eq!(parse_key_sequence("ctrl+"), Err("tecla desconhecida '' em 'ctrl+'".to_string()));
+        assert_eq!(parse_key_sequence("ctrl+é"), Err("tecla desconhecida 'é' em 'ctrl+é'".to_string()));
+        assert!(parse_key_sequence("ctrl+k nope").is_err());
+    }
+
+    #[test]
+    fn sequence_waits_for_the_next_key() {
+        let mut keymap = keymap_with(&[("ctrl+k ctrl+c", "edit.toggle_vim")]);
+        assert_eq!(keymap.press(ctrl(Key::K), EDITOR), KeyPress::Pending);
+        assert_eq!(keymap.press(ctrl(Key::C), EDITOR), KeyPress::Run("edit.toggle_vim"));
+        // Sem a primeira tecla, Ctrl+C é o atalho de sempre
+        assert_eq!(keymap.press(ctrl(Key::C), EDITOR), KeyPress::Run("edit.copy"));
+    }
+
+    #[test]
+    fn wrong_second_key_abandons_the_sequence() {
+        let mut keymap = keymap_with(&[("ctrl+k ctrl+c", "edit.toggle_vim")]);
+        assert_eq!(keymap.press(ctrl(Key::K), EDITOR), KeyPress::Pending);
+        assert_eq!(keymap.press(ctrl(Key::Q), EDITOR), KeyPress::Unbound);
+        assert_eq!(keymap.press(ctrl(Key::C), EDITOR), KeyPress::Run("edit.copy"));
+        assert_eq!(keymap.press(ctrl(Key::Q), EDITOR), KeyPress::Ignored);
+    }
+
+    #[test]
+    fn sequence_expires_after_the_timeout() {
+        let mut keymap = keymap_with(&[("ctrl+k ctrl+c", "edit.toggle_vim")]);
+        let start = Instant::now();
+        assert_eq!(keymap.press_at(ctrl(Key::K), EDITOR, start), KeyPress::Pending);
+        let late = start + SEQUENCE_TIMEOUT + Duration::from_millis(1);
+        assert_eq!(keymap.press_at(ctrl(Key::C), EDITOR, late), KeyPress::Run("edit.copy"));
+
+        assert_eq!(keymap.press_at(ctrl(Key::K), EDITOR, late), KeyPress::Pending);
+        assert_eq!(keymap.press_at(ctrl(Key::C), EDITOR, late + SEQUENCE_TIMEOUT), KeyPress::Run("edit.toggle_vim"));
+    }
+
+    #[test]
+    fn specific_context_wins_over_global() {
+        let mut keymap = keymap_with(&[]);
+        let escape = KeyboardShortcut::new(Modifiers::NONE, Key::Escape);
+        assert_eq!(keymap.press(escape, FIND_BAR), KeyPress::Run("find.close"));
+        assert_eq!(keymap.press(escape, EDITOR), KeyPress::Run("cursor.clear_extra"));
+        // F3 só vale com o foco na barra de busca
+        assert_eq!(keymap.press(KeyboardShortcut::new(Modifiers::NONE, Key::F3), EDITOR), KeyPress::Ignored);
+
+        let mut keymap = keymap_with(&[("ctrl+s", "edit.undo")]);
+        assert_eq!(keymap.press(ctrl(Key::S), EDITOR), KeyPress::Run("edit.undo"));
+        assert_eq!(keymap.press(ctrl(Key::S), FocusState::default()), KeyPress::Run("file.save"));
+    }
+
+    #[test]
+    fn terminal_focus_keeps_only_a_few_globals() {
+        let mut keymap = keymap_with(&[]);
+        for key in [Key::P, Key::F, Key::H, Key::S] {
+            assert_eq!(keymap.press(ctrl(key), TERMINAL), KeyPress::Ignored, "{:?}", key);
+        }
+        let palette = KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::P);
+        assert_eq!(keymap.press(palette, TERMINAL), KeyPress::Run("view.command_palette"));
+        assert_eq!(keymap.press(ctrl(Key::P), EDITOR), KeyPress::Run("file.quick_open"));
+    }
+
+    #[test]
+    fn user_binding_replaces_default_with_the_same_keys() {
+        let keymap = keymap_with(&[("ctrl+s", "view.toggle_problems")]);
+        assert!(keymap.conflicts.is_empty());
+        assert_eq!(keymap.bindings_for("file.save").count(), 0);
+    }
+
+    #[test]
+    fn reports_conflicts_in_the_same_context() {
+        let keymap = keymap_with(&[("ctrl+j", "view.toggle_problems"), ("ctrl+j", "view.toggle_project_search")]);
+        assert_eq!(keymap.conflicts, vec![
+            "Ctrl+J está associado a 'view.toggle_problems' e a 'view.toggle_project_search' (contexto global)".to_string(),
+        ]);
+
+        let keymap = keymap_with(&[("ctrl+k", "view.toggle_problems"), ("ctrl+k ctrl+j", "view.toggle_project_search")]);
+        assert_eq!(keymap.conflicts, vec![
+            "Ctrl+K ('view.toggle_problems') impede Ctrl+K Ctrl+J ('view.toggle_project_search') (contexto global)".to_string(),
+        ]);
+
+        // Contextos diferentes não conflitam: o mais específico vence
+        let keymap = keymap_with(&[]);
+        assert!(keymap.conflicts.is_empty(), "{:?}", keymap.conflicts);
+    }
+}
@@ -0,0 +1,5 @@
+// src/commands/mod.rs
+
+pub mod registry; // Registro de comandos com nome, atalho e ação
+pub mod builtin;  // Comandos embutidos do editor
+pub mod keymap;   // Atalhos do usuário, sequências de teclas e contextos
@@ -0,0 +1,78 @@
+// src/commands/registry.rs
+
+use eframe::egui;
+
+use crate::ui::app::MyApp;
+
+/// Ação de um comando. Recebe a aplicação inteira: comandos do editor agem na aba
+/// selecionada.
+pub type CommandHandler = fn(&mut MyApp, &egui::Context);
+
+/// Onde o atalho de um comando vale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandContext {
+    Global,   // Em qualquer lugar da janela; no terminal, só alguns (veja o keymap)
+    Editor,   // Só com o foco no texto do editor
+    Terminal, // Só com o foco em uma sessão do terminal
+    FindBar,  // Só com o foco na barra de busca da aba selecionada
+}
+
+/// Comando com nome, disponível na paleta e (opcionalmente) por atalho.
+#[derive(Clone)]
+pub struct Command {
+    pub id: &'static str, // Identificador estável, ex.: "file.save"
+    pub title: &'static str, // Nome mostrado na paleta
+    pub context: CommandContext,
+    pub keybinding: Option<egui::KeyboardShortcut>, // Atalho padrão; o usuário pode trocá-lo no keymap
+    pub handler: CommandHandler,
+}
+
+impl std::fmt::Debug for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Command")
+            .field("id", &self.id)
+            .field("context", &self.context)
+            .field("keybinding", &self.keybinding)
+            .finish()
+    }
+}
+
+/// Todos os comandos da aplicação, na ordem em que foram registrados.
+#[derive(Debug, Default)]
+pub struct CommandRegistry {
+    commands: Vec<Command>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra um comando; um comando com o mesmo id é substituído.
+    pub fn register(&mut self, command: Command) {
+        match self.commands.iter_mut().find(|existing| existing.id == command.id) {
+            Some(existing) => *existing = command,
+            None => self.commands.push(command),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Command> {
+        self.commands.iter().find(|command| command.id == id)
+    }
+
+    pub fn all(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Troca (ou remove, com `None`) o atalho de um comando. Retorna `false` se o
+    /// comando não existe.
+    pub fn set_keybinding(&mut self, id: &str, keybinding: Option<egui::KeyboardShortcut>) -> bool {
+        match self.commands.iter_mut().find(|command| command.id == id) {
+            Some(command) => {
+                command.keybinding = keybinding;
+                true
+            }
+            None => false,
+        }
+    }
+}
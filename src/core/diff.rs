@@ -0,0 +1,130 @@
+// src/core/diff.rs
+
+use std::cmp::max;
+
+/// Acima deste número de células (linhas antigas × novas no trecho que difere) a
+/// comparação não procura a maior subsequência comum: o trecho inteiro sai como
+/// removido e adicionado.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// Uma linha do resultado da comparação.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str), // Só no texto antigo
+    Added(&'a str),   // Só no texto novo
+}
+
+/// Compara dois textos linha a linha. As linhas iguais no começo e no fim são
+/// separadas antes, então textos quase iguais saem baratos mesmo se forem grandes.
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let prefix = old_lines.iter().zip(&new_lines).take_while(|(a, b)| a == b).count();
+    let suffix = old_lines[prefix..].iter().rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old_lines[prefix..old_lines.len() - suffix];
+    let new_middle = &new_lines[prefix..new_lines.len() - suffix];
+
+    let mut result: Vec<DiffLine> = old_lines[..prefix].iter().map(|line| DiffLine::Same(line)).collect();
+    if old_middle.len() * new_middle.len() > MAX_LCS_CELLS {
+        result.extend(old_middle.iter().map(|line| DiffLine::Removed(line)));
+        result.extend(new_middle.iter().map(|line| DiffLine::Added(line)));
+    } else {
+        diff_middle(old_middle, new_middle, &mut result);
+    }
+    result.extend(old_lines[old_lines.len() - suffix..].iter().map(|line| DiffLine::Same(line)));
+    result
+}
+
+/// Maior subsequência comum por programação dinâmica: `lengths[i][j]` é o tamanho da
+/// subsequência comum de `old[i..]` e `new[j..]`.
+fn diff_middle<'a>(old: &[&'a str], new: &[&'a str], result: &mut Vec<DiffLine<'a>>) {
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                max(lengths[(i + 1) * width + j], lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            result.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            result.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            result.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    result.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    result.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use DiffLine::{Added, Removed, Same};
+
+    #[test]
+    fn equal_texts_are_all_same() {
+        assert_eq!(diff_lines("a\nb\n", "a\nb\n"), vec![Same("a"), Same("b")]);
+        assert_eq!(diff_lines("", ""), vec![]);
+    }
+
+    #[test]
+    fn from_or_to_empty() {
+        assert_eq!(diff_lines("", "a\nb"), vec![Added("a"), Added("b")]);
+        assert_eq!(diff_lines("a\nb", ""), vec![Removed("a"), Removed("b")]);
+    }
+
+    #[test]
+    fn insertion_and_removal_keep_prefix_and_suffix() {
+        assert_eq!(diff_lines("a\nb\nc", "a\nb\nx\nc"), vec![Same("a"), Same("b"), Added("x"), Same("c")]);
+        assert_eq!(diff_lines("a\nb\nc", "a\nc"), vec![Same("a"), Removed("b"), Same("c")]);
+    }
+
+    #[test]
+    fn changed_line_is_removed_then_added() {
+        assert_eq!(diff_lines("a\nb\nc", "a\nB\nc"), vec![Same("a"), Removed("b"), Added("B"), Same("c")]);
+    }
+
+    #[test]
+    fn middle_uses_longest_common_subsequence() {
+        assert_eq!(
+            diff_lines("p\nx\ny\nz\ns", "p\ny\nw\nz\nx\ns"),
+            vec![Same("p"), Removed("x"), Same("y"), Added("w"), Same("z"), Added("x"), Same("s")],
+        );
+    }
+
+    #[test]
+    fn repeated_lines_at_the_edges() {
+        assert_eq!(diff_lines("a\na", "a\na\na"), vec![Same("a"), Same("a"), Added("a")]);
+        assert_eq!(diff_lines("a\nb\na", "a\na"), vec![Same("a"), Removed("b"), Same("a")]);
+    }
+
+    #[test]
+    fn large_middle_falls_back_to_remove_and_add() {
+        let old: String = (0..2001).map(|i| format!("old {}\n", i)).collect();
+        let new: String = (0..2001).map(|i| format!("new {}\n", i)).collect();
+        let old = format!("início\n{}fim\n", old);
+        let new = format!("início\n{}fim\n", new);
+        let diff = diff_lines(&old, &new);
+        assert_eq!(diff.len(), 2 + 2 * 2001);
+        assert_eq!(diff[0], Same("início"));
+        assert!(diff[1..2002].iter().all(|line| matches!(line, Removed(_))));
+        assert!(diff[2002..4003].iter().all(|line| matches!(line, Added(_))));
+        assert_eq!(diff[4003], Same("fim"));
+    }
+}
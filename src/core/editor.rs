@@ -1,20 +1,25 @@
 // src/core/editor.rs
 
+use std::ops::Range;
+
 use egui::Vec2;
 use ropey::Rope;
+use serde::{Deserialize, Serialize};
+
+use crate::core::line_ending::{line_break_len, LineEnding};
 // Corrected imports for undo crate v0.52.0
-use undo::{Record, Edit};
+use undo::{Record, Edit, Merged};
 // Removed: use std::result::Result; // This is no longer needed as Edit trait returns Self::Output
 
 /// Representa a posição do cursor no texto (linha, coluna de caractere).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct Cursor {
     pub line: usize,
     pub char_idx: usize, // Índice do caractere dentro da linha
 }
 
 /// Representa uma seleção de texto (início e fim do cursor).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct Selection {
     pub start: Cursor,
     pub end: Cursor,
@@ -37,6 +42,72 @@ impl Selection {
 }
 
 
+/// Descrição de uma alteração no texto, no formato que o tree-sitter espera para
+/// reanalisar de forma incremental. Posições são (linha, coluna em bytes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextChange {
+    pub start_byte: usize,
+    pub old_end_byte: usize,
+    pub new_end_byte: usize,
+    pub start_position: (usize, usize),
+    pub old_end_position: (usize, usize),
+    pub new_end_position: (usize, usize),
+    pub total_lines: usize, // Linhas do documento logo depois desta alteração
+}
+
+impl TextChange {
+    /// Linha em que a alteração começa.
+    pub fn first_line(&self) -> usize {
+        self.start_position.0
+    }
+
+    /// Quantas linhas o documento ganhou (positivo) ou perdeu (negativo).
+    pub fn line_delta(&self) -> isize {
+        self.new_end_position.0 as isize - self.old_end_position.0 as isize
+    }
+}
+
+/// Posição (linha, coluna em bytes) de um índice de caractere.
+fn byte_position(target: &Rope, char_idx: usize) -> (usize, usize) {
+    let line = target.char_to_line(char_idx);
+    (line, target.char_to_byte(char_idx) - target.line_to_byte(line))
+}
+
+/// Insere `text` em `at` e descreve a alteração.
+fn insert_into(target: &mut Rope, at: usize, text: &Rope) -> TextChange {
+    let start_byte = target.char_to_byte(at);
+    let start_position = byte_position(target, at);
+    target.insert(at, &text.to_string());
+    let end = at + text.len_chars();
+    TextChange {
+        start_byte,
+        old_end_byte: start_byte,
+        new_end_byte: target.char_to_byte(end),
+        start_position,
+        old_end_position: start_position,
+        new_end_position: byte_position(target, end),
+        total_lines: target.len_lines(),
+    }
+}
+
+/// Remove `len` caracteres a partir de `at` e descreve a alteração.
+fn remove_from(target: &mut Rope, at: usize, len: usize) -> TextChange {
+    let start_byte = target.char_to_byte(at);
+    let start_position = byte_position(target, at);
+    let old_end_byte = target.char_to_byte(at + len);
+    let old_end_position = byte_position(target, at + len);
+    target.remove(at..at + len);
+    TextChange {
+        start_byte,
+        old_end_byte,
+        new_end_byte: start_byte,
+        start_position,
+        old_end_position,
+        new_end_position: start_position,
+        total_lines: target.len_lines(),
+    }
+}
+
 /// Comando de edição para o sistema de desfazer/refazer.
 #[derive(Debug)]
 enum EditorCommand {
@@ -48,35 +119,72 @@ enum EditorCommand {
         at_char_idx: usize,
         text: Rope, // Usar Rope para o texto removido
     },
-    // Futuras operações (Substituir, etc.)
+    /// Edições feitas juntas (uma por cursor), desfeitas em um único passo. Cada
+    /// posição já considera as edições anteriores do lote.
+    Batch(Vec<EditorCommand>),
+    /// Edição que continua o passo de desfazer anterior (grupo aberto com
+    /// `TextEditor::begin_undo_group`); é juntada a ele pelo `merge`.
+    Join(Box<EditorCommand>),
 }
 
 // Corrected UndoCmd (now Edit) implementation for undo v0.52.0
 impl Edit for EditorCommand {
     type Target = Rope; // Define the target type for this command
-    type Output = (); // The output of the edit operation (often () for side effects)
+    type Output = Vec<TextChange>; // Alterações feitas, repassadas ao realce incremental
 
     fn edit(&mut self, target: &mut Self::Target) -> Self::Output {
         match self {
-            EditorCommand::Insert { at_char_idx, text } => {
-                target.insert(*at_char_idx, &text.to_string());
-            },
-            EditorCommand::Delete { at_char_idx, text } => {
-                let end_idx = *at_char_idx + text.len_chars();
-                target.remove(*at_char_idx..end_idx);
-            },
+            EditorCommand::Insert { at_char_idx, text } => vec![insert_into(target, *at_char_idx, text)],
+            EditorCommand::Delete { at_char_idx, text } => vec![remove_from(target, *at_char_idx, text.len_chars())],
+            EditorCommand::Batch(commands) => commands.iter_mut().flat_map(|command| command.edit(target)).collect(),
+            EditorCommand::Join(command) => command.edit(target),
         }
     }
 
     fn undo(&mut self, target: &mut Self::Target) -> Self::Output {
         match self {
-            EditorCommand::Insert { at_char_idx, text } => {
-                let end_idx = *at_char_idx + text.len_chars();
-                target.remove(*at_char_idx..end_idx);
-            },
-            EditorCommand::Delete { at_char_idx, text } => {
-                target.insert(*at_char_idx, &text.to_string());
-            },
+            EditorCommand::Insert { at_char_idx, text } => vec![remove_from(target, *at_char_idx, text.len_chars())],
+            EditorCommand::Delete { at_char_idx, text } => vec![insert_into(target, *at_char_idx, text)],
+            EditorCommand::Batch(commands) => commands.iter_mut().rev().flat_map(|command| command.undo(target)).collect(),
+            EditorCommand::Join(command) => command.undo(target),
+        }
+    }
+
+    fn merge(&mut self, other: Self) -> Merged<Self> {
+        let EditorCommand::Join(next) = other else {
+            return Merged::No(other);
+        };
+        match self {
+            EditorCommand::Batch(commands) => commands.push(*next),
+            _ => {
+                let first = std::mem::replace(self, EditorCommand::Batch(Vec::new()));
+                *self = EditorCommand::Batch(vec![first, *next]);
+            }
+        }
+        Merged::Yes
+    }
+}
+
+/// Um cursor adicional, com a própria seleção.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Caret {
+    pub cursor: Cursor,
+    pub selection: Option<Selection>,
+}
+
+impl Caret {
+    /// Trecho selecionado em índices de caractere do Rope (vazio sem seleção).
+    fn char_range(&self, content: &Rope) -> Range<usize> {
+        let to_char = |cursor: Cursor| content.line_to_char(cursor.line) + cursor.char_idx;
+        match self.selection.filter(Selection::is_active) {
+            Some(selection) => {
+                let normalized = selection.normalized();
+                to_char(normalized.start)..to_char(normalized.end)
+            }
+            None => {
+                let at = to_char(self.cursor);
+                at..at
+            }
         }
     }
 }
@@ -86,10 +194,16 @@ impl Edit for EditorCommand {
 pub struct TextEditor {
     pub cursor: Cursor,
     pub selection: Option<Selection>, // None se não houver seleção
+    pub extra_carets: Vec<Caret>, // Cursores além do principal (multi-cursor)
     pub scroll_offset: Vec2, // Para controlar a posição de rolagem
+    pub scroll_to_cursor: bool, // Pede ao painel que role até o cursor no próximo frame
+    pub line_ending: LineEnding, // Quebra inserida por `new_line`, a mesma do arquivo
     
     // Histórico de desfazer/refazer
     undo_record: Record<EditorCommand>,
+    undo_group: Option<bool>, // Grupo aberto; `true` depois da primeira edição dele
+    pending_changes: Vec<TextChange>, // Alterações ainda não vistas pelo painel
+    version: u64, // Muda a cada alteração do texto (edição, desfazer ou refazer)
 }
 
 impl TextEditor {
@@ -97,127 +211,232 @@ impl TextEditor {
         Self {
             cursor: Cursor::default(),
             selection: None,
+            extra_carets: Vec::new(),
             scroll_offset: Vec2::ZERO,
+            scroll_to_cursor: false,
+            line_ending: LineEnding::default(),
             undo_record: Record::new(),
+            undo_group: None,
+            pending_changes: Vec::new(),
+            version: 0,
         }
     }
 
-    // Métodos de manipulação de texto (operam no Rope da EditorTab pai)
+    // Métodos de manipulação de texto (operam no Rope da EditorTab pai). Todos valem
+    // para todos os cursores e são desfeitos em um único passo.
     pub fn insert_char(&mut self, content: &mut Rope, ch: char) {
-        // Clear selection first if active, and delete selected text
-        if self.selection.is_some() {
-            self.delete_selected_text(content);
-        }
-
-        let current_char_idx_in_rope = content.line_to_char(self.cursor.line) + self.cursor.char_idx;
-        self.undo_record.edit(content, EditorCommand::Insert {
-            at_char_idx: current_char_idx_in_rope,
-            text: Rope::from(ch.to_string()),
-        });
-        self.move_cursor_right(content);
-        self.selection = None; // Limpa seleção após inserção
+        self.insert_text(content, ch.encode_utf8(&mut [0; 4]));
     }
 
     pub fn insert_text(&mut self, content: &mut Rope, text: &str) {
-        // Clear selection first if active, and delete selected text
-        if self.selection.is_some() {
-            self.delete_selected_text(content);
+        self.replace_at_carets(content, |_, range| Some((range, text.to_string())));
+    }
+
+    /// Cola um trecho por cursor (ex.: texto copiado de vários cursores). Se o número de
+    /// trechos não bate com o de cursores, cada cursor recebe o texto inteiro.
+    pub fn insert_texts(&mut self, content: &mut Rope, texts: &[String]) {
+        if texts.len() != self.carets().len() {
+            self.insert_text(content, &texts.join("\n"));
+            return;
         }
+        let mut texts = texts.iter();
+        self.replace_at_carets(content, |_, range| Some((range, texts.next()?.clone())));
+    }
 
-        let current_char_idx_in_rope = content.line_to_char(self.cursor.line) + self.cursor.char_idx;
-        self.undo_record.edit(content, EditorCommand::Insert {
-            at_char_idx: current_char_idx_in_rope,
-            text: Rope::from(text),
+    pub fn delete_char_before_cursor(&mut self, content: &mut Rope) {
+        self.replace_at_carets(content, |content, range| {
+            if !range.is_empty() {
+                Some((range, String::new()))
+            } else if range.start > 1 && content.slice(range.start - 2..range.start) == "\r\n" {
+                // CRLF é uma quebra só; apagar metade deixaria um `\r` solto
+                Some((range.start - 2..range.start, String::new()))
+            } else if range.start > 0 {
+                Some((range.start - 1..range.start, String::new()))
+            } else {
+                None
+            }
         });
-        // Move o cursor para o final do texto inserido
-        let mut new_line = self.cursor.line;
-        let mut new_char_idx = self.cursor.char_idx;
-
-        for (_, c) in text.chars().enumerate() { // Fixed: `i` replaced with `_`
-            if c == '\n' {
-                new_line += 1;
-                new_char_idx = 0;
+    }
+
+    pub fn delete_char_after_cursor(&mut self, content: &mut Rope) {
+        self.replace_at_carets(content, |content, range| {
+            if !range.is_empty() {
+                Some((range, String::new()))
+            } else if range.start + 1 < content.len_chars() && content.slice(range.start..range.start + 2) == "\r\n" {
+                Some((range.start..range.start + 2, String::new()))
+            } else if range.start < content.len_chars() {
+                // Cursor não se move após delete "para frente"
+                Some((range.start..range.start + 1, String::new()))
             } else {
-                new_char_idx += 1;
+                None
+            }
+        });
+    }
+
+    pub fn delete_selected_text(&mut self, content: &mut Rope) {
+        self.replace_at_carets(content, |_, range| (!range.is_empty()).then(|| (range, String::new())));
+    }
+
+    /// Quebra a linha em cada cursor, com o fim de linha do arquivo.
+    pub fn new_line(&mut self, content: &mut Rope) {
+        let line_ending = self.line_ending;
+        self.insert_text(content, line_ending.as_str());
+    }
+
+    /// Texto selecionado em cada cursor que tem seleção, na ordem do documento.
+    pub fn selected_texts(&self, content: &Rope) -> Vec<String> {
+        let mut ranges: Vec<Range<usize>> = self.carets().iter()
+            .map(|caret| caret.char_range(content))
+            .filter(|range| !range.is_empty())
+            .collect();
+        ranges.sort_by_key(|range| range.start);
+        ranges.into_iter().map(|range| content.slice(range).to_string()).collect()
+    }
+
+    /// Aplica uma substituição por cursor. `replacement` recebe o trecho selecionado do
+    /// cursor (vazio sem seleção) e devolve o trecho a trocar e o texto novo, ou `None`
+    /// para não editar ali. Cada cursor termina no fim do texto inserido.
+    fn replace_at_carets(&mut self, content: &mut Rope, mut replacement: impl FnMut(&Rope, Range<usize>) -> Option<(Range<usize>, String)>) {
+        let carets = self.carets();
+        // (cursor, trecho a trocar, texto novo); cursores sem edição só se deslocam
+        let mut edits: Vec<(usize, Range<usize>, Option<String>)> = carets.iter().enumerate()
+            .map(|(idx, caret)| {
+                let range = caret.char_range(content);
+                match replacement(content, range.clone()) {
+                    Some((range, text)) => (idx, range, Some(text)),
+                    None => {
+                        let at = content.line_to_char(caret.cursor.line) + caret.cursor.char_idx;
+                        (idx, at..at, None)
+                    }
+                }
+            })
+            .collect();
+        edits.sort_by_key(|(_, range, _)| range.start);
+
+        let mut commands = Vec::new();
+        let mut new_positions = vec![0; carets.len()];
+        let mut delta: isize = 0; // Caracteres ganhos (ou perdidos) antes da posição atual
+        let mut covered = 0; // Fim do último trecho editado: cursores vizinhos não apagam duas vezes
+        for (idx, range, text) in edits {
+            let range = range.start.max(covered)..range.end.max(covered);
+            let at = (range.start as isize + delta) as usize;
+            let Some(text) = text else {
+                new_positions[idx] = at;
+                continue;
+            };
+            if !range.is_empty() {
+                commands.push(EditorCommand::Delete { at_char_idx: at, text: content.slice(range.clone()).into() });
+            }
+            let inserted = text.chars().count();
+            if inserted > 0 {
+                commands.push(EditorCommand::Insert { at_char_idx: at, text: Rope::from(text) });
             }
+            new_positions[idx] = at + inserted;
+            delta += inserted as isize - range.len() as isize;
+            covered = range.end;
         }
-        self.cursor.line = new_line;
-        self.cursor.char_idx = new_char_idx;
 
-        // Ensure cursor does not go beyond end of new line if it was at end of previous
-        let current_line_len = content.line(self.cursor.line).len_chars();
-        self.cursor.char_idx = self.cursor.char_idx.min(current_line_len);
+        self.apply_all(content, commands);
 
+        let to_cursor = |char_idx: usize| {
+            let line = content.char_to_line(char_idx);
+            Cursor { line, char_idx: char_idx - content.line_to_char(line) }
+        };
+        self.cursor = to_cursor(new_positions[0]);
         self.selection = None;
+        self.extra_carets = new_positions[1..].iter()
+            .map(|&char_idx| Caret { cursor: to_cursor(char_idx), selection: None })
+            .collect();
+        self.dedup_carets();
     }
 
-    pub fn delete_char_before_cursor(&mut self, content: &mut Rope) {
-        if self.selection.is_some() {
-            self.delete_selected_text(content);
-            return;
+    /// Troca cada trecho (intervalo de caracteres, sem sobreposição) pelo texto
+    /// correspondente, em um único passo de desfazer. Retorna onde cada texto novo
+    /// ficou, na ordem dos trechos. Os cursores extras e a seleção são descartados.
+    pub fn replace_ranges(&mut self, content: &mut Rope, replacements: &[(Range<usize>, String)]) -> Vec<Range<usize>> {
+        let mut order: Vec<usize> = (0..replacements.len()).collect();
+        order.sort_by_key(|&idx| replacements[idx].0.start);
+
+        let mut commands = Vec::new();
+        let mut new_ranges = vec![0..0; replacements.len()];
+        let mut delta: isize = 0;
+        for idx in order {
+            let (range, text) = &replacements[idx];
+            let at = (range.start as isize + delta) as usize;
+            if !range.is_empty() {
+                commands.push(EditorCommand::Delete { at_char_idx: at, text: content.slice(range.clone()).into() });
+            }
+            let inserted = text.chars().count();
+            if inserted > 0 {
+                commands.push(EditorCommand::Insert { at_char_idx: at, text: Rope::from(text.as_str()) });
+            }
+            new_ranges[idx] = at..at + inserted;
+            delta += inserted as isize - range.len() as isize;
         }
+        self.apply_all(content, commands);
 
-        let current_char_idx_in_rope = content.line_to_char(self.cursor.line) + self.cursor.char_idx;
-        if current_char_idx_in_rope > 0 {
-            let start_char_idx_to_remove = current_char_idx_in_rope - 1;
-            let removed_char_slice = content.slice(start_char_idx_to_remove..current_char_idx_in_rope);
-            
-            self.undo_record.edit(content, EditorCommand::Delete {
-                at_char_idx: start_char_idx_to_remove,
-                text: removed_char_slice.into(), // Convert RopeSlice to Rope
-            });
-            self.move_cursor_left(content);
-        }
+        self.selection = None;
+        self.extra_carets.clear();
+        self.cursor = clamp_cursor(content, self.cursor);
+        new_ranges
     }
 
-    pub fn delete_char_after_cursor(&mut self, content: &mut Rope) {
-        if self.selection.is_some() {
-            self.delete_selected_text(content);
-            return;
-        }
+    /// Trecho selecionado pelo cursor principal, em índices de caractere (vazio, na
+    /// posição do cursor, sem seleção).
+    pub fn selection_range(&self, content: &Rope) -> Range<usize> {
+        Caret { cursor: self.cursor, selection: self.selection }.char_range(content)
+    }
 
-        let current_char_idx_in_rope = content.line_to_char(self.cursor.line) + self.cursor.char_idx;
-        if current_char_idx_in_rope < content.len_chars() {
-            let removed_char_slice = content.slice(current_char_idx_in_rope..current_char_idx_in_rope + 1);
-            self.undo_record.edit(content, EditorCommand::Delete {
-                at_char_idx: current_char_idx_in_rope,
-                text: removed_char_slice.into(), // Convert RopeSlice to Rope
-            });
-            // Cursor não se move após delete "para frente"
-        }
+    /// Seleciona `range` (índices de caractere) com o cursor no fim e pede que o painel
+    /// role até ele. Os cursores extras são descartados.
+    pub fn select_range(&mut self, content: &Rope, range: Range<usize>) {
+        let selection = char_range_to_selection(content, range);
+        self.extra_carets.clear();
+        self.selection = Some(selection);
+        self.cursor = selection.end;
+        self.scroll_to_cursor = true;
     }
 
-    pub fn delete_selected_text(&mut self, content: &mut Rope) {
-        if let Some(selection) = self.selection.take() { // take() move a seleção e a torna None
-            let normalized_selection = selection.normalized();
-            let start_char_idx = content.line_to_char(normalized_selection.start.line) + normalized_selection.start.char_idx;
-            let end_char_idx = content.line_to_char(normalized_selection.end.line) + normalized_selection.end.char_idx;
-            
-            if start_char_idx < end_char_idx {
-                let removed_text = content.slice(start_char_idx..end_char_idx).clone();
-                self.undo_record.edit(content, EditorCommand::Delete {
-                    at_char_idx: start_char_idx,
-                    text: removed_text.into(), // Convert RopeSlice to Rope
-                });
-                self.cursor = normalized_selection.start; // Move cursor para o início da seleção
-            }
-        }
+    /// Restaura cursor e seleção guardados antes (ex.: na sessão anterior), limitados
+    /// ao conteúdo atual, sem rolar. Os cursores extras são descartados.
+    pub fn restore_cursor(&mut self, content: &Rope, cursor: Cursor, selection: Option<Selection>) {
+        self.place_cursor(content, cursor.line, cursor.char_idx);
+        self.selection = selection.map(|selection| Selection {
+            start: clamp_cursor(content, selection.start),
+            end: clamp_cursor(content, selection.end),
+        });
     }
 
-    // Métodos de movimento do cursor
+    // Métodos de movimento do cursor (valem para todos os cursores)
     pub fn move_cursor_left(&mut self, content: &Rope) {
+        self.for_each_caret(|editor| editor.step_left(content));
+    }
+
+    pub fn move_cursor_right(&mut self, content: &Rope) {
+        self.for_each_caret(|editor| editor.step_right(content));
+    }
+
+    pub fn move_cursor_up(&mut self, content: &Rope) {
+        self.for_each_caret(|editor| editor.step_up(content));
+    }
+
+    pub fn move_cursor_down(&mut self, content: &Rope) {
+        self.for_each_caret(|editor| editor.step_down(content));
+    }
+
+    fn step_left(&mut self, content: &Rope) {
         self.selection = None;
         if self.cursor.char_idx > 0 {
             self.cursor.char_idx -= 1;
         } else if self.cursor.line > 0 {
             self.cursor.line -= 1;
-            self.cursor.char_idx = content.line(self.cursor.line).len_chars();
+            self.cursor.char_idx = line_len_without_newline(content, self.cursor.line);
         }
     }
 
-    pub fn move_cursor_right(&mut self, content: &Rope) {
+    fn step_right(&mut self, content: &Rope) {
         self.selection = None;
-        let current_line_len = content.line(self.cursor.line).len_chars();
+        let current_line_len = line_len_without_newline(content, self.cursor.line);
         if self.cursor.char_idx < current_line_len {
             self.cursor.char_idx += 1;
         } else if self.cursor.line < content.len_lines() - 1 {
@@ -226,45 +445,208 @@ impl TextEditor {
         }
     }
 
-    pub fn move_cursor_up(&mut self, content: &Rope) {
+    fn step_up(&mut self, content: &Rope) {
         self.selection = None;
         if self.cursor.line > 0 {
             self.cursor.line -= 1;
-            let target_line_len = content.line(self.cursor.line).len_chars();
+            let target_line_len = line_len_without_newline(content, self.cursor.line);
             self.cursor.char_idx = self.cursor.char_idx.min(target_line_len);
         }
     }
 
-    pub fn move_cursor_down(&mut self, content: &Rope) {
+    fn step_down(&mut self, content: &Rope) {
         self.selection = None;
         if self.cursor.line < content.len_lines() - 1 {
             self.cursor.line += 1;
-            let target_line_len = content.line(self.cursor.line).len_chars();
+            let target_line_len = line_len_without_newline(content, self.cursor.line);
             self.cursor.char_idx = self.cursor.char_idx.min(target_line_len);
         }
     }
 
-    pub fn new_line(&mut self, content: &mut Rope) {
-        self.delete_selected_text(content); // Remove seleção antes de nova linha
-        let current_char_idx_in_rope = content.line_to_char(self.cursor.line) + self.cursor.char_idx;
-        self.undo_record.edit(content, EditorCommand::Insert {
-            at_char_idx: current_char_idx_in_rope,
-            text: Rope::from("\n"),
-        });
-        self.cursor.line += 1;
-        self.cursor.char_idx = 0;
+    /// Posiciona o cursor em `line`/`char_idx` (base 0, limitados ao conteúdo) e
+    /// pede que o painel role até ele. Os cursores extras são descartados.
+    pub fn go_to(&mut self, content: &Rope, line: usize, char_idx: usize) {
+        self.place_cursor(content, line, char_idx);
+        self.scroll_to_cursor = true;
+    }
+
+    /// Posiciona o cursor (ex.: clique) sem rolar, descartando os cursores extras.
+    pub fn place_cursor(&mut self, content: &Rope, line: usize, char_idx: usize) {
         self.selection = None;
+        self.extra_carets.clear();
+        self.cursor = clamp_cursor(content, Cursor { line, char_idx });
+    }
+
+    // Multi-cursor
+
+    /// Todos os cursores; o principal vem primeiro.
+    pub fn carets(&self) -> Vec<Caret> {
+        std::iter::once(Caret { cursor: self.cursor, selection: self.selection })
+            .chain(self.extra_carets.iter().copied())
+            .collect()
+    }
+
+    pub fn has_multiple_carets(&self) -> bool {
+        !self.extra_carets.is_empty()
+    }
+
+    /// Acrescenta um cursor (Alt+clique). Um cursor na mesma posição não é duplicado.
+    pub fn add_caret(&mut self, content: &Rope, line: usize, char_idx: usize) {
+        let cursor = clamp_cursor(content, Cursor { line, char_idx });
+        self.extra_carets.push(Caret { cursor, selection: None });
+        self.dedup_carets();
+    }
+
+    /// Volta a ter só o cursor principal.
+    pub fn clear_extra_carets(&mut self) {
+        self.extra_carets.clear();
+    }
+
+    /// Ctrl+D: sem seleção, seleciona a palavra sob o cursor; com seleção, acrescenta um
+    /// cursor selecionando a próxima ocorrência do texto selecionado (dando a volta no
+    /// fim do documento).
+    pub fn add_next_occurrence(&mut self, content: &Rope) {
+        let primary = Caret { cursor: self.cursor, selection: self.selection };
+        let selected = primary.char_range(content);
+        if selected.is_empty() {
+            if let Some(word) = word_at(content, selected.start) {
+                self.selection = Some(char_range_to_selection(content, word.clone()));
+                self.cursor = self.selection.unwrap().end;
+            }
+            return;
+        }
+
+        let needle: Vec<char> = content.slice(selected.clone()).chars().collect();
+        let taken: Vec<Range<usize>> = self.carets().iter().map(|caret| caret.char_range(content)).collect();
+        let search_from = taken.iter().map(|range| range.end).max().unwrap_or(selected.end);
+        let found = find_chars(content, &needle, search_from, content.len_chars())
+            .chain(find_chars(content, &needle, 0, search_from))
+            .find(|range| !taken.contains(range));
+        if let Some(range) = found {
+            let selection = char_range_to_selection(content, range);
+            self.extra_carets.push(Caret { cursor: selection.end, selection: Some(selection) });
+        }
+    }
+
+    /// Seleção em bloco (Alt+arrastar): um cursor por linha entre `anchor` e `head`,
+    /// selecionando as mesmas colunas em cada uma (limitadas ao tamanho da linha).
+    pub fn set_box_selection(&mut self, content: &Rope, anchor: Cursor, head: Cursor) {
+        let lines: Vec<usize> = if anchor.line <= head.line {
+            (anchor.line..=head.line).collect()
+        } else {
+            (head.line..=anchor.line).rev().collect()
+        };
+        let mut carets = lines.into_iter().map(|line| {
+            let start = clamp_cursor(content, Cursor { line, char_idx: anchor.char_idx });
+            let end = clamp_cursor(content, Cursor { line, char_idx: head.char_idx });
+            Caret { cursor: end, selection: Some(Selection { start, end }) }
+        });
+        let first = carets.next().unwrap_or_default();
+        self.cursor = first.cursor;
+        self.selection = first.selection;
+        self.extra_carets = carets.collect();
+    }
+
+    /// Aplica `step` a cada cursor como se fosse o único.
+    fn for_each_caret(&mut self, mut step: impl FnMut(&mut Self)) {
+        step(self);
+        if self.extra_carets.is_empty() {
+            return;
+        }
+        let primary = (self.cursor, self.selection);
+        let mut extra_carets = std::mem::take(&mut self.extra_carets);
+        for caret in &mut extra_carets {
+            (self.cursor, self.selection) = (caret.cursor, caret.selection);
+            step(self);
+            (caret.cursor, caret.selection) = (self.cursor, self.selection);
+        }
+        (self.cursor, self.selection) = primary;
+        self.extra_carets = extra_carets;
+        self.dedup_carets();
+    }
+
+    /// Remove cursores que caíram na mesma posição de outro.
+    fn dedup_carets(&mut self) {
+        let mut seen = vec![self.cursor];
+        self.extra_carets.retain(|caret| {
+            let duplicate = seen.contains(&caret.cursor);
+            seen.push(caret.cursor);
+            !duplicate
+        });
     }
 
     // Métodos de desfazer/refazer
     pub fn undo(&mut self, content: &mut Rope) -> bool {
-        self.selection = None;
-        self.undo_record.undo(content).is_some() // Fixed: use .is_some()
+        let changes = self.undo_record.undo(content);
+        self.after_history_step(content, changes)
     }
 
     pub fn redo(&mut self, content: &mut Rope) -> bool {
+        let changes = self.undo_record.redo(content);
+        self.after_history_step(content, changes)
+    }
+
+    /// Junta as edições seguintes em um único passo de desfazer, até `end_undo_group`
+    /// (ex.: tudo o que é digitado em uma passagem pelo modo de inserção do Vim).
+    pub fn begin_undo_group(&mut self) {
+        self.undo_group = Some(false);
+    }
+
+    pub fn end_undo_group(&mut self) {
+        self.undo_group = None;
+    }
+
+    /// Depois de desfazer/refazer os cursores extras somem e o principal é mantido
+    /// dentro do texto. Um grupo aberto recomeça, para não se juntar ao passo desfeito.
+    fn after_history_step(&mut self, content: &Rope, changes: Option<Vec<TextChange>>) -> bool {
+        if self.undo_group.is_some() {
+            self.undo_group = Some(false);
+        }
         self.selection = None;
-        self.undo_record.redo(content).is_some() // Fixed: use .is_some()
+        self.extra_carets.clear();
+        self.cursor = clamp_cursor(content, self.cursor);
+        match changes {
+            Some(changes) => {
+                self.pending_changes.extend(changes);
+                self.version += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Contador de alterações do texto, para saber se ele mudou desde uma leitura anterior.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Alterações feitas desde a última chamada, na ordem em que aconteceram.
+    pub fn take_changes(&mut self) -> Vec<TextChange> {
+        std::mem::take(&mut self.pending_changes)
+    }
+
+    /// Executa um comando pelo histórico e guarda as alterações resultantes.
+    fn apply(&mut self, content: &mut Rope, command: EditorCommand) {
+        let command = match self.undo_group {
+            Some(true) => EditorCommand::Join(Box::new(command)),
+            Some(false) => {
+                self.undo_group = Some(true);
+                command
+            }
+            None => command,
+        };
+        let changes = self.undo_record.edit(content, command);
+        self.pending_changes.extend(changes);
+        self.version += 1;
+    }
+
+    /// Executa os comandos como um único passo de desfazer.
+    fn apply_all(&mut self, content: &mut Rope, mut commands: Vec<EditorCommand>) {
+        match commands.len() {
+            0 => {}
+            1 => self.apply(content, commands.pop().unwrap()),
+            _ => self.apply(content, EditorCommand::Batch(commands)),
+        }
     }
 
     // Métodos de seleção (ainda bem básicos, serão aprimorados)
@@ -276,15 +658,66 @@ impl TextEditor {
     }
 
     pub fn extend_selection(&mut self) {
-        if let Some(selection) = &mut self.selection {
-            selection.end = self.cursor;
-        } else {
-            // If no selection is active, start one
-            self.set_selection_start();
-        }
+        self.for_each_caret(|editor| {
+            if let Some(selection) = &mut editor.selection {
+                selection.end = editor.cursor;
+            } else {
+                // If no selection is active, start one
+                editor.set_selection_start();
+            }
+        });
     }
 
     pub fn clear_selection(&mut self) {
-        self.selection = None;
+        self.for_each_caret(|editor| editor.selection = None);
+    }
+}
+
+/// Limita um cursor ao conteúdo atual.
+fn clamp_cursor(content: &Rope, cursor: Cursor) -> Cursor {
+    let line = cursor.line.min(content.len_lines().saturating_sub(1));
+    Cursor { line, char_idx: cursor.char_idx.min(line_len_without_newline(content, line)) }
+}
+
+/// Caracteres da linha sem a quebra de linha final.
+pub fn line_len_without_newline(content: &Rope, line: usize) -> usize {
+    let slice = content.line(line);
+    slice.len_chars() - line_break_len(slice)
+}
+
+fn char_range_to_selection(content: &Rope, range: Range<usize>) -> Selection {
+    let to_cursor = |char_idx: usize| {
+        let line = content.char_to_line(char_idx);
+        Cursor { line, char_idx: char_idx - content.line_to_char(line) }
+    };
+    Selection { start: to_cursor(range.start), end: to_cursor(range.end) }
+}
+
+/// Palavra (letras, dígitos e `_`) que contém ou termina em `char_idx`.
+fn word_at(content: &Rope, char_idx: usize) -> Option<Range<usize>> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut start = char_idx;
+    while start > 0 && is_word(content.char(start - 1)) {
+        start -= 1;
+    }
+    let mut end = char_idx;
+    while end < content.len_chars() && is_word(content.char(end)) {
+        end += 1;
     }
-}
\ No newline at end of file
+    (start < end).then_some(start..end)
+}
+
+/// Ocorrências de `needle` que começam em `from..to`, em ordem.
+fn find_chars<'a>(content: &'a Rope, needle: &'a [char], from: usize, to: usize) -> impl Iterator<Item = Range<usize>> + 'a {
+    let end = if needle.is_empty() || needle.len() > content.len_chars() {
+        from // Nada a procurar
+    } else {
+        to.min(content.len_chars() - needle.len() + 1)
+    };
+    (from..end)
+        .filter(move |&start| {
+            content.char(start) == needle[0]
+                && content.slice(start..start + needle.len()).chars().eq(needle.iter().copied())
+        })
+        .map(move |start| start..start + needle.len())
+}
@@ -0,0 +1,209 @@
+// src/core/encoding.rs
+
+use std::borrow::Cow;
+use std::io;
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+/// Codificações oferecidas nos menus de reabrir/salvar, na ordem em que aparecem.
+pub const ENCODING_CHOICES: [FileEncoding; 15] = [
+    FileEncoding::UTF8,
+    FileEncoding { encoding: UTF_8, bom: true },
+    FileEncoding { encoding: UTF_16LE, bom: true },
+    FileEncoding { encoding: UTF_16BE, bom: true },
+    FileEncoding { encoding: encoding_rs::WINDOWS_1252, bom: false }, // Também cobre o ISO-8859-1
+    FileEncoding { encoding: encoding_rs::ISO_8859_15, bom: false },
+    FileEncoding { encoding: encoding_rs::WINDOWS_1250, bom: false },
+    FileEncoding { encoding: encoding_rs::ISO_8859_2, bom: false },
+    FileEncoding { encoding: encoding_rs::WINDOWS_1251, bom: false },
+    FileEncoding { encoding: encoding_rs::KOI8_R, bom: false },
+    FileEncoding { encoding: encoding_rs::SHIFT_JIS, bom: false },
+    FileEncoding { encoding: encoding_rs::EUC_JP, bom: false },
+    FileEncoding { encoding: encoding_rs::GBK, bom: false },
+    FileEncoding { encoding: encoding_rs::BIG5, bom: false },
+    FileEncoding { encoding: encoding_rs::EUC_KR, bom: false },
+];
+
+/// Codificação de um arquivo no disco e se ele começa com BOM. O texto no `Rope` é
+/// sempre UTF-8; a conversão acontece ao abrir e ao salvar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileEncoding {
+    pub encoding: &'static Encoding,
+    pub bom: bool,
+}
+
+impl Default for FileEncoding {
+    fn default() -> Self {
+        Self::UTF8
+    }
+}
+
+impl FileEncoding {
+    pub const UTF8: Self = Self { encoding: UTF_8, bom: false };
+
+    /// Nome mostrado na barra de status: "UTF-8", "UTF-8 com BOM", "windows-1252"...
+    pub fn label(&self) -> String {
+        if self.bom && self.encoding == UTF_8 {
+            format!("{} com BOM", self.encoding.name())
+        } else {
+            self.encoding.name().to_string()
+        }
+    }
+
+    /// Codificação pelo nome (como em `Encoding::name` ou os rótulos do WHATWG).
+    pub fn for_name(name: &str, bom: bool) -> Option<Self> {
+        Encoding::for_label(name.as_bytes()).map(|encoding| Self { encoding, bom })
+    }
+
+    /// Descobre a codificação dos bytes: primeiro pelo BOM; sem ele, UTF-8 se os bytes
+    /// forem UTF-8 válido; senão, o palpite do chardetng (ex.: windows-1252 para os
+    /// arquivos Latin-1 antigos).
+    pub fn detect(bytes: &[u8]) -> Self {
+        if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+            return Self { encoding, bom: true };
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            return Self::UTF8;
+        }
+        let mut detector = EncodingDetector::new();
+        detector.feed(bytes, true);
+        Self { encoding: detector.guess(None, false), bom: false }
+    }
+
+    /// Converte os bytes para texto, descartando o BOM. Retorna também se algum trecho
+    /// era inválido na codificação (e foi trocado por U+FFFD).
+    pub fn decode<'a>(&self, bytes: &'a [u8]) -> (Cow<'a, str>, bool) {
+        let without_bom = match Encoding::for_bom(bytes) {
+            Some((encoding, bom_len)) if encoding == self.encoding => &bytes[bom_len..],
+            _ => bytes,
+        };
+        self.encoding.decode_without_bom_handling(without_bom)
+    }
+
+    /// Converte o texto para os bytes gravados no disco, com o BOM se houver. Falha se
+    /// algum caractere não existe na codificação, em vez de gravar um substituto.
+    pub fn encode<'a>(&self, text: &'a str) -> io::Result<Cow<'a, [u8]>> {
+        // O encoding_rs só decodifica UTF-16; a codificação é feita aqui
+        let utf16 = |to_bytes: fn(u16) -> [u8; 2]| {
+            let mut bytes = Vec::with_capacity(text.len() * 2 + 2);
+            if self.bom {
+                bytes.extend(to_bytes(0xFEFF));
+            }
+            bytes.extend(text.encode_utf16().flat_map(to_bytes));
+            Cow::Owned(bytes)
+        };
+        if self.encoding == UTF_16LE {
+            return Ok(utf16(u16::to_le_bytes));
+        }
+        if self.encoding == UTF_16BE {
+            return Ok(utf16(u16::to_be_bytes));
+        }
+
+        let (bytes, _, had_errors) = self.encoding.encode(text);
+        if had_errors {
+            return Err(unmappable_error(self.encoding, text));
+        }
+        if self.bom && self.encoding == UTF_8 {
+            let mut with_bom = b"\xEF\xBB\xBF".to_vec();
+            with_bom.extend_from_slice(&bytes);
+            return Ok(Cow::Owned(with_bom));
+        }
+        Ok(bytes)
+    }
+}
+
+/// Erro que aponta o primeiro caractere sem representação na codificação.
+fn unmappable_error(encoding: &'static Encoding, text: &str) -> io::Error {
+    let mut buffer = [0; 4];
+    let position = text.lines().enumerate().find_map(|(line_idx, line)| {
+        line.chars()
+            .position(|c| encoding.encode(c.encode_utf8(&mut buffer)).2)
+            .map(|column| (line_idx, column, line.chars().nth(column).unwrap_or_default()))
+    });
+    let message = match position {
+        Some((line, column, c)) => format!(
+            "o caractere '{}' (linha {}, coluna {}) não existe em {}",
+            c, line + 1, column + 1, encoding.name(),
+        ),
+        None => format!("o texto tem caracteres que não existem em {}", encoding.name()),
+    };
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UTF16LE_BOM: FileEncoding = FileEncoding { encoding: UTF_16LE, bom: true };
+    const UTF16BE_BOM: FileEncoding = FileEncoding { encoding: UTF_16BE, bom: true };
+    const UTF8_BOM: FileEncoding = FileEncoding { encoding: UTF_8, bom: true };
+    const WINDOWS_1252: FileEncoding = FileEncoding { encoding: encoding_rs::WINDOWS_1252, bom: false };
+
+    #[test]
+    fn detect_uses_bom_then_utf8_then_guess() {
+        assert_eq!(FileEncoding::detect(b"\xEF\xBB\xBFol\xC3\xA1"), UTF8_BOM);
+        assert_eq!(FileEncoding::detect(b"\xFF\xFEo\0i\0"), UTF16LE_BOM);
+        assert_eq!(FileEncoding::detect(b"\xFE\xFF\0o\0i"), UTF16BE_BOM);
+        assert_eq!(FileEncoding::detect("ação".as_bytes()), FileEncoding::UTF8);
+        assert_eq!(FileEncoding::detect(b""), FileEncoding::UTF8);
+        let latin1 = b"Fun\xE7\xE3o que l\xEA a configura\xE7\xE3o e devolve o n\xFAmero de op\xE7\xF5es v\xE1lidas.";
+        assert_eq!(FileEncoding::detect(latin1), WINDOWS_1252);
+    }
+
+    #[test]
+    fn decode_strips_only_the_matching_bom() {
+        assert_eq!(UTF8_BOM.decode(b"\xEF\xBB\xBFoi"), (Cow::Borrowed("oi"), false));
+        assert_eq!(UTF16LE_BOM.decode(b"\xFF\xFEo\0i\0").0, "oi");
+        assert_eq!(UTF16BE_BOM.decode(b"\xFE\xFF\0o\0i").0, "oi");
+        // Um BOM de outra codificação não é descartado
+        assert_eq!(WINDOWS_1252.decode(b"\xEF\xBB\xBFoi").0, "\u{EF}\u{BB}\u{BF}oi");
+    }
+
+    #[test]
+    fn decode_reports_invalid_bytes() {
+        let (text, had_errors) = FileEncoding::UTF8.decode(b"a\xFFb");
+        assert_eq!(text, "a\u{FFFD}b");
+        assert!(had_errors);
+        assert!(!WINDOWS_1252.decode(b"caf\xE9").1);
+    }
+
+    #[test]
+    fn encode_utf16_with_and_without_bom() {
+        assert_eq!(UTF16LE_BOM.encode("oé").unwrap().as_ref(), b"\xFF\xFEo\0\xE9\0");
+        assert_eq!(UTF16BE_BOM.encode("oé").unwrap().as_ref(), b"\xFE\xFF\0o\0\xE9");
+        let without_bom = FileEncoding { encoding: UTF_16LE, bom: false };
+        assert_eq!(without_bom.encode("🦀").unwrap().as_ref(), b"\x3E\xD8\x80\xDD");
+    }
+
+    #[test]
+    fn encode_adds_utf8_bom() {
+        assert_eq!(UTF8_BOM.encode("oi").unwrap().as_ref(), b"\xEF\xBB\xBFoi");
+        assert_eq!(FileEncoding::UTF8.encode("oi").unwrap().as_ref(), b"oi");
+    }
+
+    #[test]
+    fn decode_then_encode_round_trips_bytes() {
+        let files: [&[u8]; 6] = [
+            "linha 1\nação\r\n".as_bytes(),
+            b"\xEF\xBB\xBFcom BOM\n",
+            b"\xFF\xFEo\0\xE9\0\n\0",
+            b"\xFE\xFF\0o\0\xE9\0\n",
+            b"caf\xE9 \x80\n",
+            b"",
+        ];
+        for bytes in files {
+            let encoding = FileEncoding::detect(bytes);
+            let (text, had_errors) = encoding.decode(bytes);
+            assert!(!had_errors, "{:?}", bytes);
+            assert_eq!(encoding.encode(&text).unwrap().as_ref(), bytes, "{}", encoding.label());
+        }
+    }
+
+    #[test]
+    fn encode_rejects_unmappable_characters() {
+        let error = WINDOWS_1252.encode("ok\nab🦀").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "o caractere '🦀' (linha 2, coluna 3) não existe em windows-1252");
+    }
+}
@@ -1,70 +1,220 @@
 // src/core/file_handler.rs
 
 use ropey::Rope;
-use std::fs::File;
-use std::io::{self, Read, Write}; // Adicionar Write
-use std::path::Path;
+use std::fs::{File, Metadata, OpenOptions};
+use std::io::{self, BufWriter, Read, Write}; // Adicionar Write
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
 use memmap2::Mmap;
 
+use crate::core::encoding::FileEncoding;
+use crate::core::line_ending::{write_with_line_ending, LineEnding};
+
+/// Tamanho a partir do qual os arquivos são lidos com `mmap`, se a configuração não
+/// indicar outro.
+pub const DEFAULT_MMAP_THRESHOLD: u64 = 1024 * 1024; // 1MB
+
+/// Conteúdo de um arquivo convertido para UTF-8.
+#[derive(Debug)]
+pub struct LoadedFile {
+    pub content: Rope,
+    pub encoding: FileEncoding,
+    pub lossy: bool, // Havia bytes inválidos na codificação, trocados por U+FFFD
+}
+
+impl LoadedFile {
+    /// Aviso para o usuário quando a leitura perdeu bytes; salvar o texto assim não
+    /// devolve o arquivo original.
+    pub fn lossy_warning(&self, path: &Path) -> Option<String> {
+        self.lossy.then(|| format!(
+            "{} tem bytes inválidos em {}; foram trocados por U+FFFD e se perdem ao salvar",
+            path.display(),
+            self.encoding.label(),
+        ))
+    }
+}
+
 /// Carrega o conteúdo de um arquivo para um Rope, otimizando para arquivos grandes.
 ///
-/// Se o arquivo for menor que 1MB, lê todo o conteúdo para a memória.
+/// Se o arquivo for menor que `mmap_threshold`, lê todo o conteúdo para a memória.
 /// Para arquivos maiores em sistemas Unix, usa `mmap` para carregamento eficiente.
+/// A codificação é detectada pelo BOM ou, sem ele, pelo conteúdo (veja
+/// `FileEncoding::detect`), e o texto é convertido para UTF-8.
 ///
 /// # Argumentos
 ///
 /// * `path` - O caminho para o arquivo a ser carregado.
+/// * `mmap_threshold` - Tamanho, em bytes, a partir do qual o arquivo é mapeado.
 ///
 /// # Retorno
 ///
-/// Retorna um `Result` contendo o `Rope` com o conteúdo do arquivo em caso de sucesso,
-/// ou um `io::Error` em caso de falha.
-pub fn load_file_into_rope(path: &Path) -> io::Result<Rope> {
-    let file = File::open(path)?;
+/// Retorna um `Result` contendo o `LoadedFile` com o conteúdo do arquivo e a
+/// codificação detectada em caso de sucesso, ou um `io::Error` em caso de falha.
+pub fn load_file_into_rope(path: &Path, mmap_threshold: u64) -> io::Result<LoadedFile> {
+    with_file_bytes(path, mmap_threshold, |bytes| decode_into_rope(bytes, FileEncoding::detect(bytes)))
+}
+
+/// Carrega o arquivo com uma codificação escolhida pelo usuário, sem detecção. Bytes
+/// inválidos nessa codificação viram U+FFFD (veja `LoadedFile::lossy`).
+pub fn load_file_with_encoding(path: &Path, mmap_threshold: u64, encoding: FileEncoding) -> io::Result<LoadedFile> {
+    with_file_bytes(path, mmap_threshold, |bytes| decode_into_rope(bytes, encoding))
+}
+
+/// Chama `f` com o conteúdo do arquivo, lido para a memória ou mapeado com `mmap`.
+fn with_file_bytes<T>(path: &Path, mmap_threshold: u64, f: impl FnOnce(&[u8]) -> T) -> io::Result<T> {
+    let mut file = File::open(path)?;
     let metadata = file.metadata()?;
     let file_len = metadata.len();
 
-    if file_len < 1024 * 1024 { // 1MB
-        let mut buffer = String::new();
-        file.take(file_len).read_to_string(&mut buffer)?;
-        Ok(Rope::from(buffer))
+    if file_len < mmap_threshold {
+        let mut buffer = Vec::with_capacity(file_len as usize);
+        (&mut file).take(file_len).read_to_end(&mut buffer)?;
+        Ok(f(&buffer))
     } else {
         #[cfg(target_family = "unix")]
         {
             // Em sistemas Unix, podemos usar mmap para arquivos grandes de forma eficiente
             let mmap = unsafe { Mmap::map(&file)? };
-            let content_str = std::str::from_utf8(&mmap)
-                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Arquivo não é UTF-8 válido: {}", e)))?;
-            Ok(Rope::from(content_str))
+            Ok(f(&mmap))
         }
         #[cfg(not(target_family = "unix"))]
         {
             // Em outros sistemas, ou como fallback, lemos para a memória
-            let mut buffer = String::new();
-            file.read_to_string(&mut buffer)?;
-            Ok(Rope::from(buffer))
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer)?;
+            Ok(f(&buffer))
         }
     }
 }
 
+fn decode_into_rope(bytes: &[u8], encoding: FileEncoding) -> LoadedFile {
+    let (text, lossy) = encoding.decode(bytes);
+    LoadedFile { content: Rope::from_str(&text), encoding, lossy }
+}
+
 /// Salva o conteúdo de um Rope para um arquivo.
 ///
-/// Esta função escreve todo o conteúdo do Rope para o arquivo especificado.
-/// Em futuras otimizações, podemos considerar salvamento incremental ou em background
-/// para arquivos muito grandes, mas para o salvamento manual, uma escrita completa é um bom começo.
+/// A gravação é atômica (veja `write_atomically`): uma queda no meio dela deixa o
+/// arquivo antigo intacto em vez de truncado. Links simbólicos são seguidos e as
+/// permissões do arquivo original são mantidas.
 ///
 /// # Argumentos
 ///
 /// * `path` - O caminho para o arquivo onde o conteúdo será salvo.
 /// * `content` - O `Rope` contendo o texto a ser salvo.
+/// * `encoding` - Codificação (e BOM) gravada no disco.
+/// * `line_ending` - Fim de linha gravado no lugar de todas as quebras; `None` grava
+///   as quebras como estão no `Rope`.
 ///
 /// # Retorno
 ///
-/// Retorna um `io::Result` indicando sucesso ou falha na operação de escrita.
-pub fn save_rope_to_file(path: &Path, content: &Rope) -> io::Result<()> {
-    let mut file = File::create(path)?; // Cria ou trunca o arquivo
-    for chunk in content.chunks() {
-        file.write_all(chunk.as_bytes())?;
+/// Retorna um `io::Result` indicando sucesso ou falha na operação de escrita. Um
+/// caractere que não existe na codificação é um erro, e o arquivo não é alterado.
+pub fn save_rope_to_file(path: &Path, content: &Rope, encoding: FileEncoding, line_ending: Option<LineEnding>) -> io::Result<()> {
+    if encoding == FileEncoding::UTF8 {
+        return write_atomically(path, |writer| match line_ending {
+            Some(line_ending) => write_with_line_ending(content, line_ending, |text| writer.write_all(text.as_bytes())),
+            None => content.write_to(writer),
+        });
     }
+    let text = match line_ending {
+        Some(line_ending) => {
+            let mut text = String::with_capacity(content.len_bytes());
+            write_with_line_ending(content, line_ending, |chunk| {
+                text.push_str(chunk);
+                Ok::<_, io::Error>(())
+            })?;
+            text
+        },
+        None => content.to_string(),
+    };
+    let bytes = encoding.encode(&text)?;
+    write_atomically(path, |writer| writer.write_all(&bytes))
+}
+
+/// Grava o arquivo sem nunca deixá-lo pela metade: o conteúdo vai para um arquivo
+/// temporário na mesma pasta, que é sincronizado com o disco e renomeado por cima do
+/// destino (a troca é atômica no mesmo sistema de arquivos).
+///
+/// Se `path` é um link simbólico, o arquivo apontado é substituído e o link continua
+/// no lugar. O arquivo novo recebe as permissões e, quando o sistema deixa, o dono e o
+/// grupo do original. Arquivos somente leitura não são sobrescritos.
+pub fn write_atomically(path: &Path, write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>) -> io::Result<()> {
+    let target = resolve_symlinks(path)?;
+    let original = std::fs::metadata(&target).ok();
+    if original.as_ref().is_some_and(|metadata| metadata.permissions().readonly()) {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("{} é somente leitura", target.display())));
+    }
+    let dir = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let (temp_path, file) = create_temp_file(&dir, &target)?;
+    let result = (|| {
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        if let Some(metadata) = &original {
+            copy_permissions(&file, metadata)?;
+        }
+        file.sync_all()?;
+        std::fs::rename(&temp_path, &target)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result?;
+    sync_dir(&dir);
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Destino final de `path`, seguindo links simbólicos (inclusive os que apontam para um
+/// arquivo que ainda não existe). Caminhos que não são links voltam iguais.
+fn resolve_symlinks(path: &Path) -> io::Result<PathBuf> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => {
+            std::fs::canonicalize(path).or_else(|_| {
+                let link = std::fs::read_link(path)?;
+                Ok(path.parent().map_or(link.clone(), |parent| parent.join(&link)))
+            })
+        },
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
+/// Cria `.<nome>.<pid>.<n>.tmp` ao lado do destino, sem sobrescrever nada que exista.
+fn create_temp_file(dir: &Path, target: &Path) -> io::Result<(PathBuf, File)> {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    loop {
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let temp_path = dir.join(format!(".{}.{}.{}.tmp", name, std::process::id(), n));
+        match OpenOptions::new().write(true).create_new(true).open(&temp_path) {
+            Ok(file) => return Ok((temp_path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn copy_permissions(file: &File, original: &Metadata) -> io::Result<()> {
+    file.set_permissions(original.permissions())?;
+    // Só o root pode dar o arquivo a outro usuário; sem permissão ele fica com quem salvou
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let _ = std::os::unix::fs::fchown(file, Some(original.uid()), Some(original.gid()));
+    }
+    Ok(())
+}
+
+/// Sincroniza a pasta, para que a troca de nome também sobreviva a uma queda de energia.
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Err(e) = File::open(dir).and_then(|dir| dir.sync_all()) {
+        eprintln!("Falha ao sincronizar a pasta {}: {}", dir.display(), e);
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
@@ -0,0 +1,185 @@
+// src/core/line_ending.rs
+
+use std::ops::Range;
+
+use ropey::{Rope, RopeSlice};
+
+/// Fim de linha usado por um arquivo. O `Rope` aceita os três como quebra de linha;
+/// cada aba guarda o seu para inserir novas linhas e salvar no mesmo estilo.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,   // Unix
+    Crlf, // Windows
+    Cr,   // Mac clássico
+}
+
+impl LineEnding {
+    pub const ALL: [LineEnding; 3] = [LineEnding::Lf, LineEnding::Crlf, LineEnding::Cr];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+
+    /// Nome mostrado na barra de status.
+    pub fn label(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::Crlf => "CRLF",
+            LineEnding::Cr => "CR",
+        }
+    }
+
+    /// Fim de linha no final de `line`, se houver.
+    fn of_line(line: RopeSlice) -> Option<LineEnding> {
+        let len = line.len_chars();
+        match (len > 0).then(|| line.char(len - 1)) {
+            Some('\n') if len > 1 && line.char(len - 2) == '\r' => Some(LineEnding::Crlf),
+            Some('\n') => Some(LineEnding::Lf),
+            Some('\r') => Some(LineEnding::Cr),
+            _ => None,
+        }
+    }
+}
+
+/// Fim de linha mais usado no texto e se há mais de um estilo misturado. Um texto sem
+/// quebras de linha fica com LF.
+pub fn detect_line_ending(content: &Rope) -> (LineEnding, bool) {
+    let mut counts = [0usize; 3]; // Na ordem de `LineEnding::ALL`
+    let mut after_cr = false;
+    for byte in content.bytes() {
+        match byte {
+            b'\n' if after_cr => counts[1] += 1,
+            b'\n' => counts[0] += 1,
+            _ if after_cr => counts[2] += 1,
+            _ => {}
+        }
+        after_cr = byte == b'\r';
+    }
+    if after_cr {
+        counts[2] += 1;
+    }
+
+    let used = counts.iter().filter(|count| **count > 0).count();
+    // Em caso de empate vale a ordem de `ALL`
+    let dominant = (0..counts.len()).rev().max_by_key(|&idx| counts[idx]).unwrap_or(0);
+    (LineEnding::ALL[dominant], used > 1)
+}
+
+/// Trechos a trocar para que todas as quebras de linha fiquem em `target`, no formato
+/// de `TextEditor::replace_ranges` (a conversão pode ser desfeita).
+pub fn line_ending_replacements(content: &Rope, target: LineEnding) -> Vec<(Range<usize>, String)> {
+    let mut replacements = Vec::new();
+    let mut line_start = 0;
+    for line in content.lines() {
+        let line_end = line_start + line.len_chars();
+        if let Some(ending) = LineEnding::of_line(line)
+            && ending != target
+        {
+            let break_len = ending.as_str().len();
+            replacements.push((line_end - break_len..line_end, target.as_str().to_string()));
+        }
+        line_start = line_end;
+    }
+    replacements
+}
+
+/// Passa o texto para `write` em pedaços, com todas as quebras de linha trocadas por
+/// `target`, sem alterar o `Rope`.
+pub fn write_with_line_ending<E>(content: &Rope, target: LineEnding, mut write: impl FnMut(&str) -> Result<(), E>) -> Result<(), E> {
+    for line in content.lines() {
+        let break_len = line_break_len(line);
+        for chunk in line.slice(..line.len_chars() - break_len).chunks() {
+            write(chunk)?;
+        }
+        if break_len > 0 {
+            write(target.as_str())?;
+        }
+    }
+    Ok(())
+}
+
+/// Quantos caracteres a quebra no final de `line` ocupa (0, 1 ou 2).
+pub fn line_break_len(line: RopeSlice) -> usize {
+    LineEnding::of_line(line).map_or(0, |ending| ending.as_str().len())
+}
+
+/// Texto da linha sem a quebra final, para desenhar (um `\r` viraria um glifo).
+pub fn strip_line_break(line: &str) -> &str {
+    line.strip_suffix("\r\n")
+        .or_else(|| line.strip_suffix('\n'))
+        .or_else(|| line.strip_suffix('\r'))
+        .unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(text: &str) -> (LineEnding, bool) {
+        detect_line_ending(&Rope::from_str(text))
+    }
+
+    fn with_line_ending(text: &str, target: LineEnding) -> String {
+        let mut result = String::new();
+        write_with_line_ending(&Rope::from_str(text), target, |chunk| {
+            result.push_str(chunk);
+            Ok::<_, ()>(())
+        }).unwrap();
+        result
+    }
+
+    #[test]
+    fn detect_single_style() {
+        assert_eq!(detect(""), (LineEnding::Lf, false));
+        assert_eq!(detect("sem quebra"), (LineEnding::Lf, false));
+        assert_eq!(detect("a\nb\n"), (LineEnding::Lf, false));
+        assert_eq!(detect("a\r\nb\r\n"), (LineEnding::Crlf, false));
+        assert_eq!(detect("a\rb\r"), (LineEnding::Cr, false));
+    }
+
+    #[test]
+    fn detect_counts_cr_at_end_of_text() {
+        assert_eq!(detect("a\r"), (LineEnding::Cr, false));
+        assert_eq!(detect("a\nb\r"), (LineEnding::Lf, true));
+    }
+
+    #[test]
+    fn detect_mixed_picks_most_used() {
+        assert_eq!(detect("a\r\nb\r\nc\n"), (LineEnding::Crlf, true));
+        assert_eq!(detect("a\rb\rc\r\n"), (LineEnding::Cr, true));
+    }
+
+    #[test]
+    fn detect_tie_follows_all_order() {
+        assert_eq!(detect("a\r\nb\n"), (LineEnding::Lf, true));
+        assert_eq!(detect("a\rb\r\n"), (LineEnding::Crlf, true));
+        assert_eq!(detect("a\rb\n"), (LineEnding::Lf, true));
+    }
+
+    #[test]
+    fn replacements_cover_only_other_styles() {
+        let content = Rope::from_str("a\r\nb\rc\nd");
+        assert_eq!(line_ending_replacements(&content, LineEnding::Lf), vec![
+            (1..3, "\n".to_string()),
+            (4..5, "\n".to_string()),
+        ]);
+        assert_eq!(line_ending_replacements(&content, LineEnding::Crlf), vec![
+            (4..5, "\r\n".to_string()),
+            (6..7, "\r\n".to_string()),
+        ]);
+        assert!(line_ending_replacements(&Rope::from_str("a\nb\n"), LineEnding::Lf).is_empty());
+    }
+
+    #[test]
+    fn write_converts_every_break() {
+        assert_eq!(with_line_ending("a\r\nb\rc\nd", LineEnding::Crlf), "a\r\nb\r\nc\r\nd");
+        assert_eq!(with_line_ending("a\r\nb\rc\n", LineEnding::Lf), "a\nb\nc\n");
+        assert_eq!(with_line_ending("a\r\n\r\n", LineEnding::Cr), "a\r\r");
+        assert_eq!(with_line_ending("", LineEnding::Crlf), "");
+    }
+}
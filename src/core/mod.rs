@@ -1,4 +1,14 @@
 // src/core/mod.rs
 
 pub mod file_handler; // Contém a função load_file_into_rope
-pub mod editor;       // NOVO: Módulo para o núcleo do editor de texto
\ No newline at end of file
+pub mod editor;       // NOVO: Módulo para o núcleo do editor de texto
+pub mod paths;        // Pastas de configuração do usuário
+pub mod search;       // Busca e substituição no Rope
+pub mod project_search; // Busca e substituição em todos os arquivos do projeto
+pub mod vim;          // Modo de edição modal (Vim) sobre o TextEditor
+pub mod settings;     // Configurações do usuário e do projeto (settings.toml)
+pub mod session;      // Abas e painéis de cada diretório, com backup de hot exit
+pub mod swap;         // Cópias periódicas das abas modificadas, para recuperar após uma queda
+pub mod diff;         // Diferença linha a linha entre dois textos
+pub mod encoding;     // Detecção e conversão da codificação dos arquivos
+pub mod line_ending;  // Detecção e conversão dos fins de linha (LF/CRLF/CR)
@@ -0,0 +1,29 @@
+// src/core/paths.rs
+
+use std::path::{Path, PathBuf};
+
+/// Pasta de configuração do usuário: `$XDG_CONFIG_HOME/lcode` ou `~/.config/lcode`.
+pub fn user_config_dir() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("lcode"))
+}
+
+/// Pasta de estado do usuário (sessões e backups): `$XDG_STATE_HOME/lcode` ou
+/// `~/.local/state/lcode`.
+pub fn user_state_dir() -> Option<PathBuf> {
+    let state_dir = std::env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state")))?;
+    Some(state_dir.join("lcode"))
+}
+
+/// Hash FNV-1a do caminho, para nomear arquivos de estado. Diferente do `DefaultHasher`,
+/// não muda entre versões do Rust, então os nomes continuam válidos depois de recompilar.
+pub fn path_hash(path: &Path) -> u64 {
+    path.as_os_str().as_encoded_bytes().iter()
+        .fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
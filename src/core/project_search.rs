@@ -0,0 +1,283 @@
+// src/core/project_search.rs
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+
+use encoding_rs::Encoding;
+use ignore::{WalkBuilder, WalkState};
+use ropey::Rope;
+
+use crate::core::encoding::FileEncoding;
+use crate::core::search::{SearchOptions, SearchQuery};
+
+/// Limite de ocorrências por busca; acima disso a busca para e o resultado fica parcial.
+const MAX_HITS: usize = 10_000;
+/// Bytes do início do arquivo verificados para decidir se ele é binário.
+const BINARY_CHECK_BYTES: usize = 8 * 1024;
+/// Caracteres de contexto mostrados antes da ocorrência na prévia da linha.
+const PREVIEW_CONTEXT_CHARS: usize = 40;
+const PREVIEW_MAX_CHARS: usize = 200;
+
+/// Uma ocorrência em um arquivo.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub line: usize,   // Base 1
+    pub column: usize, // Base 1, em caracteres
+    pub preview: String, // Trecho da linha em volta da ocorrência
+    pub preview_match: Range<usize>, // Ocorrência dentro de `preview`, em bytes
+}
+
+/// Ocorrências de um arquivo. `selected` marca os arquivos incluídos na substituição.
+#[derive(Debug, Clone)]
+pub struct FileHits {
+    pub path: PathBuf,
+    pub hits: Vec<SearchHit>,
+    pub selected: bool,
+}
+
+/// Uma linha alterada pela substituição, antes e depois.
+#[derive(Debug, Clone)]
+pub struct LineChange {
+    pub line: usize, // Base 1
+    pub before: String,
+    pub after: String,
+}
+
+/// Prévia da substituição em um arquivo.
+#[derive(Debug, Clone)]
+pub struct FilePreview {
+    pub path: PathBuf,
+    pub changes: Vec<LineChange>,
+}
+
+/// Prévia da substituição, com o texto de substituição usado para montá-la: aplicar
+/// grava exatamente o que foi mostrado, mesmo se o campo mudar depois.
+#[derive(Debug, Clone)]
+pub struct ReplacePreview {
+    pub replacement: String,
+    pub files: Vec<FilePreview>,
+}
+
+enum WalkerMessage {
+    File(FileHits),
+    Done { truncated: bool },
+}
+
+/// Busca (e substituição) em todos os arquivos do diretório aberto.
+///
+/// A varredura roda em threads próprias, respeitando `.gitignore`/`.ignore` e pulando
+/// arquivos ocultos e binários; os demais são lidos na codificação detectada. Os
+/// resultados chegam aos poucos e ficam ordenados por caminho.
+#[derive(Default)]
+pub struct ProjectSearch {
+    pub query: String,
+    pub replacement: String,
+    pub options: SearchOptions,
+    pub results: Vec<FileHits>,
+    pub error: Option<String>,
+    pub running: bool,
+    pub truncated: bool, // A busca parou no limite de ocorrências
+    pub preview: Option<ReplacePreview>, // Prévia aguardando confirmação
+    search: Option<SearchQuery>, // Consulta dos resultados atuais
+    rx: Option<mpsc::Receiver<WalkerMessage>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl ProjectSearch {
+    /// Inicia uma nova busca em `root`, descartando a anterior.
+    pub fn start(&mut self, ctx: &egui::Context, root: &Path) {
+        self.cancel();
+        self.results.clear();
+        self.preview = None;
+        self.truncated = false;
+        self.error = None;
+        let search = match SearchQuery::new(&self.query, self.options) {
+            Ok(search) => search,
+            Err(e) => {
+                self.search = None;
+                self.error = Some(e);
+                return;
+            }
+        };
+        self.search = Some(search.clone());
+
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.rx = Some(rx);
+        self.cancel = Arc::clone(&cancel);
+        self.running = true;
+
+        let root = root.to_path_buf();
+        let ctx = ctx.clone();
+        std::thread::Builder::new()
+            .name("lcode-project-search".to_string())
+            .spawn(move || walk(&root, &search, &tx, &cancel, &ctx))
+            .expect("Falha ao iniciar a thread de busca");
+    }
+
+    /// Interrompe a busca em andamento; os resultados já recebidos ficam.
+    pub fn cancel(&mut self) {
+        self.cancel.store(true, Ordering::SeqCst);
+        self.rx = None;
+        self.running = false;
+    }
+
+    /// Recebe os resultados que chegaram desde o último frame.
+    pub fn poll(&mut self) {
+        let Some(rx) = &self.rx else {
+            return;
+        };
+        let mut done = false;
+        for message in rx.try_iter() {
+            match message {
+                WalkerMessage::File(file) => {
+                    let idx = self.results.partition_point(|existing| existing.path < file.path);
+                    self.results.insert(idx, file);
+                }
+                WalkerMessage::Done { truncated } => {
+                    self.truncated = truncated;
+                    done = true;
+                }
+            }
+        }
+        if done {
+            self.rx = None;
+            self.running = false;
+        }
+    }
+
+    pub fn hit_count(&self) -> usize {
+        self.results.iter().map(|file| file.hits.len()).sum()
+    }
+
+    /// Arquivos marcados para a substituição.
+    pub fn selected_paths(&self) -> Vec<PathBuf> {
+        self.results.iter().filter(|file| file.selected).map(|file| file.path.clone()).collect()
+    }
+
+    /// Substituições por `replacement` a fazer em `content` com a consulta dos
+    /// resultados atuais.
+    pub fn replacements_in(&self, content: &Rope, replacement: &str) -> Vec<(Range<usize>, String)> {
+        let Some(search) = &self.search else {
+            return Vec::new();
+        };
+        search.find_all(content).into_iter()
+            .map(|range| {
+                let text = search.replacement_for(content, &range, replacement);
+                (range, text)
+            })
+            .collect()
+    }
+
+    /// Linhas de `content` que a substituição por `replacement` altera, com o texto
+    /// antes e depois.
+    pub fn preview_changes(&self, content: &Rope, replacement: &str) -> Vec<LineChange> {
+        let mut changes: Vec<LineChange> = Vec::new();
+        let mut replacements = self.replacements_in(content, replacement).into_iter().peekable();
+        while let Some((range, _)) = replacements.peek() {
+            let line_idx = content.char_to_line(range.start);
+            let line_start = content.line_to_char(line_idx);
+            let before = content.line(line_idx).to_string().trim_end_matches(['\n', '\r']).to_string();
+
+            // Aplica, de trás para frente, as substituições desta linha
+            let mut in_line = Vec::new();
+            while let Some((range, text)) = replacements.next_if(|(range, _)| content.char_to_line(range.start) == line_idx) {
+                in_line.push((range.start - line_start..range.end - line_start, text));
+            }
+            let mut after: Vec<char> = before.chars().collect();
+            for (range, text) in in_line.into_iter().rev() {
+                after.splice(range, text.chars());
+            }
+            changes.push(LineChange { line: line_idx + 1, before, after: after.into_iter().collect() });
+        }
+        changes
+    }
+}
+
+/// Aplica as substituições (trechos sem sobreposição, em ordem) direto no `Rope`,
+/// para arquivos que não estão abertos no editor.
+pub fn apply_replacements(content: &mut Rope, replacements: &[(Range<usize>, String)]) {
+    for (range, text) in replacements.iter().rev() {
+        content.remove(range.clone());
+        content.insert(range.start, text);
+    }
+}
+
+/// Varre `root` em paralelo e envia as ocorrências de cada arquivo.
+fn walk(root: &Path, search: &SearchQuery, tx: &mpsc::Sender<WalkerMessage>, cancel: &AtomicBool, ctx: &egui::Context) {
+    let total_hits = AtomicUsize::new(0);
+    let counter = &total_hits;
+    // O .gitignore vale mesmo fora de um repositório git
+    WalkBuilder::new(root).require_git(false).build_parallel().run(|| {
+        Box::new(move |entry| {
+            if cancel.load(Ordering::SeqCst) {
+                return WalkState::Quit;
+            }
+            let Ok(entry) = entry else {
+                return WalkState::Continue;
+            };
+            if !entry.file_type().is_some_and(|file_type| file_type.is_file()) {
+                return WalkState::Continue;
+            }
+            let Some(hits) = search_file(entry.path(), search) else {
+                return WalkState::Continue;
+            };
+            let hit_count = hits.len();
+            let file = FileHits { path: entry.into_path(), hits, selected: true };
+            if tx.send(WalkerMessage::File(file)).is_err() {
+                return WalkState::Quit; // A busca foi descartada
+            }
+            ctx.request_repaint();
+            if counter.fetch_add(hit_count, Ordering::SeqCst) + hit_count >= MAX_HITS {
+                cancel.store(true, Ordering::SeqCst);
+                return WalkState::Quit;
+            }
+            WalkState::Continue
+        })
+    });
+    let truncated = total_hits.load(Ordering::SeqCst) >= MAX_HITS;
+    let _ = tx.send(WalkerMessage::Done { truncated });
+    ctx.request_repaint();
+}
+
+/// Ocorrências em um arquivo, ou `None` se não houver nenhuma ou o arquivo for binário.
+/// O texto é decodificado como ao abrir o arquivo no editor (Latin-1, UTF-16...).
+fn search_file(path: &Path, search: &SearchQuery) -> Option<Vec<SearchHit>> {
+    let bytes = std::fs::read(path).ok()?;
+    // Texto em UTF-16 é cheio de bytes nulos; só arquivos sem BOM passam pela checagem
+    if bytes[..bytes.len().min(BINARY_CHECK_BYTES)].contains(&0) && Encoding::for_bom(&bytes).is_none() {
+        return None;
+    }
+    let encoding = FileEncoding::detect(&bytes);
+    let (text, _) = encoding.decode(&bytes);
+    let content = Rope::from_str(&text);
+    let matches = search.find_all(&content);
+    if matches.is_empty() {
+        return None;
+    }
+    Some(matches.into_iter().map(|range| hit_for(&content, range)).collect())
+}
+
+fn hit_for(content: &Rope, range: Range<usize>) -> SearchHit {
+    let line_idx = content.char_to_line(range.start);
+    let line = content.line(line_idx);
+    let column = range.start - content.line_to_char(line_idx);
+
+    // A prévia começa um pouco antes da ocorrência, sem a indentação
+    let indent = line.chars().take_while(|c| c.is_whitespace()).count().min(column);
+    let preview_start = column.saturating_sub(PREVIEW_CONTEXT_CHARS).max(indent);
+    let preview_chars: Vec<char> = line.chars().skip(preview_start).take(PREVIEW_MAX_CHARS)
+        .take_while(|c| *c != '\n' && *c != '\r')
+        .collect();
+    let byte_at = |char_idx: usize| preview_chars[..char_idx.min(preview_chars.len())].iter().map(|c| c.len_utf8()).sum::<usize>();
+    let match_start = column - preview_start;
+
+    SearchHit {
+        line: line_idx + 1,
+        column: column + 1,
+        preview_match: byte_at(match_start)..byte_at(match_start + range.len()),
+        preview: preview_chars.into_iter().collect(),
+    }
+}
@@ -0,0 +1,269 @@
+// src/core/search.rs
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+use ropey::Rope;
+
+/// Opções da busca no documento.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchOptions {
+    pub use_regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+}
+
+/// Consulta compilada para buscar direto no `Rope`, linha a linha, sem copiar o
+/// documento para uma `String`. Por isso uma ocorrência nunca atravessa uma quebra de
+/// linha, e ocorrências vazias (ex.: `a*`) são ignoradas.
+///
+/// As posições retornadas são intervalos de índices de caractere do `Rope`.
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    regex: Regex,
+    options: SearchOptions,
+}
+
+impl SearchQuery {
+    pub fn new(query: &str, options: SearchOptions) -> Result<Self, String> {
+        if query.is_empty() {
+            return Err("Consulta vazia".to_string());
+        }
+        let pattern = if options.use_regex { Cow::Borrowed(query) } else { Cow::Owned(regex::escape(query)) };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!options.case_sensitive)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self { regex, options })
+    }
+
+    /// Ocorrências que começam nas linhas `lines`, em ordem (para o destaque das
+    /// linhas visíveis).
+    pub fn find_in_lines(&self, content: &Rope, lines: Range<usize>) -> Vec<Range<usize>> {
+        let end = lines.end.min(content.len_lines());
+        (lines.start.min(end)..end)
+            .flat_map(|line_idx| self.matches_in_line(content, line_idx))
+            .collect()
+    }
+
+    /// Todas as ocorrências do documento, em ordem.
+    pub fn find_all(&self, content: &Rope) -> Vec<Range<usize>> {
+        self.find_in_lines(content, 0..content.len_lines())
+    }
+
+    /// Primeira ocorrência que começa em `from` ou depois, dando a volta no fim do
+    /// documento.
+    pub fn find_next(&self, content: &Rope, from: usize) -> Option<Range<usize>> {
+        let from = from.min(content.len_chars());
+        let start_line = content.char_to_line(from);
+        let after = (start_line..content.len_lines())
+            .flat_map(|line_idx| self.matches_in_line(content, line_idx))
+            .find(|range| range.start >= from);
+        after.or_else(|| {
+            (0..=start_line)
+                .flat_map(|line_idx| self.matches_in_line(content, line_idx))
+                .find(|range| range.start < from)
+        })
+    }
+
+    /// Última ocorrência que começa antes de `before`, dando a volta no início do
+    /// documento.
+    pub fn find_previous(&self, content: &Rope, before: usize) -> Option<Range<usize>> {
+        let before = before.min(content.len_chars());
+        let start_line = content.char_to_line(before);
+        let earlier = (0..=start_line).rev()
+            .flat_map(|line_idx| self.matches_in_line(content, line_idx).into_iter().rev())
+            .find(|range| range.start < before);
+        earlier.or_else(|| {
+            (start_line..content.len_lines()).rev()
+                .flat_map(|line_idx| self.matches_in_line(content, line_idx).into_iter().rev())
+                .find(|range| range.start >= before)
+        })
+    }
+
+    /// Se `range` é exatamente uma ocorrência (ex.: a seleção atual).
+    pub fn is_match(&self, content: &Rope, range: &Range<usize>) -> bool {
+        if range.is_empty() || range.end > content.len_chars() {
+            return false;
+        }
+        self.matches_in_line(content, content.char_to_line(range.start)).contains(range)
+    }
+
+    /// Texto que substitui a ocorrência `range`. Com expressão regular, `$1`, `${nome}`
+    /// etc. são trocados pelos grupos capturados; em texto simples, `template` vale como
+    /// está.
+    pub fn replacement_for(&self, content: &Rope, range: &Range<usize>, template: &str) -> String {
+        if !self.options.use_regex {
+            return template.to_string();
+        }
+        let line_idx = content.char_to_line(range.start);
+        let line_start = content.line_to_char(line_idx);
+        let line = line_text(content, line_idx);
+        let start_byte = char_to_byte_in(&line, range.start - line_start);
+
+        let mut replacement = String::new();
+        if let Some(captures) = self.regex.captures_at(&line, start_byte) {
+            captures.expand(template, &mut replacement);
+        }
+        replacement
+    }
+
+    /// Ocorrências dentro de uma linha (sem a quebra de linha final).
+    fn matches_in_line(&self, content: &Rope, line_idx: usize) -> Vec<Range<usize>> {
+        let line = line_text(content, line_idx);
+        let line_start = content.line_to_char(line_idx);
+
+        let mut matches = Vec::new();
+        let mut char_offset = 0; // Caracteres antes de `last_byte`, para não recontar a linha
+        let mut last_byte = 0;
+        let mut position = 0;
+        while position <= line.len() {
+            let Some(found) = self.regex.find_at(&line, position) else {
+                break;
+            };
+            if found.is_empty() || (self.options.whole_word && !is_whole_word(&line, found.range())) {
+                // Tenta de novo a partir do caractere seguinte ao início
+                position = found.start() + line[found.start()..].chars().next().map_or(1, char::len_utf8);
+                continue;
+            }
+            char_offset += line[last_byte..found.start()].chars().count();
+            let start = line_start + char_offset;
+            let len = found.as_str().chars().count();
+            matches.push(start..start + len);
+            char_offset += len;
+            last_byte = found.end();
+            position = found.end();
+        }
+        matches
+    }
+}
+
+/// Texto de uma linha sem `\n`/`\r\n`. As linhas do `Rope` quase sempre estão em um
+/// único pedaço, e então não há cópia.
+fn line_text(content: &Rope, line_idx: usize) -> Cow<'_, str> {
+    let line: Cow<str> = content.line(line_idx).into();
+    let trimmed_len = line.trim_end_matches(['\n', '\r']).len();
+    match line {
+        Cow::Borrowed(text) => Cow::Borrowed(&text[..trimmed_len]),
+        Cow::Owned(mut text) => {
+            text.truncate(trimmed_len);
+            Cow::Owned(text)
+        }
+    }
+}
+
+fn char_to_byte_in(text: &str, char_idx: usize) -> usize {
+    text.char_indices().nth(char_idx).map_or(text.len(), |(byte, _)| byte)
+}
+
+/// O trecho não está colado a letras, dígitos ou `_` dos dois lados.
+fn is_whole_word(line: &str, range: Range<usize>) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    !line[..range.start].chars().next_back().is_some_and(is_word)
+        && !line[range.end..].chars().next().is_some_and(is_word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(text: &str, use_regex: bool, case_sensitive: bool, whole_word: bool) -> SearchQuery {
+        SearchQuery::new(text, SearchOptions { use_regex, case_sensitive, whole_word }).unwrap()
+    }
+
+    fn find_all(query: &SearchQuery, text: &str) -> Vec<Range<usize>> {
+        query.find_all(&Rope::from_str(text))
+    }
+
+    #[test]
+    fn plain_text_ignores_case_by_default() {
+        assert_eq!(find_all(&query("foo", false, false, false), "Foo foo FOO"), vec![0..3, 4..7, 8..11]);
+        assert_eq!(find_all(&query("foo", false, true, false), "Foo foo FOO"), vec![4..7]);
+        assert_eq!(find_all(&query("ÇÃO", false, false, false), "ação"), vec![1..4]);
+    }
+
+    #[test]
+    fn plain_text_escapes_regex_characters() {
+        assert_eq!(find_all(&query("a.b", false, false, false), "axb a.b"), vec![4..7]);
+        assert_eq!(find_all(&query("(x)", false, false, false), "(x) x"), vec![0..3]);
+    }
+
+    #[test]
+    fn whole_word_skips_matches_inside_words() {
+        let search = query("foo", false, false, true);
+        assert_eq!(find_all(&search, "foo foobar _foo foo.bar éfoo"), vec![0..3, 16..19]);
+        // Uma ocorrência rejeitada não esconde a seguinte sobreposta
+        assert_eq!(find_all(&query("aa", false, false, true), "aaa aa"), vec![4..6]);
+    }
+
+    #[test]
+    fn regex_positions_are_char_indices() {
+        assert_eq!(find_all(&query(r"\d+", true, false, false), "é1 çã22\nñ333"), vec![1..2, 5..7, 9..12]);
+        assert_eq!(find_all(&query("a*", true, false, false), "baa b"), vec![1..3]);
+    }
+
+    #[test]
+    fn invalid_queries_are_errors() {
+        assert!(SearchQuery::new("", SearchOptions::default()).is_err());
+        assert!(SearchQuery::new("(", SearchOptions { use_regex: true, ..Default::default() }).is_err());
+        assert!(SearchQuery::new("(", SearchOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn matches_stop_at_line_breaks() {
+        let text = "foo\nfoo\r\nfoo\rfoo";
+        assert_eq!(find_all(&query("foo$", true, false, false), text), vec![0..3, 4..7, 9..12, 13..16]);
+        assert_eq!(find_all(&query("^foo", true, false, false), text), vec![0..3, 4..7, 9..12, 13..16]);
+        assert_eq!(find_all(&query(r"o\s+f", true, false, false), text), Vec::<Range<usize>>::new());
+        assert_eq!(find_all(&query("o\nf", false, false, false), text), Vec::<Range<usize>>::new());
+    }
+
+    #[test]
+    fn finds_matches_in_lines_split_across_rope_chunks() {
+        let text: String = (0..5000).map(|i| format!("línea {} alvo çç\n", i)).collect();
+        let content = Rope::from_str(&text);
+        // Alguma linha começa em um pedaço e termina no seguinte
+        assert!((0..content.len_lines()).any(|line_idx| matches!(line_text(&content, line_idx), Cow::Owned(_))));
+        let matches = query("alvo", false, true, true).find_all(&content);
+        assert_eq!(matches.len(), 5000);
+        for (line_idx, range) in matches.iter().enumerate().step_by(97) {
+            assert_eq!(content.slice(range.clone()), "alvo");
+            assert_eq!(content.char_to_line(range.start), line_idx);
+        }
+    }
+
+    #[test]
+    fn next_and_previous_wrap_around() {
+        let content = Rope::from_str("x ab\nab x");
+        let search = query("ab", false, false, false);
+        assert_eq!(search.find_next(&content, 0), Some(2..4));
+        assert_eq!(search.find_next(&content, 3), Some(5..7));
+        assert_eq!(search.find_next(&content, 6), Some(2..4));
+        assert_eq!(search.find_previous(&content, 5), Some(2..4));
+        assert_eq!(search.find_previous(&content, 2), Some(5..7));
+        assert_eq!(query("zz", false, false, false).find_next(&content, 0), None);
+    }
+
+    #[test]
+    fn is_match_checks_the_exact_range() {
+        let content = Rope::from_str("ab abc");
+        let search = query("ab", false, false, true);
+        assert!(search.is_match(&content, &(0..2)));
+        assert!(!search.is_match(&content, &(3..5)));
+        assert!(!search.is_match(&content, &(0..1)));
+        assert!(!search.is_match(&content, &(5..9)));
+    }
+
+    #[test]
+    fn replacement_expands_groups_only_with_regex() {
+        let content = Rope::from_str("x\nçã ana@casa");
+        let search = query(r"(\w+)@(\w+)", true, false, false);
+        let range = search.find_all(&content)[0].clone();
+        assert_eq!(range, 5..13);
+        assert_eq!(search.replacement_for(&content, &range, "$2 de ${1}"), "casa de ana");
+
+        let plain = query("ana", false, false, false);
+        assert_eq!(plain.replacement_for(&content, &(5..8), "$1"), "$1");
+    }
+}
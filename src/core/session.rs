@@ -0,0 +1,298 @@
+// src/core/session.rs
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+
+use crate::core::editor::{Cursor, Selection};
+use crate::core::file_handler::write_atomically;
+use crate::core::paths::{path_hash, user_state_dir};
+
+/// Intervalo entre as gravações periódicas da sessão (e dos backups), para que uma
+/// queda do programa perca no máximo esse tempo de trabalho.
+const SAVE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Arquivo que aponta a sessão aberta por último, restaurada ao iniciar.
+const LAST_SESSION_FILE: &str = "last_session.json";
+
+/// Pasta das sessões: `~/.local/state/lcode/sessions`.
+pub fn sessions_dir() -> Option<PathBuf> {
+    user_state_dir().map(|dir| dir.join("sessions"))
+}
+
+/// Estado de uma aba aberta.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabSession {
+    pub path: PathBuf,
+    pub cursor: Cursor,
+    pub selection: Option<Selection>,
+    pub scroll_offset: [f32; 2],
+    #[serde(default)]
+    pub has_backup: bool, // A aba tinha alterações não salvas, guardadas no backup
+    #[serde(default)]
+    pub encoding: Option<String>, // Nome da codificação do arquivo (`Encoding::name`)
+    #[serde(default)]
+    pub bom: bool,
+}
+
+/// Painéis abertos e seus tamanhos. Tamanhos ausentes ficam com o padrão do painel.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutSession {
+    pub side_panel_width: Option<f32>,
+    pub terminal_open: bool,
+    pub terminal_height: Option<f32>,
+    pub show_problems: bool,
+    pub problems_height: Option<f32>,
+    pub show_search: bool,
+    pub search_height: Option<f32>,
+}
+
+/// Estado do editor em um diretório: abas, diretórios expandidos e painéis.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub workspace: Option<PathBuf>,
+    pub tabs: Vec<TabSession>,
+    pub selected_tab: Option<usize>,
+    pub expanded_dirs: Vec<PathBuf>,
+    pub layout: LayoutSession,
+}
+
+/// Conteúdo de `last_session.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LastSession {
+    workspace: Option<PathBuf>,
+}
+
+/// Conteúdo de uma aba com alterações não salvas, para o backup de hot exit.
+#[derive(Debug, Clone, Copy)]
+pub struct DirtyBuffer<'a> {
+    pub path: &'a Path,
+    pub content: &'a Rope,
+    pub version: u64, // `TextEditor::version` do conteúdo; o backup só é regravado quando muda
+}
+
+enum BackupJob {
+    Write { backup_path: PathBuf, content: Rope },
+    RemoveStale { backups_dir: PathBuf, keep: Vec<String> }, // Nomes dos backups que ficam
+}
+
+impl std::fmt::Debug for BackupJob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackupJob::Write { backup_path, .. } => write!(f, "Write({})", backup_path.display()),
+            BackupJob::RemoveStale { backups_dir, .. } => write!(f, "RemoveStale({})", backups_dir.display()),
+        }
+    }
+}
+
+/// Grava e lê as sessões, uma por diretório aberto, e os backups das abas não salvas.
+///
+/// A sessão de `/projeto` fica em `sessions/projeto-<hash>.json` e os backups em
+/// `sessions/projeto-<hash>.backups/`. Os backups permitem fechar o editor (ou perdê-lo
+/// numa queda) sem salvar: na próxima abertura as abas voltam com as alterações.
+///
+/// O JSON da sessão é pequeno e gravado na hora; os backups, que podem ser arquivos
+/// grandes, são gravados por uma thread, como os arquivos de swap.
+#[derive(Debug)]
+pub struct SessionStore {
+    dir: Option<PathBuf>,
+    last_saved: Option<Session>,
+    backup_versions: HashMap<PathBuf, u64>, // Versão do texto já enviada para o backup de cada aba
+    last_save: Option<Instant>,
+    jobs: Option<mpsc::Sender<BackupJob>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Default for SessionStore {
+    fn default() -> Self {
+        Self::new(sessions_dir())
+    }
+}
+
+impl SessionStore {
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        let (jobs, worker) = match &dir {
+            Some(_) => {
+                let (tx, rx) = mpsc::channel();
+                let worker = std::thread::Builder::new()
+                    .name("lcode-session-backup".to_string())
+                    .spawn(move || run_worker(rx))
+                    .expect("Falha ao iniciar a thread de backups");
+                (Some(tx), Some(worker))
+            },
+            None => (None, None),
+        };
+        Self {
+            dir,
+            last_saved: None,
+            backup_versions: HashMap::new(),
+            last_save: None,
+            jobs,
+            worker,
+        }
+    }
+
+    /// Sessão usada por último, restaurada ao iniciar.
+    pub fn load_last(&self) -> Option<Session> {
+        let last: LastSession = read_json(&self.dir.as_ref()?.join(LAST_SESSION_FILE))?;
+        self.load(last.workspace.as_deref())
+    }
+
+    /// Sessão gravada para o diretório, se houver.
+    pub fn load(&self, workspace: Option<&Path>) -> Option<Session> {
+        read_json(&self.session_path(workspace)?)
+    }
+
+    /// Conteúdo não salvo de uma aba, guardado na última gravação da sessão.
+    pub fn read_backup(&self, workspace: Option<&Path>, path: &Path) -> io::Result<String> {
+        let backup = self.backup_path(workspace, path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Pasta de estado do usuário não encontrada"))?;
+        std::fs::read_to_string(backup)
+    }
+
+    /// Já passou o intervalo desde a última gravação.
+    pub fn is_due(&self) -> bool {
+        self.last_save.is_none_or(|last| last.elapsed() >= SAVE_INTERVAL)
+    }
+
+    /// Grava a sessão e envia para a thread os backups das abas não salvas. Backups de
+    /// abas que foram salvas ou fechadas são apagados; arquivos iguais aos já gravados
+    /// não são reescritos.
+    pub fn save(&mut self, session: &Session, dirty: &[DirtyBuffer]) -> io::Result<()> {
+        self.last_save = Some(Instant::now());
+        let (Some(dir), Some(session_path)) = (self.dir.clone(), self.session_path(session.workspace.as_deref())) else {
+            return Ok(());
+        };
+        let backups_dir = session_path.with_extension("backups");
+
+        if self.last_saved.as_ref().is_some_and(|last| last.workspace != session.workspace) {
+            self.backup_versions.clear();
+        }
+        self.backup_versions.retain(|path, _| dirty.iter().any(|buffer| buffer.path == path));
+        for buffer in dirty {
+            if self.backup_versions.get(buffer.path) == Some(&buffer.version) {
+                continue;
+            }
+            self.send(BackupJob::Write {
+                backup_path: backups_dir.join(backup_name(buffer.path)),
+                content: buffer.content.clone(),
+            });
+            self.backup_versions.insert(buffer.path.to_path_buf(), buffer.version);
+        }
+        self.send(BackupJob::RemoveStale {
+            backups_dir,
+            keep: dirty.iter().map(|buffer| backup_name(buffer.path)).collect(),
+        });
+
+        if self.last_saved.as_ref() != Some(session) {
+            std::fs::create_dir_all(&dir)?;
+            write_json(&session_path, session)?;
+            write_json(&dir.join(LAST_SESSION_FILE), &LastSession { workspace: session.workspace.clone() })?;
+            self.last_saved = Some(session.clone());
+        }
+        Ok(())
+    }
+
+    /// Encerramento: espera a thread terminar de gravar os backups pendentes.
+    pub fn shutdown(&mut self) {
+        self.jobs = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    fn send(&self, job: BackupJob) {
+        if let Some(jobs) = &self.jobs {
+            let _ = jobs.send(job);
+        }
+    }
+
+    fn session_path(&self, workspace: Option<&Path>) -> Option<PathBuf> {
+        Some(self.dir.as_ref()?.join(format!("{}.json", session_name(workspace))))
+    }
+
+    fn backup_path(&self, workspace: Option<&Path>, path: &Path) -> Option<PathBuf> {
+        Some(self.session_path(workspace)?.with_extension("backups").join(backup_name(path)))
+    }
+}
+
+/// Nome do arquivo da sessão: o nome do diretório, para facilitar a leitura, mais o
+/// hash do caminho completo, para diferenciar diretórios de mesmo nome.
+fn session_name(workspace: Option<&Path>) -> String {
+    let Some(workspace) = workspace else {
+        return "sem-projeto".to_string();
+    };
+    let name: String = workspace.file_name().unwrap_or_default().to_string_lossy()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}-{:016x}", name, path_hash(workspace))
+}
+
+fn backup_name(path: &Path) -> String {
+    format!("{:016x}.txt", path_hash(path))
+}
+
+/// Grava e apaga os backups na ordem dos pedidos. Termina quando o `SessionStore`
+/// fecha o canal.
+fn run_worker(rx: mpsc::Receiver<BackupJob>) {
+    while let Ok(job) = rx.recv() {
+        match job {
+            BackupJob::Write { backup_path, content } => {
+                if let Err(e) = write_backup(&backup_path, &content) {
+                    eprintln!("Erro ao gravar o backup {}: {}", backup_path.display(), e);
+                }
+            }
+            BackupJob::RemoveStale { backups_dir, keep } => remove_stale_backups(&backups_dir, &keep),
+        }
+    }
+}
+
+fn write_backup(backup_path: &Path, content: &Rope) -> io::Result<()> {
+    if let Some(parent) = backup_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_atomically(backup_path, |writer| content.write_to(writer))
+}
+
+fn remove_stale_backups(backups_dir: &Path, keep: &[String]) {
+    let Ok(entries) = std::fs::read_dir(backups_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if !keep.iter().any(|name| entry.file_name() == name.as_str())
+            && let Err(e) = std::fs::remove_file(entry.path())
+        {
+            eprintln!("Falha ao apagar o backup {}: {}", entry.path().display(), e);
+        }
+    }
+    if keep.is_empty() {
+        let _ = std::fs::remove_dir(backups_dir);
+    }
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Option<T> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            eprintln!("Erro ao ler {}: {}", path.display(), e);
+            return None;
+        },
+    };
+    serde_json::from_str(&text)
+        .map_err(|e| eprintln!("Sessão inválida em {}: {}", path.display(), e))
+        .ok()
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    write_atomically(path, |writer| serde_json::to_writer_pretty(writer, value).map_err(io::Error::from))
+}
@@ -0,0 +1,245 @@
+// src/core/settings.rs
+
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::file_handler::{write_atomically, DEFAULT_MMAP_THRESHOLD};
+use crate::core::paths::user_config_dir;
+use crate::syntax_highlighting::themes::DEFAULT_THEME;
+use crate::terminal::emulator::DEFAULT_SCROLLBACK_LINES;
+
+/// Caminho do arquivo de configurações do projeto, relativo à raiz do projeto.
+pub const WORKSPACE_SETTINGS_FILE: &str = ".lcode/settings.toml";
+
+/// Intervalo entre as verificações de alteração dos arquivos de configuração.
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+// Valores aceitos; fora deles a chave é ignorada e o erro aparece na interface
+pub const FONT_SIZE_RANGE: RangeInclusive<f32> = 6.0..=72.0;
+pub const LINE_HEIGHT_RANGE: RangeInclusive<f32> = 8.0..=120.0;
+pub const GUTTER_WIDTH_RANGE: RangeInclusive<f32> = 20.0..=300.0;
+pub const SIDE_PANEL_WIDTH_RANGE: RangeInclusive<f32> = 100.0..=1000.0;
+pub const TERMINAL_SCROLLBACK_RANGE: RangeInclusive<usize> = 0..=1_000_000;
+
+/// Modelo gravado quando o arquivo do projeto é aberto pela primeira vez.
+pub const WORKSPACE_SETTINGS_TEMPLATE: &str = r#"# Configurações deste projeto. Cada chave aqui substitui a de ~/.config/lcode/settings.toml.
+#
+# font_size = 14.0
+# theme = "InspiredGitHub"
+"#;
+
+/// Caminho do arquivo de configurações do usuário: `~/.config/lcode/settings.toml`.
+pub fn user_settings_path() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join("settings.toml"))
+}
+
+/// Configurações do editor. Chaves ausentes no arquivo ficam com o valor padrão.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub font_size: f32,                // Tamanho da fonte do editor, em pontos
+    pub line_height: f32,              // Altura de cada linha do editor
+    pub line_number_gutter_width: f32, // Largura da coluna de números de linha
+    pub side_panel_width: f32,         // Largura do explorador de arquivos
+    pub theme: String,                 // Tema do realce de sintaxe
+    pub mmap_threshold: u64,           // Arquivos a partir deste tamanho (bytes) são lidos com mmap
+    pub terminal_scrollback: usize,    // Linhas no histórico de rolagem de cada sessão do terminal
+    pub vim_mode: bool,                // O editor começa no modo Vim
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            font_size: 13.0,
+            line_height: 16.0,
+            line_number_gutter_width: 60.0,
+            side_panel_width: 200.0,
+            theme: DEFAULT_THEME.to_string(),
+            mmap_threshold: DEFAULT_MMAP_THRESHOLD,
+            terminal_scrollback: DEFAULT_SCROLLBACK_LINES,
+            vim_mode: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Valores fora dos intervalos aceitos. O tema só é conferido por quem conhece os
+    /// temas carregados.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut check = |name: &str, value: f32, range: RangeInclusive<f32>| {
+            if !range.contains(&value) {
+                errors.push(format!("{}: {} fora do intervalo {}..={}", name, value, range.start(), range.end()));
+            }
+        };
+        check("font_size", self.font_size, FONT_SIZE_RANGE);
+        check("line_height", self.line_height, LINE_HEIGHT_RANGE);
+        check("line_number_gutter_width", self.line_number_gutter_width, GUTTER_WIDTH_RANGE);
+        check("side_panel_width", self.side_panel_width, SIDE_PANEL_WIDTH_RANGE);
+        if !TERMINAL_SCROLLBACK_RANGE.contains(&self.terminal_scrollback) {
+            errors.push(format!(
+                "terminal_scrollback: {} fora do intervalo {}..={}",
+                self.terminal_scrollback, TERMINAL_SCROLLBACK_RANGE.start(), TERMINAL_SCROLLBACK_RANGE.end(),
+            ));
+        }
+        if self.theme.trim().is_empty() {
+            errors.push("theme: o nome do tema não pode ser vazio".to_string());
+        }
+        errors
+    }
+}
+
+/// Configurações em uso: os padrões, o arquivo do usuário e o do projeto aberto, nessa
+/// ordem de precedência crescente.
+///
+/// Cada chave é lida e validada separadamente: uma chave inválida é ignorada (vale a
+/// do arquivo anterior ou o padrão) e o erro fica em `errors` para a interface mostrar.
+/// Os arquivos são relidos quando mudam no disco.
+#[derive(Debug, Default)]
+pub struct SettingsStore {
+    pub values: Settings,           // Resultado final, usado pelo editor
+    pub user: Settings,             // Só o arquivo do usuário (o que a tela de configurações edita)
+    pub overridden: Vec<String>,    // Chaves definidas pelo arquivo do projeto
+    pub errors: Vec<String>,
+    user_path: Option<PathBuf>,
+    workspace_path: Option<PathBuf>,
+    loaded_modified: [Option<SystemTime>; 2], // Datas de modificação dos arquivos lidos
+    last_check: Option<Instant>,
+    revision: u64,
+}
+
+impl SettingsStore {
+    /// Lê `~/.config/lcode/settings.toml` e, com um projeto aberto, `.lcode/settings.toml`.
+    pub fn load(workspace: Option<&Path>) -> Self {
+        let mut store = Self { user_path: user_settings_path(), ..Self::default() };
+        store.set_workspace(workspace);
+        store
+    }
+
+    /// Troca o projeto cujas configurações sobrescrevem as do usuário.
+    pub fn set_workspace(&mut self, workspace: Option<&Path>) {
+        self.workspace_path = workspace.map(|dir| dir.join(WORKSPACE_SETTINGS_FILE));
+        self.reload();
+    }
+
+    pub fn user_path(&self) -> Option<&Path> {
+        self.user_path.as_deref()
+    }
+
+    pub fn workspace_path(&self) -> Option<&Path> {
+        self.workspace_path.as_deref()
+    }
+
+    /// Muda a cada leitura dos arquivos.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Relê os arquivos e refaz as configurações.
+    pub fn reload(&mut self) {
+        self.errors.clear();
+        self.last_check = Some(Instant::now());
+        self.loaded_modified = [
+            self.user_path.as_deref().and_then(modified_time),
+            self.workspace_path.as_deref().and_then(modified_time),
+        ];
+        self.revision += 1;
+
+        let mut layers = Vec::new();
+        for path in [&self.user_path, &self.workspace_path].into_iter().flatten() {
+            match read_settings_table(path) {
+                Ok(table) => layers.push((path.as_path(), table)),
+                Err(e) => self.errors.push(e.to_string()),
+            }
+        }
+        let workspace_table = layers.iter()
+            .find(|(path, _)| Some(*path) == self.workspace_path.as_deref())
+            .map(|(_, table)| table);
+        self.overridden = workspace_table.map(|table| table.keys().cloned().collect()).unwrap_or_default();
+
+        let user_layers: Vec<_> = layers.iter()
+            .filter(|(path, _)| Some(*path) == self.user_path.as_deref())
+            .cloned()
+            .collect();
+        self.user = merge_layers(&user_layers, &mut Vec::new());
+        self.values = merge_layers(&layers, &mut self.errors);
+
+        for error in &self.errors {
+            eprintln!("Configurações: {}", error);
+        }
+    }
+
+    /// Relê os arquivos se algum mudou desde a última leitura. A verificação é feita no
+    /// máximo uma vez por segundo. Retorna se houve recarga.
+    pub fn reload_if_changed(&mut self) -> bool {
+        if self.last_check.is_some_and(|last| last.elapsed() < RELOAD_INTERVAL) {
+            return false;
+        }
+        self.last_check = Some(Instant::now());
+        let modified = [
+            self.user_path.as_deref().and_then(modified_time),
+            self.workspace_path.as_deref().and_then(modified_time),
+        ];
+        if modified == self.loaded_modified {
+            return false;
+        }
+        self.reload();
+        eprintln!("Configurações recarregadas.");
+        true
+    }
+
+    /// Grava as configurações no arquivo do usuário (de forma atômica) e as relê.
+    pub fn save_user(&mut self, settings: &Settings) -> io::Result<()> {
+        let Some(path) = self.user_path.clone() else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Pasta de configuração do usuário não encontrada"));
+        };
+        let text = toml::to_string(settings).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        write_atomically(&path, |writer| {
+            write!(writer, "# Configurações do lcode. O arquivo é relido automaticamente ao ser salvo.\n\n{}", text)
+        })?;
+        self.reload();
+        Ok(())
+    }
+}
+
+/// Junta as camadas em ordem: cada chave válida substitui a das camadas anteriores.
+/// Chaves desconhecidas, de tipo errado ou fora do intervalo vão para `errors`.
+fn merge_layers(layers: &[(&Path, toml::Table)], errors: &mut Vec<String>) -> Settings {
+    let mut valid = toml::Table::new();
+    for (path, table) in layers {
+        for (key, value) in table {
+            let single = toml::Table::from_iter([(key.clone(), value.clone())]);
+            let problem = match toml::Value::Table(single).try_into::<Settings>() {
+                Ok(settings) => settings.validate().into_iter().next(),
+                Err(e) => Some(format!("{}: {}", key, e.message())),
+            };
+            match problem {
+                Some(problem) => errors.push(format!("{}: {}", path.display(), problem)),
+                None => {
+                    valid.insert(key.clone(), value.clone());
+                },
+            }
+        }
+    }
+    toml::Value::Table(valid).try_into().unwrap_or_default()
+}
+
+fn read_settings_table(path: &Path) -> io::Result<toml::Table> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(toml::Table::new()),
+        Err(e) => return Err(e),
+    };
+    text.parse().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
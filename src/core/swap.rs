@@ -0,0 +1,243 @@
+// src/core/swap.rs
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+
+use crate::core::file_handler::write_atomically;
+use crate::core::paths::{path_hash, user_state_dir};
+use crate::core::session::DirtyBuffer;
+
+/// Intervalo entre as cópias das abas modificadas para os arquivos de swap.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Primeira linha de todo arquivo de swap, antes do cabeçalho em JSON.
+const SWAP_MAGIC: &str = "lcode-swap 1";
+
+/// Pasta dos arquivos de swap: `~/.local/state/lcode/swap`.
+pub fn swap_dir() -> Option<PathBuf> {
+    user_state_dir().map(|dir| dir.join("swap"))
+}
+
+/// Cabeçalho gravado antes do conteúdo.
+#[derive(Debug, Serialize, Deserialize)]
+struct SwapHeader {
+    path: PathBuf,
+    saved_at: u64, // Segundos desde 1970
+}
+
+/// Conteúdo de uma aba encontrado num arquivo de swap deixado por uma execução que
+/// não terminou normalmente.
+#[derive(Debug, Clone)]
+pub struct RecoveredSwap {
+    pub swap_path: PathBuf,
+    pub path: PathBuf, // Arquivo editado quando o swap foi gravado
+    pub content: String,
+    pub saved_at: SystemTime,
+}
+
+enum SwapJob {
+    Write { swap_path: PathBuf, header: SwapHeader, content: Rope },
+    Remove(PathBuf),
+}
+
+/// Cópias periódicas das abas modificadas, gravadas por uma thread para não travar a
+/// interface (clonar o `Rope` é barato).
+///
+/// Cada execução grava `<hash do caminho>.<pid>.swp` e apaga os próprios arquivos ao
+/// fechar normalmente. Arquivos de um processo que não existe mais são sobras de uma
+/// queda e podem ser recuperados com `find_leftovers`.
+pub struct SwapFiles {
+    dir: Option<PathBuf>,
+    jobs: Option<mpsc::Sender<SwapJob>>,
+    worker: Option<JoinHandle<()>>,
+    written: HashMap<PathBuf, u64>, // Versão do texto de cada aba já enviada para o swap
+    last_snapshot: Option<Instant>,
+}
+
+impl std::fmt::Debug for SwapFiles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SwapFiles")
+            .field("dir", &self.dir)
+            .field("written", &self.written)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for SwapFiles {
+    fn default() -> Self {
+        Self::new(swap_dir())
+    }
+}
+
+impl SwapFiles {
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        let (jobs, worker) = match &dir {
+            Some(_) => {
+                let (tx, rx) = mpsc::channel();
+                let worker = std::thread::Builder::new()
+                    .name("lcode-swap".to_string())
+                    .spawn(move || run_worker(rx))
+                    .expect("Falha ao iniciar a thread de swap");
+                (Some(tx), Some(worker))
+            },
+            None => (None, None),
+        };
+        Self { dir, jobs, worker, written: HashMap::new(), last_snapshot: None }
+    }
+
+    /// Já passou o intervalo desde a última cópia.
+    pub fn is_due(&self) -> bool {
+        self.last_snapshot.is_none_or(|last| last.elapsed() >= SNAPSHOT_INTERVAL)
+    }
+
+    /// Envia para a thread as abas modificadas desde a última cópia e apaga o swap das
+    /// que foram salvas ou fechadas.
+    pub fn snapshot(&mut self, dirty: &[DirtyBuffer]) {
+        self.last_snapshot = Some(Instant::now());
+        let Some(dir) = self.dir.clone() else {
+            return;
+        };
+
+        let stale: Vec<PathBuf> = self.written.keys()
+            .filter(|path| !dirty.iter().any(|buffer| buffer.path == *path))
+            .cloned()
+            .collect();
+        for path in stale {
+            self.written.remove(&path);
+            self.send(SwapJob::Remove(own_swap_path(&dir, &path)));
+        }
+
+        let saved_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+        for buffer in dirty {
+            if self.written.get(buffer.path) == Some(&buffer.version) {
+                continue;
+            }
+            self.written.insert(buffer.path.to_path_buf(), buffer.version);
+            self.send(SwapJob::Write {
+                swap_path: own_swap_path(&dir, buffer.path),
+                header: SwapHeader { path: buffer.path.to_path_buf(), saved_at },
+                content: buffer.content.clone(),
+            });
+        }
+    }
+
+    /// Encerramento normal: apaga os arquivos desta execução e espera a thread terminar.
+    /// As alterações não salvas ficam no backup da sessão.
+    pub fn shutdown(&mut self) {
+        if let Some(dir) = self.dir.clone() {
+            for path in std::mem::take(&mut self.written).into_keys() {
+                self.send(SwapJob::Remove(own_swap_path(&dir, &path)));
+            }
+        }
+        self.jobs = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    /// Arquivos de swap deixados por execuções que não terminaram normalmente.
+    pub fn find_leftovers(&self) -> Vec<RecoveredSwap> {
+        let Some(entries) = self.dir.as_deref().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+            return Vec::new();
+        };
+        let mut leftovers: Vec<RecoveredSwap> = entries.flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "swp"))
+            .filter(|path| swap_owner(path).is_some_and(|pid| pid != std::process::id() && !process_is_running(pid)))
+            .filter_map(|path| match read_swap(&path) {
+                Ok(recovered) => Some(recovered),
+                Err(e) => {
+                    eprintln!("Arquivo de swap ilegível {}: {}", path.display(), e);
+                    None
+                },
+            })
+            .collect();
+        leftovers.sort_by(|a, b| a.path.cmp(&b.path));
+        leftovers
+    }
+
+    fn send(&self, job: SwapJob) {
+        if let Some(jobs) = &self.jobs {
+            let _ = jobs.send(job);
+        }
+    }
+}
+
+/// Apaga um arquivo de swap já recuperado ou descartado.
+pub fn discard_swap(swap_path: &Path) -> io::Result<()> {
+    match std::fs::remove_file(swap_path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Grava e apaga os arquivos na ordem dos pedidos. Termina quando o `SwapFiles` fecha
+/// o canal.
+fn run_worker(rx: mpsc::Receiver<SwapJob>) {
+    while let Ok(job) = rx.recv() {
+        let result = match &job {
+            SwapJob::Write { swap_path, header, content } => write_swap(swap_path, header, content),
+            SwapJob::Remove(swap_path) => discard_swap(swap_path),
+        };
+        if let Err(e) = result {
+            let swap_path = match &job {
+                SwapJob::Write { swap_path, .. } | SwapJob::Remove(swap_path) => swap_path,
+            };
+            eprintln!("Erro no arquivo de swap {}: {}", swap_path.display(), e);
+        }
+    }
+}
+
+fn own_swap_path(dir: &Path, path: &Path) -> PathBuf {
+    dir.join(format!("{:016x}.{}.swp", path_hash(path), std::process::id()))
+}
+
+/// Pid do processo que gravou o swap, tirado do nome do arquivo.
+fn swap_owner(swap_path: &Path) -> Option<u32> {
+    let stem = swap_path.file_stem()?.to_str()?;
+    stem.rsplit_once('.')?.1.parse().ok()
+}
+
+#[cfg(target_os = "linux")]
+fn process_is_running(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+// Sem /proc não dá para saber; o swap é oferecido para recuperação de qualquer forma
+#[cfg(not(target_os = "linux"))]
+fn process_is_running(_pid: u32) -> bool {
+    false
+}
+
+fn write_swap(swap_path: &Path, header: &SwapHeader, content: &Rope) -> io::Result<()> {
+    if let Some(parent) = swap_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let header = serde_json::to_string(header).map_err(io::Error::from)?;
+    write_atomically(swap_path, |writer| {
+        writeln!(writer, "{}", SWAP_MAGIC)?;
+        writeln!(writer, "{}", header)?;
+        content.write_to(writer)
+    })
+}
+
+fn read_swap(swap_path: &Path) -> io::Result<RecoveredSwap> {
+    let text = std::fs::read_to_string(swap_path)?;
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "cabeçalho inválido");
+    let rest = text.strip_prefix(SWAP_MAGIC).and_then(|rest| rest.strip_prefix('\n')).ok_or_else(invalid)?;
+    let (header, content) = rest.split_once('\n').ok_or_else(invalid)?;
+    let header: SwapHeader = serde_json::from_str(header).map_err(io::Error::from)?;
+    Ok(RecoveredSwap {
+        swap_path: swap_path.to_path_buf(),
+        path: header.path,
+        content: content.to_string(),
+        saved_at: UNIX_EPOCH + Duration::from_secs(header.saved_at),
+    })
+}
@@ -0,0 +1,1230 @@
+// src/core/vim.rs
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use ropey::Rope;
+
+use crate::core::editor::{line_len_without_newline, Cursor, Selection, TextEditor};
+use crate::core::line_ending::strip_line_break;
+
+/// Colunas deslocadas por `>` e `<`.
+const SHIFT_WIDTH: usize = 4;
+/// Maior contador aceito. Cabe qualquer número de linha (`G`); os movimentos param
+/// sozinhos no fim do texto.
+const MAX_COUNT: usize = 999_999_999;
+/// Limite do texto colado de uma vez por `p` com contador.
+const MAX_PUT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Modo de edição do Vim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VimMode {
+    #[default]
+    Normal,
+    Insert,
+    Visual,
+    VisualLine,
+    CommandLine, // Digitando um comando depois de `:`
+}
+
+/// Tecla recebida pela camada do Vim, já separada dos eventos da interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VimKey {
+    Char(char),
+    Ctrl(char), // Ctrl+letra, em minúscula
+    Escape,
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Comando de `:` que depende da aplicação (a aba é dela).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExCommand {
+    Save,                  // :w
+    Close { force: bool }, // :q e :q!
+    SaveAndClose,          // :wq e :x
+}
+
+/// O que uma tecla causou além da mudança de modo e de cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct VimEffect {
+    pub modified: bool, // O texto mudou
+    pub command: Option<ExCommand>,
+}
+
+impl VimEffect {
+    fn merge(&mut self, other: VimEffect) {
+        self.modified |= other.modified;
+        self.command = other.command.or(self.command);
+    }
+}
+
+/// Conteúdo de um registrador; `linewise` quando veio de linhas inteiras (`yy`, `dd`).
+#[derive(Debug, Clone, Default)]
+struct Register {
+    text: String,
+    linewise: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+    Indent,
+    Outdent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+    WordForward { big: bool },
+    WordBackward { big: bool },
+    WordEnd { big: bool },
+    FirstLine, // gg (ou a linha do contador)
+    LastLine,  // G (ou a linha do contador)
+    MatchingBracket,
+    Find { ch: char, forward: bool, till: bool }, // f, F, t, T
+    RepeatFind { reverse: bool },                 // ; e ,
+}
+
+/// Como o trecho de um movimento é usado por um operador.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MotionKind {
+    Exclusive, // Até antes do destino
+    Inclusive, // Incluindo o caractere do destino
+    Linewise,  // Linhas inteiras
+}
+
+impl Motion {
+    fn kind(self) -> MotionKind {
+        match self {
+            Motion::Up | Motion::Down | Motion::FirstLine | Motion::LastLine => MotionKind::Linewise,
+            Motion::LineEnd | Motion::WordEnd { .. } | Motion::MatchingBracket => MotionKind::Inclusive,
+            Motion::Find { forward, .. } => if forward { MotionKind::Inclusive } else { MotionKind::Exclusive },
+            _ => MotionKind::Exclusive,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OperatorTarget {
+    Motion(Motion, Option<usize>), // Movimento e o contador digitado depois do operador
+    Lines,                         // Operador dobrado: dd, cc, yy, >>, <<
+    Selection,                     // Seleção do modo visual
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Insert,         // i
+    Append,         // a
+    InsertAtStart,  // I
+    AppendAtEnd,    // A
+    OpenBelow,      // o
+    OpenAbove,      // O
+    Put { before: bool },
+    Replace(char),  // r
+    Undo,
+    Redo,
+    Visual { linewise: bool },
+    SwapAnchor,     // o no modo visual
+    Repeat,         // .
+    CommandLine,    // :
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CommandKind {
+    Move(Motion),
+    Operate(Operator, OperatorTarget),
+    Action(Action),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Command {
+    register: Option<char>,
+    count: Option<usize>,
+    kind: CommandKind,
+}
+
+/// Resultado da leitura das teclas digitadas até agora.
+enum Parsed<T> {
+    Done(T, usize), // Valor e quantas teclas ele usou
+    NeedMore,
+    Invalid,
+}
+
+/// Trecho sobre o qual um operador age.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Span {
+    Chars(Range<usize>),
+    Lines(usize, usize), // Primeira e última linha, inclusive
+}
+
+/// Camada de edição modal (Vim) por cima do `TextEditor`.
+///
+/// Recebe teclas já traduzidas e age sobre o editor pelos mesmos métodos usados pelo
+/// resto da interface, então tudo entra no histórico de desfazer: cada comando do modo
+/// normal (e cada passagem pelo modo de inserção) é um único passo.
+#[derive(Debug, Default)]
+pub struct VimState {
+    pub mode: VimMode,
+    pub command_line: String, // Texto depois de `:`
+    pending: Vec<VimKey>,     // Teclas do comando ainda incompleto
+    registers: HashMap<char, Register>,
+    last_find: Option<(char, bool, bool)>, // Último f/F/t/T: caractere, para frente, até antes
+    visual_anchor: usize,                  // Onde a seleção visual começou
+    preferred_column: Option<(usize, usize)>, // Coluna mantida por j/k e onde o cursor ficou
+    last_change: Vec<VimKey>,              // Teclas da última alteração, repetida por `.`
+    recording: Option<Vec<VimKey>>,        // Alteração em andamento no modo de inserção
+    replaying: bool,
+}
+
+impl VimState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Texto do modo para a barra de status.
+    pub fn mode_label(&self) -> &'static str {
+        match self.mode {
+            VimMode::Normal => "NORMAL",
+            VimMode::Insert => "-- INSERT --",
+            VimMode::Visual => "-- VISUAL --",
+            VimMode::VisualLine => "-- VISUAL LINE --",
+            VimMode::CommandLine => ":",
+        }
+    }
+
+    /// Teclas do comando em andamento (ex.: `2d`), para a barra de status.
+    pub fn pending_keys(&self) -> String {
+        self.pending.iter().map(|key| match key {
+            VimKey::Char(ch) => ch.to_string(),
+            VimKey::Ctrl(ch) => format!("^{}", ch.to_ascii_uppercase()),
+            _ => String::new(),
+        }).collect()
+    }
+
+    /// O cursor é desenhado como um bloco sobre o caractere (modos normal e visual).
+    pub fn block_cursor(&self) -> bool {
+        !matches!(self.mode, VimMode::Insert | VimMode::CommandLine)
+    }
+
+    /// Volta ao modo normal, abandonando o que estava em andamento (ex.: ao desativar o
+    /// modo Vim ou trocar de aba).
+    pub fn reset(&mut self, editor: &mut TextEditor) {
+        if self.mode == VimMode::Insert {
+            editor.end_undo_group();
+        }
+        if matches!(self.mode, VimMode::Visual | VimMode::VisualLine) {
+            editor.selection = None;
+        }
+        self.mode = VimMode::Normal;
+        self.pending.clear();
+        self.command_line.clear();
+        self.recording = None;
+    }
+
+    /// Processa uma tecla no modo atual.
+    pub fn handle_key(&mut self, key: VimKey, editor: &mut TextEditor, content: &mut Rope) -> VimEffect {
+        match self.mode {
+            VimMode::Insert => self.insert_key(key, editor, content),
+            VimMode::CommandLine => self.command_line_key(key, editor, content),
+            VimMode::Normal | VimMode::Visual | VimMode::VisualLine => {
+                if key == VimKey::Escape {
+                    self.pending.clear();
+                    if self.mode != VimMode::Normal {
+                        self.mode = VimMode::Normal;
+                        editor.selection = None;
+                    }
+                    return VimEffect::default();
+                }
+                self.pending.push(key);
+                let visual = self.mode != VimMode::Normal;
+                match parse_command(&self.pending, visual) {
+                    Parsed::NeedMore => VimEffect::default(),
+                    Parsed::Invalid => {
+                        self.pending.clear();
+                        VimEffect::default()
+                    }
+                    Parsed::Done(command, _) => {
+                        let keys = std::mem::take(&mut self.pending);
+                        self.execute(command, keys, editor, content)
+                    }
+                }
+            }
+        }
+    }
+
+    fn insert_key(&mut self, key: VimKey, editor: &mut TextEditor, content: &mut Rope) -> VimEffect {
+        if let Some(recording) = &mut self.recording {
+            recording.push(key);
+        }
+        let mut modified = true;
+        match key {
+            VimKey::Char(ch) => editor.insert_char(content, ch),
+            VimKey::Enter => editor.new_line(content),
+            VimKey::Backspace => editor.delete_char_before_cursor(content),
+            VimKey::Delete => editor.delete_char_after_cursor(content),
+            VimKey::Left | VimKey::Right | VimKey::Up | VimKey::Down => {
+                modified = false;
+                match key {
+                    VimKey::Left => editor.move_cursor_left(content),
+                    VimKey::Right => editor.move_cursor_right(content),
+                    VimKey::Up => editor.move_cursor_up(content),
+                    _ => editor.move_cursor_down(content),
+                }
+            }
+            VimKey::Ctrl(_) => modified = false,
+            VimKey::Escape => {
+                modified = false;
+                editor.end_undo_group();
+                self.mode = VimMode::Normal;
+                if let Some(recording) = self.recording.take() {
+                    self.last_change = recording;
+                }
+                // Ao sair da inserção o cursor volta para cima do último caractere digitado
+                let pos = cursor_pos(content, editor.cursor);
+                let back = if editor.cursor.char_idx > 0 { pos - 1 } else { pos };
+                set_cursor(editor, content, clamp_normal(content, back));
+            }
+        }
+        VimEffect { modified, command: None }
+    }
+
+    fn command_line_key(&mut self, key: VimKey, editor: &mut TextEditor, content: &mut Rope) -> VimEffect {
+        match key {
+            VimKey::Char(ch) => self.command_line.push(ch),
+            VimKey::Backspace if self.command_line.is_empty() => self.mode = VimMode::Normal,
+            VimKey::Backspace => {
+                self.command_line.pop();
+            }
+            VimKey::Escape => {
+                self.command_line.clear();
+                self.mode = VimMode::Normal;
+            }
+            VimKey::Enter => {
+                self.mode = VimMode::Normal;
+                let line = std::mem::take(&mut self.command_line);
+                return self.run_ex_command(line.trim(), editor, content);
+            }
+            _ => {}
+        }
+        VimEffect::default()
+    }
+
+    fn run_ex_command(&mut self, command: &str, editor: &mut TextEditor, content: &Rope) -> VimEffect {
+        let command = match command {
+            "w" => Some(ExCommand::Save),
+            "q" => Some(ExCommand::Close { force: false }),
+            "q!" => Some(ExCommand::Close { force: true }),
+            "wq" | "x" => Some(ExCommand::SaveAndClose),
+            _ => {
+                if let Ok(line) = command.parse::<usize>() {
+                    let line = line.saturating_sub(1).min(last_line(content));
+                    set_cursor(editor, content, first_non_blank(content, line));
+                    editor.scroll_to_cursor = true;
+                } else if !command.is_empty() {
+                    eprintln!("Vim: comando não suportado ':{}'", command);
+                }
+                None
+            }
+        };
+        VimEffect { modified: false, command }
+    }
+
+    fn execute(&mut self, command: Command, keys: Vec<VimKey>, editor: &mut TextEditor, content: &mut Rope) -> VimEffect {
+        let pos = clamp_normal(content, cursor_pos(content, editor.cursor));
+        let count = command.count.unwrap_or(1);
+        let visual = matches!(self.mode, VimMode::Visual | VimMode::VisualLine);
+
+        let is_change = match command.kind {
+            CommandKind::Move(_) => false,
+            CommandKind::Operate(operator, _) => operator != Operator::Yank,
+            CommandKind::Action(action) => matches!(action,
+                Action::Insert | Action::Append | Action::InsertAtStart | Action::AppendAtEnd
+                | Action::OpenBelow | Action::OpenAbove | Action::Put { .. } | Action::Replace(_)),
+        };
+        if is_change {
+            editor.begin_undo_group();
+        }
+
+        let mut effect = VimEffect::default();
+        match command.kind {
+            CommandKind::Move(motion) => {
+                if let Some((target, column)) = self.motion_target(content, pos, motion, command.count) {
+                    let target = clamp_normal(content, target);
+                    set_cursor(editor, content, target);
+                    self.preferred_column = column.map(|column| (column, target));
+                    if matches!(motion, Motion::FirstLine | Motion::LastLine | Motion::MatchingBracket) {
+                        editor.scroll_to_cursor = true;
+                    }
+                }
+            }
+            CommandKind::Operate(operator, target) => {
+                let span = match target {
+                    OperatorTarget::Lines => {
+                        let line = content.char_to_line(pos);
+                        Some(Span::Lines(line, line.saturating_add(count - 1).min(last_line(content))))
+                    }
+                    OperatorTarget::Selection => Some(self.visual_span(content, pos)),
+                    OperatorTarget::Motion(motion, motion_count) => {
+                        let total = match (command.count, motion_count) {
+                            (None, None) => None,
+                            (first, second) => Some(first.unwrap_or(1).saturating_mul(second.unwrap_or(1))),
+                        };
+                        self.motion_span(content, pos, motion, total, operator)
+                    }
+                };
+                if visual {
+                    self.mode = VimMode::Normal;
+                    editor.selection = None;
+                }
+                if let Some(span) = span {
+                    effect.merge(self.operate(operator, span, command.register, editor, content));
+                }
+            }
+            CommandKind::Action(action) => {
+                effect.merge(self.run_action(action, command, pos, editor, content));
+            }
+        }
+
+        if is_change && self.mode != VimMode::Insert {
+            editor.end_undo_group();
+        }
+        // Alterações feitas pelo modo visual não são repetidas pelo `.`
+        if is_change && !self.replaying && !visual {
+            if self.mode == VimMode::Insert {
+                self.recording = Some(keys);
+            } else {
+                self.last_change = keys;
+            }
+        }
+        if matches!(self.mode, VimMode::Visual | VimMode::VisualLine) {
+            self.show_visual_selection(editor, content);
+        }
+        effect
+    }
+
+    fn run_action(&mut self, action: Action, command: Command, pos: usize, editor: &mut TextEditor, content: &mut Rope) -> VimEffect {
+        let count = command.count.unwrap_or(1);
+        let line = content.char_to_line(pos);
+        let mut effect = VimEffect::default();
+        match action {
+            Action::Insert => self.start_insert(editor, content, pos),
+            Action::Append => {
+                let at = if line_len_without_newline(content, line) > 0 { pos + 1 } else { pos };
+                self.start_insert(editor, content, at);
+            }
+            Action::InsertAtStart => self.start_insert(editor, content, first_non_blank(content, line)),
+            Action::AppendAtEnd => self.start_insert(editor, content, line_end(content, line)),
+            Action::OpenBelow | Action::OpenAbove => {
+                // A linha nova herda a indentação da atual
+                let indent: String = content.line(line).chars().take_while(|c| *c == ' ' || *c == '\t').collect();
+                let newline = editor.line_ending.as_str();
+                let (at, text, cursor) = if action == Action::OpenBelow {
+                    let at = line_end(content, line);
+                    (at, format!("{}{}", newline, indent), at + newline.len() + indent.chars().count())
+                } else {
+                    let at = line_start(content, line);
+                    (at, format!("{}{}", indent, newline), at + indent.chars().count())
+                };
+                editor.replace_ranges(content, &[(at..at, text)]);
+                effect.modified = true;
+                self.start_insert(editor, content, cursor);
+            }
+            Action::Put { before } => effect.modified = self.put(command.register, count, before, pos, editor, content),
+            Action::Replace(ch) => {
+                let end = line_end(content, line);
+                if pos.saturating_add(count) <= end {
+                    let text: String = std::iter::repeat_n(ch, count).collect();
+                    editor.replace_ranges(content, &[(pos..pos + count, text)]);
+                    set_cursor(editor, content, pos + count - 1);
+                    effect.modified = true;
+                }
+            }
+            Action::Undo | Action::Redo => {
+                for _ in 0..count {
+                    let changed = if action == Action::Undo { editor.undo(content) } else { editor.redo(content) };
+                    if !changed {
+                        break; // Histórico esgotado
+                    }
+                    effect.modified = true;
+                }
+                let pos = clamp_normal(content, cursor_pos(content, editor.cursor));
+                set_cursor(editor, content, pos);
+            }
+            Action::Visual { linewise } => {
+                let mode = if linewise { VimMode::VisualLine } else { VimMode::Visual };
+                if self.mode == mode {
+                    self.mode = VimMode::Normal;
+                    editor.selection = None;
+                } else {
+                    if self.mode == VimMode::Normal {
+                        self.visual_anchor = pos;
+                        set_cursor(editor, content, pos);
+                    }
+                    self.mode = mode;
+                }
+            }
+            Action::SwapAnchor => {
+                let anchor = std::mem::replace(&mut self.visual_anchor, pos);
+                set_cursor(editor, content, anchor);
+            }
+            Action::Repeat => {
+                let keys = with_count(&self.last_change, command.count);
+                self.replaying = true;
+                for key in keys {
+                    effect.merge(self.handle_key(key, editor, content));
+                }
+                self.replaying = false;
+            }
+            Action::CommandLine => {
+                self.mode = VimMode::CommandLine;
+                self.command_line.clear();
+            }
+        }
+        effect
+    }
+
+    fn start_insert(&mut self, editor: &mut TextEditor, content: &Rope, at: usize) {
+        self.mode = VimMode::Insert;
+        set_cursor(editor, content, at);
+    }
+
+    /// Aplica um operador ao trecho, guardando o texto no registrador.
+    fn operate(&mut self, operator: Operator, span: Span, register: Option<char>, editor: &mut TextEditor, content: &mut Rope) -> VimEffect {
+        let mut effect = VimEffect::default();
+        match (operator, span) {
+            (Operator::Indent | Operator::Outdent, span) => {
+                let (first, last) = match span {
+                    Span::Lines(first, last) => (first, last),
+                    Span::Chars(range) => (content.char_to_line(range.start), content.char_to_line(range.end.max(range.start + 1) - 1)),
+                };
+                let edits: Vec<(Range<usize>, String)> = (first..=last)
+                    .filter_map(|line| shift_line(content, line, operator == Operator::Indent))
+                    .collect();
+                if !edits.is_empty() {
+                    editor.replace_ranges(content, &edits);
+                    effect.modified = true;
+                }
+                set_cursor(editor, content, first_non_blank(content, first));
+            }
+            (_, Span::Chars(range)) if range.is_empty() && operator != Operator::Change => {}
+            (_, Span::Chars(range)) => {
+                let text = content.slice(range.clone()).to_string();
+                self.store(register, text, false, operator == Operator::Yank);
+                if operator != Operator::Yank && !range.is_empty() {
+                    editor.replace_ranges(content, &[(range.clone(), String::new())]);
+                    effect.modified = true;
+                }
+                if operator == Operator::Change {
+                    self.start_insert(editor, content, range.start);
+                } else {
+                    set_cursor(editor, content, clamp_normal(content, range.start));
+                }
+            }
+            (_, Span::Lines(first, last)) => {
+                let mut text = content.slice(line_start(content, first)..line_end(content, last)).to_string();
+                text.push_str(editor.line_ending.as_str());
+                self.store(register, text, true, operator == Operator::Yank);
+                match operator {
+                    Operator::Yank => {
+                        let column = editor.cursor.char_idx;
+                        let line_pos = line_start(content, first) + column;
+                        set_cursor(editor, content, clamp_normal(content, line_pos.min(line_end(content, first))));
+                    }
+                    Operator::Change => {
+                        // `cc` mantém uma linha vazia no lugar das apagadas
+                        let range = line_start(content, first)..line_end(content, last);
+                        if !range.is_empty() {
+                            editor.replace_ranges(content, &[(range.clone(), String::new())]);
+                            effect.modified = true;
+                        }
+                        self.start_insert(editor, content, range.start);
+                    }
+                    _ => {
+                        editor.replace_ranges(content, &[(delete_lines_range(content, first, last), String::new())]);
+                        effect.modified = true;
+                        let line = first.min(last_line(content));
+                        set_cursor(editor, content, first_non_blank(content, line));
+                    }
+                }
+            }
+        }
+        effect
+    }
+
+    /// Cola o registrador `count` vezes depois (ou antes) do cursor. Retorna se colou algo.
+    fn put(&mut self, register: Option<char>, count: usize, before: bool, pos: usize, editor: &mut TextEditor, content: &mut Rope) -> bool {
+        let name = register.map_or('"', |name| name.to_ascii_lowercase());
+        let Some(register) = self.registers.get(&name).cloned().filter(|register| !register.text.is_empty()) else {
+            return false;
+        };
+        let count = count.min(MAX_PUT_BYTES / register.text.len()).max(1);
+        let text = register.text.repeat(count);
+        let line = content.char_to_line(pos);
+        if register.linewise {
+            let (at, text, first_line) = if before {
+                (line_start(content, line), text, line)
+            } else if line < last_line(content) || content_ends_with_newline(content) {
+                (line_start(content, line + 1), text, line + 1)
+            } else {
+                // Última linha sem quebra no fim: a quebra vai antes do texto colado
+                let trimmed = strip_line_break(&text);
+                (content.len_chars(), format!("{}{}", editor.line_ending.as_str(), trimmed), line + 1)
+            };
+            editor.replace_ranges(content, &[(at..at, text)]);
+            set_cursor(editor, content, first_non_blank(content, first_line));
+        } else {
+            let at = if before || line_len_without_newline(content, line) == 0 { pos } else { pos + 1 };
+            let inserted = editor.replace_ranges(content, &[(at..at, text)]);
+            let end = inserted.first().map_or(at, |range| range.end);
+            set_cursor(editor, content, clamp_normal(content, end.saturating_sub(1).max(at)));
+        }
+        true
+    }
+
+    /// Guarda um texto copiado ou apagado. `"_` descarta; maiúsculas acrescentam ao
+    /// registrador; o registrador sem nome sempre recebe o texto e `"0` guarda a última
+    /// cópia feita sem registrador.
+    fn store(&mut self, register: Option<char>, text: String, linewise: bool, yank: bool) {
+        if register == Some('_') {
+            return;
+        }
+        let value = match register {
+            Some(name) if name.is_ascii_uppercase() => {
+                let entry = self.registers.entry(name.to_ascii_lowercase()).or_default();
+                entry.text.push_str(&text);
+                entry.linewise |= linewise;
+                entry.clone()
+            }
+            Some(name) => {
+                let value = Register { text, linewise };
+                self.registers.insert(name, value.clone());
+                value
+            }
+            None => {
+                let value = Register { text, linewise };
+                if yank {
+                    self.registers.insert('0', value.clone());
+                }
+                value
+            }
+        };
+        self.registers.insert('"', value);
+    }
+
+    /// Trecho da seleção visual.
+    fn visual_span(&self, content: &Rope, pos: usize) -> Span {
+        let (start, end) = (self.visual_anchor.min(pos), self.visual_anchor.max(pos));
+        if self.mode == VimMode::VisualLine {
+            Span::Lines(content.char_to_line(start), content.char_to_line(end))
+        } else {
+            Span::Chars(start..(end + 1).min(content.len_chars()))
+        }
+    }
+
+    /// Mostra a seleção visual no editor; o cursor principal fica na ponta que se move.
+    fn show_visual_selection(&self, editor: &mut TextEditor, content: &Rope) {
+        let pos = cursor_pos(content, editor.cursor);
+        let (start, end) = match self.visual_span(content, pos) {
+            Span::Chars(range) => (range.start, range.end),
+            Span::Lines(first, last) => (line_start(content, first), line_end(content, last)),
+        };
+        let (start, end) = if pos < self.visual_anchor { (end, start) } else { (start, end) };
+        editor.selection = Some(Selection { start: cursor_at(content, start), end: cursor_at(content, end) });
+    }
+
+    /// Trecho que um operador usa com um movimento.
+    fn motion_span(&mut self, content: &Rope, pos: usize, motion: Motion, count: Option<usize>, operator: Operator) -> Option<Span> {
+        // `cw` em cima de uma palavra troca só até o fim dela, como `ce`
+        let on_word = pos < content.len_chars() && !content.char(pos).is_whitespace();
+        let (target, kind) = match motion {
+            Motion::WordForward { big } if operator == Operator::Change && on_word => {
+                (word_end(content, pos, count.unwrap_or(1), big, true), MotionKind::Inclusive)
+            }
+            _ => (self.motion_target(content, pos, motion, count)?.0, motion.kind()),
+        };
+        let (start, end) = (pos.min(target), pos.max(target));
+        Some(match kind {
+            MotionKind::Linewise => Span::Lines(content.char_to_line(start), content.char_to_line(end)),
+            MotionKind::Inclusive => Span::Chars(start..(end + 1).min(content.len_chars())),
+            MotionKind::Exclusive => {
+                // Um movimento exclusivo que termina no início de outra linha para no fim
+                // da linha anterior (`dw` na última palavra não junta as linhas)
+                let end_line = content.char_to_line(end);
+                let end = if end > start && end_line > content.char_to_line(start) && end == line_start(content, end_line) {
+                    line_end(content, end_line - 1)
+                } else {
+                    end
+                };
+                Span::Chars(start..end.max(start))
+            }
+        })
+    }
+
+    /// Destino de um movimento a partir de `pos` e, para j, k e $, a coluna que os
+    /// próximos j/k devem manter.
+    fn motion_target(&mut self, content: &Rope, pos: usize, motion: Motion, count: Option<usize>) -> Option<(usize, Option<usize>)> {
+        let n = count.unwrap_or(1);
+        let line = content.char_to_line(pos);
+        let column = pos - line_start(content, line);
+        let mut keep_column = None;
+        let target = match motion {
+            Motion::Left => pos.saturating_sub(n).max(line_start(content, line)),
+            Motion::Right => pos.saturating_add(n).min(line_end(content, line)),
+            Motion::Up | Motion::Down => {
+                let target_line = if motion == Motion::Up {
+                    line.saturating_sub(n)
+                } else {
+                    line.saturating_add(n).min(last_line(content))
+                };
+                // A coluna só é mantida se o cursor não foi movido por outro meio
+                let column = self.preferred_column
+                    .filter(|(_, at)| *at == pos)
+                    .map_or(column, |(column, _)| column);
+                keep_column = Some(column);
+                line_start(content, target_line) + column.min(line_len_without_newline(content, target_line))
+            }
+            Motion::LineStart => line_start(content, line),
+            Motion::FirstNonBlank => first_non_blank(content, line),
+            Motion::LineEnd => {
+                let last = line.saturating_add(n - 1).min(last_line(content));
+                keep_column = Some(usize::MAX);
+                line_end(content, last).saturating_sub(1).max(line_start(content, last))
+            }
+            Motion::WordForward { big } => repeat_motion(pos, n, |at| word_forward(content, at, big)),
+            Motion::WordBackward { big } => repeat_motion(pos, n, |at| word_backward(content, at, big)),
+            Motion::WordEnd { big } => word_end(content, pos, n, big, false),
+            Motion::FirstLine | Motion::LastLine => {
+                let default = if motion == Motion::FirstLine { 0 } else { last_line(content) };
+                let line = count.map_or(default, |line| line.saturating_sub(1)).min(last_line(content));
+                first_non_blank(content, line)
+            }
+            Motion::MatchingBracket => matching_bracket(content, pos)?,
+            Motion::Find { ch, forward, till } => {
+                self.last_find = Some((ch, forward, till));
+                find_in_line(content, pos, ch, forward, till, n)?
+            }
+            Motion::RepeatFind { reverse } => {
+                let (ch, forward, till) = self.last_find?;
+                find_in_line(content, pos, ch, forward != reverse, till, n)?
+            }
+        };
+        Some((target, keep_column))
+    }
+}
+
+/// Lê um comando completo do modo normal (ou visual):
+/// `["x][contador](movimento | operador [contador] (movimento | operador) | ação)`.
+fn parse_command(keys: &[VimKey], visual: bool) -> Parsed<Command> {
+    let mut idx = 0;
+    let mut register = None;
+    if keys.first() == Some(&VimKey::Char('"')) {
+        match keys.get(1) {
+            None => return Parsed::NeedMore,
+            Some(VimKey::Char(name)) if name.is_ascii_alphanumeric() || *name == '"' || *name == '_' => {
+                register = Some(*name);
+                idx = 2;
+            }
+            Some(_) => return Parsed::Invalid,
+        }
+    }
+    let (count, used) = parse_count(&keys[idx..]);
+    idx += used;
+    let done = |kind: CommandKind, used: usize| Parsed::Done(Command { register, count, kind }, used);
+
+    let rest = &keys[idx..];
+    let Some(&key) = rest.first() else {
+        return Parsed::NeedMore;
+    };
+    match parse_motion(rest) {
+        Parsed::Done(motion, used) => return done(CommandKind::Move(motion), idx + used),
+        Parsed::NeedMore => return Parsed::NeedMore,
+        Parsed::Invalid => {}
+    }
+
+    let VimKey::Char(ch) = key else {
+        return match key {
+            VimKey::Ctrl('r') if !visual => done(CommandKind::Action(Action::Redo), idx + 1),
+            _ => Parsed::Invalid,
+        };
+    };
+    if let Some(operator) = operator_for(ch) {
+        if visual {
+            return done(CommandKind::Operate(operator, OperatorTarget::Selection), idx + 1);
+        }
+        let after = &rest[1..];
+        let (motion_count, used) = parse_count(after);
+        let Some(&next) = after.get(used) else {
+            return Parsed::NeedMore;
+        };
+        if next == key {
+            return done(CommandKind::Operate(operator, OperatorTarget::Lines), idx + 2 + used);
+        }
+        return match parse_motion(&after[used..]) {
+            Parsed::Done(motion, motion_used) => {
+                done(CommandKind::Operate(operator, OperatorTarget::Motion(motion, motion_count)), idx + 1 + used + motion_used)
+            }
+            Parsed::NeedMore => Parsed::NeedMore,
+            Parsed::Invalid => Parsed::Invalid,
+        };
+    }
+
+    // Atalhos que equivalem a um operador com movimento
+    let operate = |operator: Operator, motion: Motion| CommandKind::Operate(operator, OperatorTarget::Motion(motion, None));
+    let kind = match (ch, visual) {
+        ('x', true) => CommandKind::Operate(Operator::Delete, OperatorTarget::Selection),
+        ('s', true) => CommandKind::Operate(Operator::Change, OperatorTarget::Selection),
+        ('o', true) => CommandKind::Action(Action::SwapAnchor),
+        ('x', false) => operate(Operator::Delete, Motion::Right),
+        ('X', false) => operate(Operator::Delete, Motion::Left),
+        ('D', false) => operate(Operator::Delete, Motion::LineEnd),
+        ('C', false) => operate(Operator::Change, Motion::LineEnd),
+        ('s', false) => operate(Operator::Change, Motion::Right),
+        ('S', false) => CommandKind::Operate(Operator::Change, OperatorTarget::Lines),
+        ('Y', false) => CommandKind::Operate(Operator::Yank, OperatorTarget::Lines),
+        ('i', false) => CommandKind::Action(Action::Insert),
+        ('a', false) => CommandKind::Action(Action::Append),
+        ('I', false) => CommandKind::Action(Action::InsertAtStart),
+        ('A', false) => CommandKind::Action(Action::AppendAtEnd),
+        ('o', false) => CommandKind::Action(Action::OpenBelow),
+        ('O', false) => CommandKind::Action(Action::OpenAbove),
+        ('p', false) => CommandKind::Action(Action::Put { before: false }),
+        ('P', false) => CommandKind::Action(Action::Put { before: true }),
+        ('u', false) => CommandKind::Action(Action::Undo),
+        ('.', false) => CommandKind::Action(Action::Repeat),
+        (':', false) => CommandKind::Action(Action::CommandLine),
+        ('v', _) => CommandKind::Action(Action::Visual { linewise: false }),
+        ('V', _) => CommandKind::Action(Action::Visual { linewise: true }),
+        ('r', false) => {
+            return match rest.get(1) {
+                None => Parsed::NeedMore,
+                Some(VimKey::Char(replacement)) => done(CommandKind::Action(Action::Replace(*replacement)), idx + 2),
+                Some(_) => Parsed::Invalid,
+            };
+        }
+        _ => return Parsed::Invalid,
+    };
+    done(kind, idx + 1)
+}
+
+/// Contador no início das teclas (um `0` inicial é o movimento, não um contador),
+/// limitado a `MAX_COUNT`.
+fn parse_count(keys: &[VimKey]) -> (Option<usize>, usize) {
+    let mut count: Option<usize> = None;
+    let mut used = 0;
+    for key in keys {
+        match key {
+            VimKey::Char(digit @ '0'..='9') if !(count.is_none() && *digit == '0') => {
+                let value = digit.to_digit(10).unwrap_or(0) as usize;
+                count = Some(count.unwrap_or(0).saturating_mul(10).saturating_add(value).min(MAX_COUNT));
+                used += 1;
+            }
+            _ => break,
+        }
+    }
+    (count, used)
+}
+
+fn parse_motion(keys: &[VimKey]) -> Parsed<Motion> {
+    let Some(&key) = keys.first() else {
+        return Parsed::NeedMore;
+    };
+    let motion = match key {
+        VimKey::Left | VimKey::Backspace | VimKey::Char('h') => Motion::Left,
+        VimKey::Right | VimKey::Char('l') | VimKey::Char(' ') => Motion::Right,
+        VimKey::Up | VimKey::Char('k') => Motion::Up,
+        VimKey::Down | VimKey::Char('j') => Motion::Down,
+        VimKey::Char('0') => Motion::LineStart,
+        VimKey::Char('^') => Motion::FirstNonBlank,
+        VimKey::Char('$') => Motion::LineEnd,
+        VimKey::Char('w') => Motion::WordForward { big: false },
+        VimKey::Char('W') => Motion::WordForward { big: true },
+        VimKey::Char('b') => Motion::WordBackward { big: false },
+        VimKey::Char('B') => Motion::WordBackward { big: true },
+        VimKey::Char('e') => Motion::WordEnd { big: false },
+        VimKey::Char('E') => Motion::WordEnd { big: true },
+        VimKey::Char('G') => Motion::LastLine,
+        VimKey::Char('%') => Motion::MatchingBracket,
+        VimKey::Char(';') => Motion::RepeatFind { reverse: false },
+        VimKey::Char(',') => Motion::RepeatFind { reverse: true },
+        VimKey::Char('g') => {
+            return match keys.get(1) {
+                None => Parsed::NeedMore,
+                Some(VimKey::Char('g')) => Parsed::Done(Motion::FirstLine, 2),
+                Some(_) => Parsed::Invalid,
+            };
+        }
+        VimKey::Char(find @ ('f' | 'F' | 't' | 'T')) => {
+            return match keys.get(1) {
+                None => Parsed::NeedMore,
+                Some(VimKey::Char(ch)) => Parsed::Done(Motion::Find {
+                    ch: *ch,
+                    forward: find.is_ascii_lowercase(),
+                    till: find.eq_ignore_ascii_case(&'t'),
+                }, 2),
+                Some(_) => Parsed::Invalid,
+            };
+        }
+        _ => return Parsed::Invalid,
+    };
+    Parsed::Done(motion, 1)
+}
+
+fn operator_for(ch: char) -> Option<Operator> {
+    match ch {
+        'd' => Some(Operator::Delete),
+        'c' => Some(Operator::Change),
+        'y' => Some(Operator::Yank),
+        '>' => Some(Operator::Indent),
+        '<' => Some(Operator::Outdent),
+        _ => None,
+    }
+}
+
+/// Teclas da última alteração com outro contador (`3.`), trocando o original.
+fn with_count(keys: &[VimKey], count: Option<usize>) -> Vec<VimKey> {
+    let Some(count) = count else {
+        return keys.to_vec();
+    };
+    let register_len = if keys.first() == Some(&VimKey::Char('"')) { 2.min(keys.len()) } else { 0 };
+    let (_, count_len) = parse_count(&keys[register_len..]);
+    let mut result = keys[..register_len].to_vec();
+    result.extend(count.to_string().chars().map(VimKey::Char));
+    result.extend_from_slice(&keys[register_len + count_len..]);
+    result
+}
+
+// Posições no Rope (índices de caractere)
+
+fn cursor_pos(content: &Rope, cursor: Cursor) -> usize {
+    let line = cursor.line.min(content.len_lines().saturating_sub(1));
+    line_start(content, line) + cursor.char_idx.min(line_len_without_newline(content, line))
+}
+
+fn cursor_at(content: &Rope, pos: usize) -> Cursor {
+    let line = content.char_to_line(pos);
+    Cursor { line, char_idx: pos - content.line_to_char(line) }
+}
+
+/// Coloca o cursor principal em `pos`, descartando seleção e cursores extras.
+fn set_cursor(editor: &mut TextEditor, content: &Rope, pos: usize) {
+    let cursor = cursor_at(content, pos.min(content.len_chars()));
+    editor.place_cursor(content, cursor.line, cursor.char_idx);
+}
+
+fn line_start(content: &Rope, line: usize) -> usize {
+    content.line_to_char(line)
+}
+
+/// Fim da linha, antes da quebra de linha.
+fn line_end(content: &Rope, line: usize) -> usize {
+    line_start(content, line) + line_len_without_newline(content, line)
+}
+
+fn content_ends_with_newline(content: &Rope) -> bool {
+    content.len_chars() > 0 && matches!(content.char(content.len_chars() - 1), '\n' | '\r')
+}
+
+/// Última linha de verdade: a linha vazia depois da quebra final não conta.
+fn last_line(content: &Rope) -> usize {
+    let lines = content.len_lines();
+    if lines > 1 && content_ends_with_newline(content) { lines - 2 } else { lines - 1 }
+}
+
+fn first_non_blank(content: &Rope, line: usize) -> usize {
+    let start = line_start(content, line);
+    let end = line_end(content, line);
+    (start..end).find(|&pos| !matches!(content.char(pos), ' ' | '\t')).unwrap_or(start)
+}
+
+/// No modo normal o cursor fica sobre um caractere: nunca depois do último da linha.
+fn clamp_normal(content: &Rope, pos: usize) -> usize {
+    let line = content.char_to_line(pos.min(content.len_chars())).min(last_line(content));
+    let start = line_start(content, line);
+    let end = line_end(content, line);
+    pos.clamp(start, end.saturating_sub(1).max(start))
+}
+
+/// Trecho apagado por `dd` nas linhas `first..=last`, com uma quebra de linha.
+fn delete_lines_range(content: &Rope, first: usize, last: usize) -> Range<usize> {
+    let start = line_start(content, first);
+    if last + 1 < content.len_lines() && (last < last_line(content) || content_ends_with_newline(content)) {
+        return start..line_start(content, last + 1);
+    }
+    // Sem linha depois: leva a quebra de linha anterior junto
+    let start = if first > 0 { line_end(content, first - 1) } else { start };
+    start..content.len_chars()
+}
+
+/// Edição que indenta (ou desindenta) uma linha; linhas vazias não mudam.
+fn shift_line(content: &Rope, line: usize, indent: bool) -> Option<(Range<usize>, String)> {
+    let start = line_start(content, line);
+    if indent {
+        return (line_len_without_newline(content, line) > 0).then(|| (start..start, " ".repeat(SHIFT_WIDTH)));
+    }
+    let removable = match content.line(line).chars().next()? {
+        '\t' => 1,
+        _ => content.line(line).chars().take(SHIFT_WIDTH).take_while(|c| *c == ' ').count(),
+    };
+    (removable > 0).then(|| (start..start + removable, String::new()))
+}
+
+/// Classe do caractere para os movimentos de palavra: espaço, palavra ou pontuação.
+/// Com `big` (W, B, E), tudo o que não é espaço é palavra.
+fn char_class(ch: char, big: bool) -> u8 {
+    if ch.is_whitespace() {
+        0
+    } else if big || ch.is_alphanumeric() || ch == '_' {
+        1
+    } else {
+        2
+    }
+}
+
+/// Aplica um movimento `count` vezes, parando quando ele não sai mais do lugar.
+fn repeat_motion(pos: usize, count: usize, step: impl Fn(usize) -> usize) -> usize {
+    let mut at = pos;
+    for _ in 0..count {
+        let next = step(at);
+        if next == at {
+            break;
+        }
+        at = next;
+    }
+    at
+}
+
+/// `w`: início da próxima palavra.
+fn word_forward(content: &Rope, pos: usize, big: bool) -> usize {
+    let len = content.len_chars();
+    let class_at = |at: usize| char_class(content.char(at), big);
+    let mut at = pos;
+    if at < len && class_at(at) != 0 {
+        let class = class_at(at);
+        while at < len && class_at(at) == class {
+            at += 1;
+        }
+    }
+    while at < len && class_at(at) == 0 {
+        at += 1;
+    }
+    at
+}
+
+/// `b`: início da palavra anterior (ou da atual, se o cursor está no meio dela).
+fn word_backward(content: &Rope, pos: usize, big: bool) -> usize {
+    let class_at = |at: usize| char_class(content.char(at), big);
+    if pos == 0 {
+        return 0;
+    }
+    let mut at = pos - 1;
+    while at > 0 && class_at(at) == 0 {
+        at -= 1;
+    }
+    let class = class_at(at);
+    while at > 0 && class_at(at - 1) == class {
+        at -= 1;
+    }
+    at
+}
+
+/// `e`: fim da `count`-ésima palavra. Com `stay`, o fim da palavra atual conta como a
+/// primeira (usado por `cw`).
+fn word_end(content: &Rope, pos: usize, count: usize, big: bool, stay: bool) -> usize {
+    let len = content.len_chars();
+    if len == 0 {
+        return 0;
+    }
+    let class_at = |at: usize| char_class(content.char(at), big);
+    let mut at = pos;
+    for step in 0..count {
+        if !(stay && step == 0) {
+            at += 1;
+            while at < len && class_at(at) == 0 {
+                at += 1;
+            }
+        }
+        if at >= len {
+            return len - 1;
+        }
+        let class = class_at(at);
+        while at + 1 < len && class_at(at + 1) == class {
+            at += 1;
+        }
+    }
+    at
+}
+
+/// `%`: par do primeiro parêntese, colchete ou chave a partir do cursor na linha.
+fn matching_bracket(content: &Rope, pos: usize) -> Option<usize> {
+    const PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+    let end = line_end(content, content.char_to_line(pos));
+    let (start, bracket) = (pos..end)
+        .map(|at| (at, content.char(at)))
+        .find(|(_, ch)| PAIRS.iter().any(|(open, close)| ch == open || ch == close))?;
+    let (open, close) = PAIRS.iter().copied().find(|(open, close)| bracket == *open || bracket == *close)?;
+    let forward = bracket == open;
+    let mut depth = 0usize;
+    let mut at = start;
+    loop {
+        let ch = content.char(at);
+        if ch == open || ch == close {
+            if (ch == open) == forward {
+                depth += 1;
+            } else {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(at);
+                }
+            }
+        }
+        if forward {
+            at += 1;
+            if at >= content.len_chars() {
+                return None;
+            }
+        } else {
+            at = at.checked_sub(1)?;
+        }
+    }
+}
+
+/// `f`/`t` (e `F`/`T` para trás): `count`-ésima ocorrência de `ch` na linha.
+fn find_in_line(content: &Rope, pos: usize, ch: char, forward: bool, till: bool, count: usize) -> Option<usize> {
+    let line = content.char_to_line(pos);
+    let found = if forward {
+        (pos + 1..line_end(content, line)).filter(|&at| content.char(at) == ch).nth(count - 1)?
+    } else {
+        (line_start(content, line)..pos).rev().filter(|&at| content.char(at) == ch).nth(count - 1)?
+    };
+    Some(match (till, forward) {
+        (false, _) => found,
+        (true, true) => found - 1,
+        (true, false) => found + 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::line_ending::detect_line_ending;
+
+    /// Estado do Vim sobre `text`, com o cursor em (`line`, `column`).
+    fn setup(text: &str, line: usize, column: usize) -> (VimState, TextEditor, Rope) {
+        let content = Rope::from_str(text);
+        let mut editor = TextEditor::new();
+        editor.place_cursor(&content, line, column);
+        (VimState::new(), editor, content)
+    }
+
+    /// Digita as teclas; `\x1b` é Esc.
+    fn type_keys(vim: &mut VimState, editor: &mut TextEditor, content: &mut Rope, keys: &str) {
+        for ch in keys.chars() {
+            let key = if ch == '\x1b' { VimKey::Escape } else { VimKey::Char(ch) };
+            vim.handle_key(key, editor, content);
+        }
+    }
+
+    /// Texto e cursor (linha, coluna) depois de digitar `keys`.
+    fn run(text: &str, line: usize, column: usize, keys: &str) -> (String, (usize, usize)) {
+        let (mut vim, mut editor, mut content) = setup(text, line, column);
+        type_keys(&mut vim, &mut editor, &mut content, keys);
+        (content.to_string(), (editor.cursor.line, editor.cursor.char_idx))
+    }
+
+    #[test]
+    fn dw_deletes_up_to_the_next_word() {
+        assert_eq!(run("foo bar baz", 0, 0, "dw"), ("bar baz".to_string(), (0, 0)));
+        assert_eq!(run("foo bar baz", 0, 4, "2dw"), ("foo ".to_string(), (0, 3)));
+        // Na última palavra da linha, não junta com a seguinte
+        assert_eq!(run("foo bar\nbaz", 0, 4, "dw"), ("foo \nbaz".to_string(), (0, 3)));
+    }
+
+    #[test]
+    fn cw_changes_to_the_end_of_the_word() {
+        let (mut vim, mut editor, mut content) = setup("foo bar", 0, 0);
+        type_keys(&mut vim, &mut editor, &mut content, "cwxy\x1b");
+        assert_eq!(content.to_string(), "xy bar");
+        assert_eq!((editor.cursor.line, editor.cursor.char_idx), (0, 1));
+        assert_eq!(vim.mode, VimMode::Normal);
+    }
+
+    #[test]
+    fn count_dd_on_the_last_line_deletes_only_what_exists() {
+        assert_eq!(run("a\nb\nc", 2, 0, "3dd"), ("a\nb".to_string(), (1, 0)));
+        assert_eq!(run("a\nb\nc\n", 1, 0, "5dd"), ("a\n".to_string(), (0, 0)));
+    }
+
+    #[test]
+    fn linewise_put_goes_below_or_above_the_line() {
+        assert_eq!(run("one\ntwo\n", 0, 0, "yyjp"), ("one\ntwo\none\n".to_string(), (2, 0)));
+        assert_eq!(run("one\ntwo\n", 0, 0, "yyjP"), ("one\none\ntwo\n".to_string(), (1, 0)));
+        // Última linha sem quebra no fim
+        assert_eq!(run("one\ntwo", 0, 0, "yyjp"), ("one\ntwo\none".to_string(), (2, 0)));
+    }
+
+    #[test]
+    fn linewise_yank_uses_the_buffer_line_ending() {
+        for (text, line, keys, expected) in [
+            ("one\r\ntwo\r\n", 0, "yyjp", "one\r\ntwo\r\none\r\n"),
+            ("one\r\ntwo\r\n", 0, "2yyjp", "one\r\ntwo\r\none\r\ntwo\r\n"),
+            ("one\r\ntwo", 0, "yyjp", "one\r\ntwo\r\none"),
+            ("one\r\ntwo\r\n", 0, "ddp", "two\r\none\r\n"),
+            ("one\rtwo\r", 0, "yyjp", "one\rtwo\rone\r"),
+        ] {
+            let (mut vim, mut editor, mut content) = setup(text, line, 0);
+            editor.line_ending = detect_line_ending(&content).0;
+            type_keys(&mut vim, &mut editor, &mut content, keys);
+            assert_eq!(content.to_string(), expected, "{:?} em {:?}", keys, text);
+        }
+    }
+
+    #[test]
+    fn dot_repeats_with_a_new_count() {
+        assert_eq!(run("a b c d e f", 0, 0, "dw2."), ("d e f".to_string(), (0, 0)));
+        assert_eq!(run("a b c d e f", 0, 0, "2dw."), ("e f".to_string(), (0, 0)));
+    }
+
+    #[test]
+    fn percent_jumps_between_brackets() {
+        assert_eq!(run("(a [b] c)", 0, 0, "%"), ("(a [b] c)".to_string(), (0, 8)));
+        assert_eq!(run("(a [b] c)", 0, 0, "%%"), ("(a [b] c)".to_string(), (0, 0)));
+        // Procura o primeiro parêntese a partir do cursor
+        assert_eq!(run("x [y] z", 0, 0, "%"), ("x [y] z".to_string(), (0, 4)));
+        assert_eq!(run("x (y z", 0, 0, "d%"), ("x (y z".to_string(), (0, 0)));
+    }
+
+    #[test]
+    fn find_till_and_repeat() {
+        assert_eq!(run("a,b,c,d", 0, 0, "f,").1, (0, 1));
+        assert_eq!(run("a,b,c,d", 0, 0, "f,;").1, (0, 3));
+        assert_eq!(run("a,b,c,d", 0, 0, "f,;,").1, (0, 1));
+        assert_eq!(run("a,b,c,d", 0, 0, "2f,").1, (0, 3));
+        assert_eq!(run("a,b,c,d", 0, 0, "tc").1, (0, 3));
+        assert_eq!(run("a,b,c,d", 0, 6, "F,").1, (0, 5));
+        assert_eq!(run("a,b,c,d", 0, 0, "dtc"), ("c,d".to_string(), (0, 0)));
+        assert_eq!(run("a,b,c,d", 0, 0, "df,"), ("b,c,d".to_string(), (0, 0)));
+    }
+
+    #[test]
+    fn huge_counts_do_not_overflow_or_hang() {
+        assert_eq!(run("a\nb\nc", 0, 0, "99999999999999999999j").1, (2, 0));
+        assert_eq!(run("a\nb\nc", 0, 0, "99999999999999999999$").1, (2, 0));
+        assert_eq!(run("foo bar", 0, 0, "9999999999d9999999999w").0, "");
+        assert_eq!(run("foo bar", 0, 0, "99999999w").1, (0, 6));
+        assert_eq!(run("foo bar", 0, 0, "xx99999999u").0, "foo bar");
+    }
+}
@@ -2,15 +2,14 @@
 
 use eframe::egui;
 use walkdir::WalkDir;
-use std::path::PathBuf;
+use std::path::Path;
 
-use crate::ui::app::{EditorTab, MyApp};
-use crate::core::file_handler;
+use crate::ui::app::MyApp;
 
 use egui_phosphor::variants::{fill, regular}; // Módulos de variantes
 
 // Função auxiliar para obter o ícone Phosphor com base na extensão do arquivo
-fn get_file_icon(path: &PathBuf) -> &'static str {
+fn get_file_icon(path: &Path) -> &'static str {
     if let Some(extension) = path.extension().and_then(|s| s.to_str()) {
         match extension.to_lowercase().as_str() {
             // Exemplos de mapeamento para ícones específicos de linguagem
@@ -37,8 +36,8 @@ fn get_file_icon(path: &PathBuf) -> &'static str {
 }
 
 impl MyApp {
-    pub fn display_dir_tree(&mut self, ui: &mut egui::Ui, path: &PathBuf, indent_level: usize) {
-        let is_dir_expanded = *self.expanded_dirs.entry(path.clone()).or_insert(false);
+    pub fn display_dir_tree(&mut self, ui: &mut egui::Ui, path: &Path, indent_level: usize) {
+        let is_dir_expanded = *self.expanded_dirs.entry(path.to_path_buf()).or_insert(false);
         let indent = indent_level as f32 * 15.0;
 
         if path.is_dir() {
@@ -51,7 +50,7 @@ impl MyApp {
                 };
 
                 if ui_dir_entry.add(egui::Button::new(toggle_icon).small()).clicked() {
-                    *self.expanded_dirs.entry(path.clone()).or_insert(false) = !is_dir_expanded;
+                    *self.expanded_dirs.entry(path.to_path_buf()).or_insert(false) = !is_dir_expanded;
                 }
                 ui_dir_entry.label(format!("{} {}", regular::FOLDER_SIMPLE, path.file_name().unwrap_or_default().to_string_lossy()));
             });
@@ -67,22 +66,7 @@ impl MyApp {
                             // Use a nova função auxiliar para obter o ícone correto
                             let file_icon = get_file_icon(&entry_path);
                             if ui_file_entry.button(format!("{} {}", file_icon, entry_path.file_name().unwrap_or_default().to_string_lossy())).clicked() {
-                                if let Some(idx) = self.open_tabs.iter().position(|tab| tab.path == entry_path) {
-                                    self.selected_tab_idx = Some(idx);
-                                    eprintln!("Arquivo '{}' já aberto, focando na aba existente.", entry_path.display());
-                                } else {
-                                    match file_handler::load_file_into_rope(&entry_path) {
-                                        Ok(rope) => {
-                                            let new_tab = EditorTab::new(entry_path.clone(), rope);
-                                            self.open_tabs.push(new_tab);
-                                            self.selected_tab_idx = Some(self.open_tabs.len() - 1);
-                                            eprintln!("Arquivo '{}' carregado e nova aba criada.", entry_path.display());
-                                        },
-                                        Err(e) => {
-                                            eprintln!("Erro ao carregar o arquivo '{}': {}", entry_path.display(), e);
-                                        }
-                                    }
-                                }
+                                self.open_file(&entry_path);
                             }
                         });
                     }
@@ -1,3 +1,4 @@
 // src/file_explorer/mod.rs
 
-pub mod fs_tree; // Onde a lógica da árvore de diretórios será movida
\ No newline at end of file
+pub mod fs_tree; // Onde a lógica da árvore de diretórios será movida
+pub mod quick_open; // Abertura rápida de arquivos (Ctrl+P)
\ No newline at end of file
@@ -0,0 +1,288 @@
+// src/file_explorer/quick_open.rs
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+
+use eframe::egui;
+use egui::text::LayoutJob;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use ignore::WalkBuilder;
+
+/// Caminhos enviados de uma vez pela thread de indexação.
+const INDEX_BATCH: usize = 1_000;
+/// Resultados mostrados na lista.
+const MAX_RESULTS: usize = 50;
+/// Arquivos abertos recentemente que ganham bônus no ranking.
+const MAX_RECENT: usize = 50;
+/// Bônus do arquivo aberto mais recentemente; os seguintes ganham um pouco menos.
+const RECENT_BONUS: i64 = 100;
+
+/// Resultado do ranking: índice em `files`, pontuação e caracteres casados do caminho.
+struct Ranked {
+    file_idx: usize,
+    score: i64,
+    matched: Vec<usize>,
+}
+
+/// Abertura rápida (Ctrl+P): lista todos os arquivos do diretório aberto, indexados em
+/// outra thread, e os ordena pela busca aproximada no caminho relativo.
+///
+/// O `SkimMatcherV2` já favorece casamentos no início de segmentos do caminho e de
+/// palavras em camelCase; acertos no nome do arquivo e arquivos abertos recentemente
+/// ganham pontos extras.
+#[derive(Default)]
+pub struct QuickOpen {
+    pub is_open: bool,
+    query: String,
+    selected: usize,
+    root: Option<PathBuf>,
+    files: Vec<String>, // Caminhos relativos a `root`, com `/`
+    indexing: Option<mpsc::Receiver<Vec<String>>>,
+    cancel: Arc<AtomicBool>,
+    recent: Vec<PathBuf>, // Mais recente primeiro
+    ranked: Vec<Ranked>,
+    ranked_for: Option<String>, // Consulta usada em `ranked`; `None` refaz a ordenação
+    ranked_files: usize, // Arquivos de `files` já pontuados em `ranked`
+    request_focus: bool,
+}
+
+impl QuickOpen {
+    /// Abre a lista, indexando o diretório se ele ainda não foi indexado.
+    pub fn open(&mut self, ctx: &egui::Context, root: &Path) {
+        if self.root.as_deref() != Some(root) {
+            self.index(ctx, root);
+        }
+        self.is_open = true;
+        self.query.clear();
+        self.selected = 0;
+        self.request_focus = true;
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+    }
+
+    /// Reindexa `root` em segundo plano, descartando o índice anterior.
+    pub fn index(&mut self, ctx: &egui::Context, root: &Path) {
+        self.cancel.store(true, Ordering::SeqCst);
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.cancel = Arc::clone(&cancel);
+        self.root = Some(root.to_path_buf());
+        self.files.clear();
+        self.ranked_for = None;
+
+        let (tx, rx) = mpsc::channel();
+        self.indexing = Some(rx);
+        let root = root.to_path_buf();
+        let ctx = ctx.clone();
+        std::thread::Builder::new()
+            .name("lcode-file-index".to_string())
+            .spawn(move || index_files(&root, &tx, &cancel, &ctx))
+            .expect("Falha ao iniciar a thread de indexação");
+    }
+
+    /// Registra um arquivo aberto, para o bônus de recência.
+    pub fn note_opened(&mut self, path: &Path) {
+        self.recent.retain(|recent| recent != path);
+        self.recent.insert(0, path.to_path_buf());
+        self.recent.truncate(MAX_RECENT);
+        self.ranked_for = None;
+    }
+
+    /// Recebe os caminhos indexados desde o último frame.
+    fn poll(&mut self) {
+        let Some(rx) = &self.indexing else {
+            return;
+        };
+        loop {
+            match rx.try_recv() {
+                Ok(batch) => self.files.extend(batch),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.indexing = None;
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Mantém o ranking em dia. Quando a consulta é a mesma e nada invalidou o ranking,
+    /// só os arquivos indexados desde a última vez são pontuados e juntados ao resultado.
+    fn rank(&mut self) {
+        if self.ranked_for.as_deref() != Some(self.query.as_str()) {
+            self.ranked_for = Some(self.query.clone());
+            self.ranked.clear();
+            self.ranked_files = 0;
+            self.selected = 0;
+        }
+        if self.ranked_files == self.files.len() {
+            return;
+        }
+
+        let mut ranked = std::mem::take(&mut self.ranked);
+        ranked.extend(self.score(self.ranked_files..self.files.len()));
+        self.ranked_files = self.files.len();
+        // Empates ficam com os caminhos mais curtos
+        ranked.sort_by(|a, b| {
+            b.score.cmp(&a.score)
+                .then(self.files[a.file_idx].len().cmp(&self.files[b.file_idx].len()))
+        });
+        ranked.truncate(MAX_RESULTS);
+        self.ranked = ranked;
+        self.selected = self.selected.min(self.ranked.len().saturating_sub(1));
+    }
+
+    /// Pontua os arquivos `file_range` contra a consulta atual.
+    fn score(&self, file_range: Range<usize>) -> Vec<Ranked> {
+        let root = self.root.clone().unwrap_or_default();
+        let recent: Vec<String> = self.recent.iter()
+            .filter_map(|path| Some(relative_path(path.strip_prefix(&root).ok()?)))
+            .collect();
+        let recency_bonus = |path: &str| {
+            recent.iter().position(|recent| recent == path)
+                .map_or(0, |position| RECENT_BONUS - position as i64 * RECENT_BONUS / MAX_RECENT as i64)
+        };
+
+        let matcher = SkimMatcherV2::default().smart_case();
+        let query = self.query.trim();
+        self.files[file_range.clone()].iter().zip(file_range)
+            .filter_map(|(path, file_idx)| {
+                if query.is_empty() {
+                    return Some(Ranked { file_idx, score: recency_bonus(path), matched: Vec::new() });
+                }
+                let (score, matched) = matcher.fuzzy_indices(path, query)?;
+                let file_name = path.rsplit('/').next().unwrap_or(path);
+                let name_bonus = matcher.fuzzy_match(file_name, query).unwrap_or(0);
+                Some(Ranked { file_idx, score: score + name_bonus + recency_bonus(path), matched })
+            })
+            .collect()
+    }
+
+    /// Desenha a lista por cima do editor. Retorna o arquivo escolhido.
+    pub fn ui(&mut self, ctx: &egui::Context) -> Option<PathBuf> {
+        if !self.is_open {
+            return None;
+        }
+        self.poll();
+
+        // As setas e o Enter são tratados aqui, antes que o campo de texto os use
+        let (up, down, enter, escape) = ctx.input_mut(|i| (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+        ));
+        if escape {
+            self.close();
+            return None;
+        }
+
+        let mut chosen = None;
+        egui::Window::new("Abrir arquivo")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 40.0))
+            .fixed_size(egui::vec2(520.0, 0.0))
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("Buscar arquivo pelo nome..."),
+                );
+                if self.request_focus {
+                    response.request_focus();
+                    self.request_focus = false;
+                }
+                self.rank();
+
+                if up {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+                if down && self.selected + 1 < self.ranked.len() {
+                    self.selected += 1;
+                }
+
+                if self.indexing.is_some() {
+                    ui.horizontal(|ui_status| {
+                        ui_status.spinner();
+                        ui_status.weak(format!("Indexando... {} arquivos", self.files.len()));
+                    });
+                } else if self.ranked.is_empty() {
+                    ui.weak("Nenhum arquivo encontrado.");
+                }
+
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui_scroll| {
+                    let text_color = ui_scroll.visuals().text_color();
+                    let match_color = ui_scroll.visuals().hyperlink_color;
+                    for (idx, ranked) in self.ranked.iter().enumerate() {
+                        let path = &self.files[ranked.file_idx];
+                        let mut job = LayoutJob::default();
+                        for (char_idx, ch) in path.chars().enumerate() {
+                            let color = if ranked.matched.contains(&char_idx) { match_color } else { text_color };
+                            job.append(ch.encode_utf8(&mut [0; 4]), 0.0, egui::TextFormat {
+                                font_id: egui::TextStyle::Body.resolve(ui_scroll.style()),
+                                color,
+                                ..Default::default()
+                            });
+                        }
+                        let is_selected = idx == self.selected;
+                        let response = ui_scroll.selectable_label(is_selected, job);
+                        if is_selected && (up || down) {
+                            response.scroll_to_me(None);
+                        }
+                        if response.clicked() {
+                            chosen = Some(idx);
+                        }
+                    }
+                });
+            });
+
+        if enter && !self.ranked.is_empty() {
+            chosen = Some(self.selected.min(self.ranked.len() - 1));
+        }
+        let path = chosen.and_then(|idx| {
+            let root = self.root.as_ref()?;
+            Some(root.join(&self.files[self.ranked[idx].file_idx]))
+        });
+        if path.is_some() {
+            self.close();
+        }
+        path
+    }
+}
+
+/// Caminho relativo com `/`, como mostrado na lista.
+fn relative_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Lista os arquivos de `root` (respeitando `.gitignore`) em lotes.
+fn index_files(root: &Path, tx: &mpsc::Sender<Vec<String>>, cancel: &AtomicBool, ctx: &egui::Context) {
+    let mut batch = Vec::with_capacity(INDEX_BATCH);
+    for entry in WalkBuilder::new(root).require_git(false).build().filter_map(Result::ok) {
+        if cancel.load(Ordering::SeqCst) {
+            return;
+        }
+        if !entry.file_type().is_some_and(|file_type| file_type.is_file()) {
+            continue;
+        }
+        if let Ok(relative) = entry.path().strip_prefix(root) {
+            batch.push(relative_path(relative));
+        }
+        if batch.len() >= INDEX_BATCH {
+            if tx.send(std::mem::take(&mut batch)).is_err() {
+                return;
+            }
+            ctx.request_repaint();
+        }
+    }
+    let _ = tx.send(batch);
+    ctx.request_repaint();
+}
@@ -5,5 +5,7 @@ pub mod file_explorer;
 pub mod syntax_highlighting;
 pub mod ui;
 pub mod terminal; // <--- ADICIONE ESTA LINHA
+pub mod tasks; // Tarefas do projeto e problem matchers
+pub mod commands; // Registro de comandos e atalhos
 
 pub use ui::app::MyApp; // Exporta MyApp para ser usado em main.rs
\ No newline at end of file
@@ -1,10 +1,14 @@
 // src/main.rs
 
 use lcode::MyApp;
-use egui::FontFamily::Proportional;
 use egui_phosphor::{add_to_fonts, Variant};
 
 fn main() -> Result<(), eframe::Error> {
+    // O terminal integrado usa tarefas tokio para ler e escrever no PTY, então o
+    // runtime precisa existir (e estar ativo na thread da UI) antes do eframe.
+    let runtime = tokio::runtime::Runtime::new().expect("Falha ao criar o runtime tokio");
+    let _runtime_guard = runtime.enter();
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size(egui::vec2(1200.0, 800.0))
@@ -1,60 +1,211 @@
 use syntect::parsing::{SyntaxSet, SyntaxReference};
-use syntect::highlighting::{ThemeSet, Theme, Style, Color};
-use syntect::easy::HighlightLines;
+use syntect::highlighting::{Color, ThemeSet, Theme};
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use std::path::Path;
+use ropey::Rope;
+
+use crate::syntax_highlighting::incremental::HighlightEngine;
+use crate::syntax_highlighting::syntaxes;
+use crate::syntax_highlighting::themes::{self, DEFAULT_THEME};
+use crate::syntax_highlighting::treesitter::TreeSitterLanguage;
+
+/// Backend usado para realçar uma linguagem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HighlightBackend {
+    #[default]
+    Syntect,
+    TreeSitter,
+}
+
+impl HighlightBackend {
+    pub const ALL: [Self; 2] = [Self::Syntect, Self::TreeSitter];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Syntect => "syntect",
+            Self::TreeSitter => "tree-sitter",
+        }
+    }
+}
 
 /// Struct para gerenciar o realce de sintaxe.
 pub struct SyntaxHighlighter {
-    syntax_set: SyntaxSet,
+    syntax_set: Arc<SyntaxSet>, // Compartilhado com a thread de realce
     theme_set: ThemeSet,
-    current_theme: Theme,
+    current_theme: Arc<Theme>,
+    current_theme_name: String,
+    revision: u64, // Muda a cada troca ou recarga de tema e de sintaxes
+    pub theme_errors: Vec<String>, // Arquivos de tema do usuário que não puderam ser lidos
+    pub syntax_errors: Vec<String>, // Arquivos de sintaxe do usuário que não puderam ser lidos
+    backends: HashMap<TreeSitterLanguage, HighlightBackend>, // Linguagens fora do padrão (syntect)
+    language_overrides: HashMap<PathBuf, String>, // Sintaxe escolhida à mão para um arquivo
 }
 
 impl SyntaxHighlighter {
     /// Cria uma nova instância do SyntaxHighlighter.
     pub fn new() -> Self {
-        let syntax_set = SyntaxSet::load_defaults_newlines();
-        let theme_set = ThemeSet::load_defaults();
-        let current_theme = theme_set.themes["base16-ocean.dark"].clone();
+        let (syntax_set, syntax_errors) = syntaxes::load_syntaxes();
+        let (theme_set, theme_errors) = themes::load_themes();
+        let current_theme_name = DEFAULT_THEME.to_string();
+        let current_theme = Arc::new(theme_set.themes[&current_theme_name].clone());
 
         Self {
-            syntax_set,
+            syntax_set: Arc::new(syntax_set),
             theme_set,
             current_theme,
+            current_theme_name,
+            revision: 0,
+            theme_errors,
+            syntax_errors,
+            backends: HashMap::new(),
+            language_overrides: HashMap::new(),
         }
     }
 
-    /// Retorna a referência de sintaxe baseada na extensão do arquivo.
-    fn get_syntax_for_file(&self, file_path: &Path) -> &SyntaxReference {
-        if let Some(extension) = file_path.extension().and_then(|s| s.to_str()) {
-            self.syntax_set.find_syntax_by_extension(extension).unwrap_or_else(|| {
-                self.syntax_set.find_syntax_plain_text()
-            })
-        } else {
-            self.syntax_set.find_syntax_plain_text()
+    /// Sintaxe do arquivo: a escolhida à mão, senão pelo nome ou extensão, senão pela
+    /// primeira linha (shebang, `<?xml`, modelines).
+    fn get_syntax_for_file(&self, file_path: &Path, content: &Rope) -> &SyntaxReference {
+        if let Some(syntax) = self.language_overrides.get(file_path).and_then(|name| self.syntax_set.find_syntax_by_name(name)) {
+            return syntax;
+        }
+        // Nomes como "Dockerfile" ou "Makefile" são registrados como extensão
+        let by_name = file_path.file_name().and_then(|s| s.to_str())
+            .and_then(|name| self.syntax_set.find_syntax_by_extension(name));
+        let by_extension = || file_path.extension().and_then(|s| s.to_str())
+            .and_then(|extension| self.syntax_set.find_syntax_by_extension(extension));
+        by_name
+            .or_else(by_extension)
+            .or_else(|| self.syntax_set.find_syntax_by_first_line(&first_line(content)))
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text())
+    }
+
+    /// Motor de realce para o arquivo, com a sintaxe e o tema atuais. É barato de
+    /// clonar e pode ser enviado para a thread de realce.
+    pub fn engine_for(&self, file_path: &Path, content: &Rope) -> HighlightEngine {
+        HighlightEngine {
+            syntax_set: Arc::clone(&self.syntax_set),
+            syntax_name: self.get_syntax_for_file(file_path, content).name.clone(),
+            theme: Arc::clone(&self.current_theme),
+            theme_name: self.current_theme_name.clone(),
+            tree_sitter: self.tree_sitter_language(file_path)
+                .filter(|language| self.backend(*language) == HighlightBackend::TreeSitter),
+            revision: self.revision,
+        }
+    }
+
+    /// Nome da sintaxe usada para o arquivo.
+    pub fn syntax_name_for(&self, file_path: &Path, content: &Rope) -> String {
+        self.get_syntax_for_file(file_path, content).name.clone()
+    }
+
+    /// Gramática tree-sitter do arquivo. Com a linguagem escolhida à mão, vale a escolha.
+    pub fn tree_sitter_language(&self, file_path: &Path) -> Option<TreeSitterLanguage> {
+        match self.language_overrides.get(file_path) {
+            Some(name) => TreeSitterLanguage::from_syntax_name(name),
+            None => TreeSitterLanguage::from_path(file_path),
+        }
+    }
+
+    /// Linguagem escolhida à mão para o arquivo, se houver.
+    pub fn language_override(&self, file_path: &Path) -> Option<&str> {
+        self.language_overrides.get(file_path).map(String::as_str)
+    }
+
+    /// Força a sintaxe de um arquivo (`None` volta à detecção automática).
+    pub fn set_language_override(&mut self, file_path: &Path, syntax_name: Option<String>) {
+        match syntax_name {
+            Some(name) => {
+                eprintln!("Linguagem de {} definida como {}", file_path.display(), name);
+                self.language_overrides.insert(file_path.to_path_buf(), name);
+            }
+            None => {
+                self.language_overrides.remove(file_path);
+            }
         }
     }
 
-    /// Realça uma linha de texto.
-    pub fn highlight_line<'a>(&self, line: &'a str, file_path: &Path) -> Vec<(Style, &'a str)> {
-        let syntax = self.get_syntax_for_file(file_path);
-        let mut highlighter = HighlightLines::new(syntax, &self.current_theme);
+    /// Nomes das sintaxes disponíveis, em ordem alfabética.
+    pub fn syntax_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.syntax_set.syntaxes().iter()
+            .filter(|syntax| !syntax.hidden)
+            .map(|syntax| syntax.name.clone())
+            .collect();
+        names.sort_by_key(|name| name.to_lowercase());
+        names.dedup();
+        names
+    }
+
+    /// Lê de novo a pasta de sintaxes do usuário. Os documentos abertos são realçados
+    /// de novo no próximo frame.
+    pub fn reload_syntaxes(&mut self) {
+        let (syntax_set, syntax_errors) = syntaxes::load_syntaxes();
+        self.syntax_set = Arc::new(syntax_set);
+        self.syntax_errors = syntax_errors;
+        self.revision += 1;
+    }
+
+    /// Backend escolhido para uma linguagem.
+    pub fn backend(&self, language: TreeSitterLanguage) -> HighlightBackend {
+        self.backends.get(&language).copied().unwrap_or_default()
+    }
 
-        highlighter.highlight_line(line, &self.syntax_set).unwrap_or_default()
+    /// Escolhe o backend de uma linguagem. Os documentos abertos são realçados de novo
+    /// no próximo frame.
+    pub fn set_backend(&mut self, language: TreeSitterLanguage, backend: HighlightBackend) {
+        self.backends.insert(language, backend);
+        eprintln!("Realce de {} agora usa {}", language.name(), backend.label());
+    }
+
+    /// Cor do texto sem realce no tema atual.
+    pub fn default_foreground(&self) -> egui::Color32 {
+        self.current_theme.settings.foreground
+            .map(Self::syntect_color_to_egui_color)
+            .unwrap_or(egui::Color32::LIGHT_GRAY)
     }
 
     /// Altera o tema atual do realce de sintaxe.
     pub fn set_theme(&mut self, theme_name: &str) {
         if let Some(theme) = self.theme_set.themes.get(theme_name) {
-            self.current_theme = theme.clone();
+            self.current_theme = Arc::new(theme.clone());
+            self.current_theme_name = theme_name.to_string();
+            self.revision += 1;
             eprintln!("Tema de realce de sintaxe alterado para: {}", theme_name);
         } else {
             eprintln!("Tema '{}' não encontrado. Mantendo o tema atual.", theme_name);
         }
     }
 
+    /// Lê de novo a pasta de temas do usuário. O tema atual é reaplicado se o arquivo
+    /// dele mudou.
+    pub fn reload_themes(&mut self) {
+        let (theme_set, theme_errors) = themes::load_themes();
+        self.theme_set = theme_set;
+        self.theme_errors = theme_errors;
+        if let Some(theme) = self.theme_set.themes.get(&self.current_theme_name) {
+            self.current_theme = Arc::new(theme.clone());
+        }
+        self.revision += 1;
+    }
+
+    /// Nome do tema atual.
+    pub fn current_theme_name(&self) -> &str {
+        &self.current_theme_name
+    }
+
+    /// Muda a cada `set_theme`, `reload_themes` ou `reload_syntaxes`.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Tema atual, para derivar as cores da interface.
+    pub fn current_theme(&self) -> &Theme {
+        &self.current_theme
+    }
+
     /// Lista os temas disponíveis.
     pub fn available_themes(&self) -> Vec<String> {
         self.theme_set.themes.keys().cloned().collect()
@@ -72,3 +223,10 @@ impl Default for SyntaxHighlighter {
         Self::new()
     }
 }
+
+/// Primeira linha do documento, limitada para não passar arquivos minificados inteiros
+/// pelas expressões de detecção a cada frame.
+fn first_line(content: &Rope) -> Cow<'_, str> {
+    let line = content.line(0);
+    line.slice(..line.len_chars().min(256)).into()
+}
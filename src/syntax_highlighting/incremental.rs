@@ -0,0 +1,268 @@
+// src/syntax_highlighting/incremental.rs
+
+use std::borrow::Cow;
+use std::ops::Range;
+use std::sync::Arc;
+
+use ropey::Rope;
+use syntect::highlighting::{HighlightIterator, HighlightState, Highlighter, Style, Theme};
+use syntect::parsing::{ParseState, ScopeStack, SyntaxSet};
+
+use crate::core::editor::TextChange;
+use crate::syntax_highlighting::treesitter::TreeSitterLanguage;
+
+/// Trechos realçados de uma linha: estilo e intervalo em bytes dentro da linha.
+pub type LineSpans = Vec<(Style, Range<usize>)>;
+
+/// Sintaxe, tema, backend e revisão de um motor de realce; quando muda, o realce é refeito.
+pub type EngineKey = (String, String, Option<TreeSitterLanguage>, u64);
+
+/// Estado do syntect em uma fronteira de linha: é tudo o que é preciso para
+/// continuar realçando a partir dali.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineState {
+    pub parse: ParseState,
+    pub highlight: HighlightState,
+}
+
+/// A cada quantas linhas o estado do syntect é guardado no cache.
+const CHECKPOINT_INTERVAL: usize = 128;
+
+/// Cache de realce de um documento, com o estado do syntect guardado a cada
+/// `CHECKPOINT_INTERVAL` linhas.
+///
+/// O realce de uma linha qualquer recomeça do checkpoint anterior a ela, então a memória
+/// não cresce com o tamanho do arquivo. Depois de uma edição, o realce refeito para
+/// assim que o estado num checkpoint abaixo dela volta a ser o de antes.
+#[derive(Debug, Default)]
+pub struct HighlightCache {
+    checkpoints: Vec<(usize, LineState)>, // Estado no início da linha, em ordem; os antes de `valid_up_to` estão conferidos
+    valid_up_to: usize, // Linhas anteriores a esta já foram conferidas
+    frontier: Option<LineState>, // Estado no início de `valid_up_to` (`None`: estado inicial)
+    resume: Option<(usize, LineState)>, // Até onde o realce ia antes da edição, se os estados convergirem
+    converge_after: usize, // Só checkpoints depois desta linha (abaixo da edição) levam a `resume`
+    key: Option<EngineKey>, // Sintaxe e tema usados no cache
+}
+
+impl HighlightCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra uma edição. Os checkpoints abaixo dela são deslocados pelas linhas
+    /// inseridas/removidas e servem para detectar quando o realce converge.
+    pub fn edit(&mut self, change: &TextChange) {
+        let first_line = change.first_line();
+        let old_end = change.old_end_position.0;
+        let new_end = change.new_end_position.0;
+        let shift = |line: usize| line.saturating_add_signed(change.line_delta());
+
+        self.resume = if self.valid_up_to > old_end {
+            self.frontier.clone().map(|state| (shift(self.valid_up_to), state))
+        } else {
+            match self.resume.take() {
+                Some((line, state)) if line > old_end => Some((shift(line), state)),
+                Some((line, state)) if line <= first_line => Some((line, state)),
+                _ => None,
+            }
+        };
+
+        self.checkpoints.retain(|(line, _)| *line <= first_line || *line > old_end);
+        for (line, _) in &mut self.checkpoints {
+            if *line > old_end {
+                *line = shift(*line);
+            }
+        }
+
+        if first_line < self.valid_up_to {
+            self.rewind_to(first_line);
+        }
+        // Checkpoints ainda não conferidos acima do fim da edição não servem para
+        // convergir: o texto entre eles e `resume` mudou.
+        let valid_up_to = self.valid_up_to;
+        self.checkpoints.retain(|(line, _)| *line <= valid_up_to || *line > new_end);
+        self.converge_after = valid_up_to;
+    }
+
+    /// Descarta tudo (troca de tema ou de sintaxe).
+    pub fn clear(&mut self) {
+        self.checkpoints.clear();
+        self.valid_up_to = 0;
+        self.frontier = None;
+        self.resume = None;
+    }
+
+    /// Primeira linha ainda não conferida.
+    pub fn valid_up_to(&self) -> usize {
+        self.valid_up_to
+    }
+
+    /// Volta a conferência para o último checkpoint em ou antes de `line`.
+    fn rewind_to(&mut self, line: usize) {
+        let pos = self.checkpoints.partition_point(|(l, _)| *l <= line);
+        match pos.checked_sub(1).map(|i| &self.checkpoints[i]) {
+            Some((l, state)) => {
+                self.valid_up_to = *l;
+                self.frontier = Some(state.clone());
+            }
+            None => {
+                self.valid_up_to = 0;
+                self.frontier = None;
+            }
+        }
+    }
+
+    /// Último checkpoint conferido em ou antes de `line`.
+    fn checkpoint_before(&self, line: usize) -> Option<&(usize, LineState)> {
+        let line = line.min(self.valid_up_to.saturating_sub(1));
+        let pos = self.checkpoints.partition_point(|(l, _)| *l <= line);
+        pos.checked_sub(1).map(|i| &self.checkpoints[i])
+    }
+}
+
+/// Sintaxe e tema usados para realçar um documento. Independe do `SyntaxHighlighter`
+/// para poder rodar na thread de realce.
+#[derive(Clone)]
+pub struct HighlightEngine {
+    pub(crate) syntax_set: Arc<SyntaxSet>,
+    pub(crate) syntax_name: String,
+    pub(crate) theme: Arc<Theme>,
+    pub(crate) theme_name: String,
+    pub(crate) tree_sitter: Option<TreeSitterLanguage>, // Usa o tree-sitter em vez do syntect
+    pub(crate) revision: u64, // Recarregar temas ou sintaxes com o mesmo nome também refaz o realce
+}
+
+impl std::fmt::Debug for HighlightEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HighlightEngine")
+            .field("syntax_name", &self.syntax_name)
+            .field("theme_name", &self.theme_name)
+            .field("tree_sitter", &self.tree_sitter)
+            .finish()
+    }
+}
+
+impl HighlightEngine {
+    /// Identifica sintaxe, tema e backend; o cache é descartado quando ela muda.
+    pub fn key(&self) -> EngineKey {
+        (self.syntax_name.clone(), self.theme_name.clone(), self.tree_sitter, self.revision)
+    }
+
+    /// Garante que as linhas `..end` do documento estejam conferidas no `cache` e
+    /// retorna o realce das linhas processadas de novo.
+    ///
+    /// O realce continua do estado no fim da linha anterior, então construções de
+    /// várias linhas (comentários de bloco, strings) saem corretas. Ao chegar a um
+    /// checkpoint com o mesmo estado de antes de uma edição, pula direto para onde o
+    /// realce já tinha chegado.
+    pub fn update_cache(&self, cache: &mut HighlightCache, content: &Rope, end: usize) -> Vec<(usize, LineSpans)> {
+        let key = self.key();
+        if cache.key.as_ref() != Some(&key) {
+            cache.clear();
+            cache.key = Some(key);
+        }
+
+        let total_lines = content.len_lines();
+        if cache.valid_up_to > total_lines {
+            cache.rewind_to(total_lines);
+        }
+        let end = end.min(total_lines);
+
+        let highlighter = Highlighter::new(&self.theme);
+        let mut state = cache.frontier.clone().unwrap_or_else(|| self.initial_state(&highlighter));
+        let mut idx = cache.valid_up_to;
+        let mut lines = Vec::new();
+        while idx < end {
+            match cache.checkpoints.binary_search_by_key(&idx, |(line, _)| *line) {
+                Ok(pos) if cache.checkpoints[pos].1 == state => {
+                    if let Some((resume_line, resume_state)) = cache.resume.take_if(|(line, _)| idx > cache.converge_after && *line > idx && *line <= total_lines) {
+                        idx = resume_line;
+                        state = resume_state;
+                        continue;
+                    }
+                }
+                Ok(pos) => cache.checkpoints[pos].1 = state.clone(),
+                Err(pos) => {
+                    if pos == 0 || cache.checkpoints[pos - 1].0 + CHECKPOINT_INTERVAL <= idx {
+                        cache.checkpoints.insert(pos, (idx, state.clone()));
+                    }
+                }
+            }
+
+            let line: Cow<str> = content.line(idx).into();
+            let (spans, next) = self.highlight_line(&highlighter, state, &line);
+            lines.push((idx, spans));
+            state = next;
+            idx += 1;
+        }
+
+        if idx > cache.valid_up_to {
+            cache.valid_up_to = idx;
+            cache.frontier = Some(state);
+        }
+        if cache.resume.as_ref().is_some_and(|(line, _)| *line <= cache.valid_up_to) {
+            cache.resume = None;
+        }
+        lines
+    }
+
+    /// Realce das linhas `range` já conferidas, refeito a partir do checkpoint anterior.
+    pub fn highlight_range(&self, cache: &HighlightCache, content: &Rope, range: Range<usize>) -> Vec<(usize, LineSpans)> {
+        let end = range.end.min(cache.valid_up_to).min(content.len_lines());
+        let highlighter = Highlighter::new(&self.theme);
+        let (mut idx, mut state) = match cache.checkpoint_before(range.start) {
+            Some((line, state)) => (*line, state.clone()),
+            None => (0, self.initial_state(&highlighter)),
+        };
+
+        let mut lines = Vec::new();
+        while idx < end {
+            let line: Cow<str> = content.line(idx).into();
+            let (spans, next) = self.highlight_line(&highlighter, state, &line);
+            if idx >= range.start {
+                lines.push((idx, spans));
+            }
+            state = next;
+            idx += 1;
+        }
+        lines
+    }
+
+    fn initial_state(&self, highlighter: &Highlighter) -> LineState {
+        let syntax = self.syntax_set
+            .find_syntax_by_name(&self.syntax_name)
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+        LineState {
+            parse: ParseState::new(syntax),
+            highlight: HighlightState::new(highlighter, ScopeStack::new()),
+        }
+    }
+
+    /// Realça uma única linha a partir de `start`, retornando também o estado ao fim dela.
+    fn highlight_line(&self, highlighter: &Highlighter, start: LineState, line: &str) -> (LineSpans, LineState) {
+        let LineState { mut parse, mut highlight } = start;
+        let ops = parse.parse_line(line, &self.syntax_set).unwrap_or_default();
+
+        let mut spans = Vec::new();
+        let mut offset = 0;
+        for (style, text) in HighlightIterator::new(&mut highlight, &ops, line, highlighter) {
+            spans.push((style, offset..offset + text.len()));
+            offset += text.len();
+        }
+        (spans, LineState { parse, highlight })
+    }
+}
+
+/// Ajusta um cache indexado por linha depois de uma edição em `first_line`: linhas
+/// novas entram (vazias) logo depois dela e linhas removidas saem dali.
+pub fn splice_line_cache<T>(cache: &mut Vec<Option<T>>, first_line: usize, total_lines: usize) {
+    let at = (first_line + 1).min(cache.len());
+    if total_lines > cache.len() {
+        let added = total_lines - cache.len();
+        cache.splice(at..at, std::iter::repeat_with(|| None).take(added));
+    } else {
+        let removed = (cache.len() - total_lines).min(cache.len() - at);
+        cache.drain(at..at + removed);
+        cache.truncate(total_lines);
+    }
+}
@@ -1,4 +1,8 @@
 // src/syntax_highlighting/mod.rs
 
 pub mod themes;     // Para os temas de cor
-pub mod highlighter; 
\ No newline at end of file
+pub mod highlighter;
+pub mod syntaxes; // Sintaxes .sublime-syntax do usuário
+pub mod incremental; // Cache de estados do syntect por linha
+pub mod worker; // Realce em segundo plano, linhas visíveis primeiro 
+pub mod treesitter; // Backend alternativo com gramáticas tree-sitter
@@ -0,0 +1,49 @@
+// src/syntax_highlighting/syntaxes.rs
+
+use std::path::{Path, PathBuf};
+
+use syntect::parsing::{SyntaxDefinition, SyntaxSet, SyntaxSetBuilder};
+
+use crate::core::paths::user_config_dir;
+
+/// Pasta de sintaxes do usuário (`~/.config/lcode/syntaxes`).
+pub fn user_syntaxes_dir() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join("syntaxes"))
+}
+
+/// Sintaxes embutidas do syntect mais os `.sublime-syntax` da pasta do usuário.
+/// Retorna também os erros de cada arquivo que não pôde ser lido; os demais são
+/// carregados normalmente.
+pub fn load_syntaxes() -> (SyntaxSet, Vec<String>) {
+    let mut builder = SyntaxSet::load_defaults_newlines().into_builder();
+    let mut errors = Vec::new();
+    if let Some(dir) = user_syntaxes_dir() {
+        add_syntaxes_from_dir(&mut builder, &dir, &mut errors);
+    }
+    (builder.build(), errors)
+}
+
+fn add_syntaxes_from_dir(builder: &mut SyntaxSetBuilder, dir: &Path, errors: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return; // Sem pasta de sintaxes: só as embutidas
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "sublime-syntax"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let fallback_name = path.file_stem().and_then(|s| s.to_str());
+        let loaded = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| SyntaxDefinition::load_from_str(&text, true, fallback_name).map_err(|e| e.to_string()));
+        match loaded {
+            Ok(syntax) => {
+                eprintln!("Sintaxe carregada: {} ({})", syntax.name, path.display());
+                builder.add(syntax);
+            }
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+}
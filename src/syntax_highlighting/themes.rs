@@ -0,0 +1,340 @@
+// src/syntax_highlighting/themes.rs
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Deserialize;
+use syntect::highlighting::{
+    Color, FontStyle, ScopeSelectors, StyleModifier, Theme, ThemeItem, ThemeSet, ThemeSettings,
+};
+
+use crate::core::paths::user_config_dir;
+
+/// Tema usado quando nenhum outro foi escolhido.
+pub const DEFAULT_THEME: &str = "base16-ocean.dark";
+
+/// Pasta de temas do usuário (`~/.config/lcode/themes`).
+pub fn user_themes_dir() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join("themes"))
+}
+
+/// Temas embutidos do syntect mais os da pasta do usuário. Um tema do usuário com o
+/// mesmo nome de um embutido o substitui. Retorna também os erros de cada arquivo que
+/// não pôde ser lido.
+pub fn load_themes() -> (ThemeSet, Vec<String>) {
+    let mut theme_set = ThemeSet::load_defaults();
+    let mut errors = Vec::new();
+    if let Some(dir) = user_themes_dir() {
+        load_themes_from_dir(&mut theme_set, &dir, &mut errors);
+    }
+    (theme_set, errors)
+}
+
+fn load_themes_from_dir(theme_set: &mut ThemeSet, dir: &Path, errors: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return; // Sem pasta de temas: só os embutidos
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|entry| Some(entry.ok()?.path())).collect();
+    paths.sort();
+
+    for path in paths {
+        let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+            continue;
+        };
+        let loaded = match extension {
+            "tmTheme" => ThemeSet::get_theme(&path).map_err(|e| e.to_string()),
+            "sublime-color-scheme" => load_color_scheme(&path),
+            _ => continue,
+        };
+        match loaded {
+            Ok(theme) => {
+                // O nome do tema é o do arquivo, como nos temas embutidos
+                let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+                eprintln!("Tema carregado: {} ({})", name, path.display());
+                theme_set.themes.insert(name, theme);
+            }
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+}
+
+/// Formato `.sublime-color-scheme` (JSON com comentários e vírgulas sobrando).
+#[derive(Debug, Deserialize)]
+struct ColorScheme {
+    name: Option<String>,
+    author: Option<String>,
+    #[serde(default)]
+    variables: HashMap<String, String>,
+    #[serde(default)]
+    globals: HashMap<String, String>,
+    #[serde(default)]
+    rules: Vec<ColorSchemeRule>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ColorSchemeRule {
+    scope: String,
+    foreground: Option<serde_json::Value>, // Pode ser uma lista (gradiente); usamos a primeira cor
+    background: Option<String>,
+    font_style: Option<String>,
+}
+
+/// Converte um `.sublime-color-scheme` no tema do syntect.
+fn load_color_scheme(path: &Path) -> Result<Theme, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let scheme: ColorScheme = serde_json::from_str(&relax_json(&text)).map_err(|e| e.to_string())?;
+    let color = |value: &str| resolve_color(value, &scheme.variables, 0);
+    let global = |key: &str| scheme.globals.get(key).and_then(|value| color(value));
+
+    let settings = ThemeSettings {
+        foreground: global("foreground"),
+        background: global("background"),
+        caret: global("caret"),
+        line_highlight: global("line_highlight"),
+        misspelling: global("misspelling"),
+        accent: global("accent"),
+        gutter: global("gutter"),
+        gutter_foreground: global("gutter_foreground"),
+        selection: global("selection"),
+        selection_foreground: global("selection_foreground"),
+        selection_border: global("selection_border"),
+        inactive_selection: global("inactive_selection"),
+        inactive_selection_foreground: global("inactive_selection_foreground"),
+        find_highlight: global("find_highlight"),
+        find_highlight_foreground: global("find_highlight_foreground"),
+        highlight: global("highlight"),
+        guide: global("guide"),
+        active_guide: global("active_guide"),
+        ..ThemeSettings::default()
+    };
+
+    let mut scopes = Vec::new();
+    for rule in &scheme.rules {
+        let Ok(scope) = ScopeSelectors::from_str(&rule.scope) else {
+            eprintln!("Escopo inválido em {}: {}", path.display(), rule.scope);
+            continue;
+        };
+        let foreground = match &rule.foreground {
+            Some(serde_json::Value::String(value)) => color(value),
+            Some(serde_json::Value::Array(values)) => values.first().and_then(|v| v.as_str()).and_then(color),
+            _ => None,
+        };
+        scopes.push(ThemeItem {
+            scope,
+            style: StyleModifier {
+                foreground,
+                background: rule.background.as_deref().and_then(color),
+                font_style: rule.font_style.as_deref().map(parse_font_style),
+            },
+        });
+    }
+
+    Ok(Theme {
+        name: scheme.name.clone(),
+        author: scheme.author.clone(),
+        settings,
+        scopes,
+    })
+}
+
+/// Remove comentários `//` e `/* */` e vírgulas antes de `]`/`}`, que o Sublime aceita
+/// e o JSON não.
+fn relax_json(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            (']' | '}', _) => {
+                let trimmed = out.trim_end().len();
+                if out[..trimmed].ends_with(',') {
+                    out.truncate(trimmed - 1);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Interpreta uma cor do `.sublime-color-scheme`: `#rgb[a]`, `#rrggbb[aa]`, `rgb()`,
+/// `rgba()`, `hsl()`, `hsla()`, `var(nome)` e `color(<cor> alpha(x))`. Outros ajustes
+/// de `color()` são ignorados.
+fn resolve_color(value: &str, variables: &HashMap<String, String>, depth: usize) -> Option<Color> {
+    let value = value.trim();
+    if depth > 8 {
+        return None; // Variáveis que se referem em ciclo
+    }
+    if value.starts_with('#') {
+        return Color::from_str(value).ok();
+    }
+    let (function, args) = value.strip_suffix(')').and_then(|v| v.split_once('('))?;
+    match function.trim() {
+        "var" => resolve_color(variables.get(args.trim())?, variables, depth + 1),
+        "rgb" | "rgba" => {
+            let parts = split_args(args);
+            let channel = |idx: usize| parts.get(idx)?.trim().parse::<f32>().ok();
+            Some(Color {
+                r: channel(0)?.clamp(0.0, 255.0) as u8,
+                g: channel(1)?.clamp(0.0, 255.0) as u8,
+                b: channel(2)?.clamp(0.0, 255.0) as u8,
+                a: parts.get(3).and_then(|a| parse_alpha(a)).unwrap_or(255),
+            })
+        }
+        "hsl" | "hsla" => {
+            let parts = split_args(args);
+            let number = |idx: usize| parts.get(idx)?.trim().trim_end_matches('%').parse::<f32>().ok();
+            let (r, g, b) = hsl_to_rgb(number(0)?, number(1)? / 100.0, number(2)? / 100.0);
+            Some(Color { r, g, b, a: parts.get(3).and_then(|a| parse_alpha(a)).unwrap_or(255) })
+        }
+        "color" => {
+            // color(var(x) alpha(0.5)): a cor base vai até o primeiro ajuste
+            let base_end = args.find(" alpha(").or_else(|| args.find(" a(")).unwrap_or(args.len());
+            let mut base = resolve_color(&args[..base_end], variables, depth + 1)?;
+            if base_end < args.len() {
+                let adjuster = args[base_end..].trim();
+                let inner = adjuster.split_once('(').and_then(|(_, rest)| rest.split(')').next());
+                if let Some(alpha) = inner.and_then(parse_alpha) {
+                    base.a = alpha;
+                }
+            }
+            Some(base)
+        }
+        _ => None,
+    }
+}
+
+fn split_args(args: &str) -> Vec<&str> {
+    args.split(',').collect()
+}
+
+/// Alfa como fração (`0.5`) ou porcentagem (`50%`).
+fn parse_alpha(value: &str) -> Option<u8> {
+    let value = value.trim();
+    let fraction = match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f32>().ok()? / 100.0,
+        None => value.parse::<f32>().ok()?,
+    };
+    Some((fraction.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (u8, u8, u8) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let h = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let to_u8 = |v: f32| ((v + m).clamp(0.0, 1.0) * 255.0).round() as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
+
+fn parse_font_style(value: &str) -> FontStyle {
+    value.split_whitespace().fold(FontStyle::empty(), |style, word| match word {
+        "bold" => style | FontStyle::BOLD,
+        "italic" => style | FontStyle::ITALIC,
+        "underline" => style | FontStyle::UNDERLINE,
+        _ => style,
+    })
+}
+
+/// Visual do egui (painéis, seleção, cursor) derivado das cores do tema, para que a
+/// interface acompanhe o editor.
+pub fn visuals_for_theme(theme: &Theme) -> egui::Visuals {
+    let settings = &theme.settings;
+    let to_egui = |color: Color| egui::Color32::from_rgb(color.r, color.g, color.b);
+
+    let Some(background) = settings.background.map(to_egui) else {
+        return egui::Visuals::dark();
+    };
+    let is_dark = relative_luminance(background) < 0.2;
+    let mut visuals = if is_dark { egui::Visuals::dark() } else { egui::Visuals::light() };
+    // Tons um pouco mais claros (ou escuros, em temas claros) que o fundo
+    let shade = |amount: f32| {
+        if is_dark {
+            background.lerp_to_gamma(egui::Color32::WHITE, amount)
+        } else {
+            background.lerp_to_gamma(egui::Color32::BLACK, amount)
+        }
+    };
+
+    visuals.panel_fill = background;
+    visuals.window_fill = background;
+    visuals.extreme_bg_color = settings.gutter.map(to_egui).unwrap_or_else(|| shade(0.04));
+    visuals.faint_bg_color = settings.line_highlight.map(to_egui).unwrap_or_else(|| shade(0.06));
+    visuals.code_bg_color = shade(0.08);
+
+    visuals.widgets.noninteractive.bg_fill = background;
+    visuals.widgets.noninteractive.weak_bg_fill = background;
+    visuals.widgets.noninteractive.bg_stroke.color = shade(0.15);
+    visuals.widgets.inactive.bg_fill = shade(0.12);
+    visuals.widgets.inactive.weak_bg_fill = shade(0.08);
+    visuals.widgets.hovered.bg_fill = shade(0.2);
+    visuals.widgets.hovered.weak_bg_fill = shade(0.16);
+    visuals.widgets.active.bg_fill = shade(0.25);
+    visuals.widgets.active.weak_bg_fill = shade(0.22);
+
+    if let Some(foreground) = settings.foreground.map(to_egui) {
+        visuals.widgets.noninteractive.fg_stroke.color = foreground;
+        visuals.widgets.inactive.fg_stroke.color = foreground;
+    }
+    if let Some(selection) = settings.selection {
+        // A seleção é pintada por cima do texto: a transparência do tema é mantida
+        visuals.selection.bg_fill = egui::Color32::from_rgba_unmultiplied(selection.r, selection.g, selection.b, selection.a);
+    }
+    if let Some(selection_foreground) = settings.selection_foreground.map(to_egui) {
+        visuals.selection.stroke.color = selection_foreground;
+    }
+    if let Some(caret) = settings.caret.or(settings.foreground).map(to_egui) {
+        visuals.text_cursor.stroke.color = caret;
+    }
+    if let Some(accent) = settings.accent.map(to_egui) {
+        visuals.hyperlink_color = accent;
+    }
+    visuals
+}
+
+/// Luminância relativa (0 = preto, 1 = branco).
+fn relative_luminance(color: egui::Color32) -> f32 {
+    let linear = egui::Rgba::from(color);
+    0.2126 * linear.r() + 0.7152 * linear.g() + 0.0722 * linear.b()
+}
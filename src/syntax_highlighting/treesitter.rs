@@ -0,0 +1,312 @@
+// src/syntax_highlighting/treesitter.rs
+
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+
+use ropey::Rope;
+use streaming_iterator::StreamingIterator;
+use syntect::highlighting::{Highlighter, Style, Theme};
+use syntect::parsing::Scope;
+use tree_sitter::{InputEdit, Language, Node, ParseOptions, Parser, Point, Query, QueryCursor, Tree};
+
+use crate::core::editor::TextChange;
+use crate::syntax_highlighting::incremental::LineSpans;
+
+/// Linguagens com gramática tree-sitter embutida.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TreeSitterLanguage {
+    Rust,
+    Json,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Python,
+    Sql,
+}
+
+impl TreeSitterLanguage {
+    /// Linguagem de um arquivo pela extensão, se houver gramática para ela.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "rs" => Some(Self::Rust),
+            "json" => Some(Self::Json),
+            "js" | "mjs" | "cjs" | "jsx" => Some(Self::JavaScript),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" => Some(Self::Tsx),
+            "py" | "pyi" => Some(Self::Python),
+            "sql" => Some(Self::Sql),
+            _ => None,
+        }
+    }
+
+    /// Linguagem correspondente a uma sintaxe do syntect, para quando o usuário escolhe
+    /// a linguagem de um arquivo à mão.
+    pub fn from_syntax_name(name: &str) -> Option<Self> {
+        match name {
+            "Rust" => Some(Self::Rust),
+            "JSON" => Some(Self::Json),
+            "JavaScript" | "JavaScript (Babel)" | "JSX" => Some(Self::JavaScript),
+            "TypeScript" => Some(Self::TypeScript),
+            "TypeScriptReact" | "TSX" => Some(Self::Tsx),
+            "Python" => Some(Self::Python),
+            "SQL" => Some(Self::Sql),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rust => "Rust",
+            Self::Json => "JSON",
+            Self::JavaScript => "JavaScript",
+            Self::TypeScript => "TypeScript",
+            Self::Tsx => "TSX",
+            Self::Python => "Python",
+            Self::Sql => "SQL",
+        }
+    }
+
+    fn language(&self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::Json => tree_sitter_json::LANGUAGE.into(),
+            Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::Sql => tree_sitter_sequel::LANGUAGE.into(),
+        }
+    }
+
+    /// Consulta de realce. As de TypeScript estendem a de JavaScript, como no
+    /// `package.json` das gramáticas.
+    fn highlights_query(&self) -> String {
+        match self {
+            Self::Rust => tree_sitter_rust::HIGHLIGHTS_QUERY.to_string(),
+            Self::Json => tree_sitter_json::HIGHLIGHTS_QUERY.to_string(),
+            Self::JavaScript => format!(
+                "{}\n{}",
+                tree_sitter_javascript::JSX_HIGHLIGHT_QUERY,
+                tree_sitter_javascript::HIGHLIGHT_QUERY,
+            ),
+            Self::TypeScript => format!(
+                "{}\n{}",
+                tree_sitter_javascript::HIGHLIGHT_QUERY,
+                tree_sitter_typescript::HIGHLIGHTS_QUERY,
+            ),
+            Self::Tsx => format!(
+                "{}\n{}\n{}",
+                tree_sitter_javascript::JSX_HIGHLIGHT_QUERY,
+                tree_sitter_javascript::HIGHLIGHT_QUERY,
+                tree_sitter_typescript::HIGHLIGHTS_QUERY,
+            ),
+            Self::Python => tree_sitter_python::HIGHLIGHTS_QUERY.to_string(),
+            Self::Sql => tree_sitter_sequel::HIGHLIGHTS_QUERY.to_string(),
+        }
+    }
+}
+
+/// Escopo TextMate equivalente a uma captura do tree-sitter, para que o tema do
+/// syntect pinte os dois backends da mesma forma.
+fn scope_for_capture(name: &str) -> &'static str {
+    let mut name = name;
+    loop {
+        let scope = match name {
+            "comment.documentation" => Some("comment.block.documentation"),
+            "comment" => Some("comment"),
+            "conditional" => Some("keyword.control"),
+            "keyword" => Some("keyword"),
+            "operator" => Some("keyword.operator"),
+            "string.special.key" => Some("string.key"),
+            "string" => Some("string"),
+            "escape" | "string.escape" => Some("constant.character.escape"),
+            "number" | "float" => Some("constant.numeric"),
+            "constant.builtin" | "boolean" => Some("constant.language"),
+            "constant" => Some("constant"),
+            "function.builtin" => Some("support.function"),
+            "function.macro" => Some("entity.name.function.macro"),
+            "function" => Some("entity.name.function"),
+            "constructor" => Some("entity.name.type"),
+            "type.builtin" => Some("storage.type"),
+            "type.qualifier" | "storageclass" => Some("storage.modifier"),
+            "type" => Some("entity.name.type"),
+            "attribute" => Some("entity.other.attribute-name"),
+            "tag" => Some("entity.name.tag"),
+            "label" => Some("entity.name.label"),
+            "property" | "field" => Some("variable.other.member"),
+            "variable.builtin" => Some("variable.language"),
+            "variable.parameter" | "parameter" => Some("variable.parameter"),
+            "variable" => Some("variable"),
+            "punctuation.special" => Some("punctuation.section.embedded"),
+            "punctuation" => Some("punctuation"),
+            "embedded" => Some("source"),
+            _ => None,
+        };
+        if let Some(scope) = scope {
+            return scope;
+        }
+        // "function.method" cai em "function", e assim por diante
+        match name.rfind('.') {
+            Some(dot) => name = &name[..dot],
+            None => return "source",
+        }
+    }
+}
+
+/// Documento analisado pelo tree-sitter. A árvore é ajustada a cada edição e a
+/// reanálise reaproveita tudo o que a edição não tocou.
+pub struct TreeSitterDocument {
+    language: TreeSitterLanguage,
+    parser: Parser,
+    query: Query,
+    tree: Option<Tree>,
+    needs_parse: bool,
+}
+
+impl TreeSitterDocument {
+    pub fn new(language: TreeSitterLanguage) -> Result<Self, String> {
+        let ts_language = language.language();
+        let mut parser = Parser::new();
+        parser.set_language(&ts_language)
+            .map_err(|e| format!("Gramática {} incompatível: {}", language.name(), e))?;
+        let query = Query::new(&ts_language, &language.highlights_query())
+            .map_err(|e| format!("Consulta de realce de {} inválida: {}", language.name(), e))?;
+
+        Ok(Self {
+            language,
+            parser,
+            query,
+            tree: None,
+            needs_parse: true,
+        })
+    }
+
+    pub fn language(&self) -> TreeSitterLanguage {
+        self.language
+    }
+
+    /// Árvore sintática da última análise completa.
+    pub fn tree(&self) -> Option<&Tree> {
+        self.tree.as_ref()
+    }
+
+    /// Ajusta a árvore atual a uma edição; a reanálise acontece no próximo `parse`.
+    pub fn edit(&mut self, change: &TextChange) {
+        let point = |(row, column): (usize, usize)| Point { row, column };
+        if let Some(tree) = &mut self.tree {
+            tree.edit(&InputEdit {
+                start_byte: change.start_byte,
+                old_end_byte: change.old_end_byte,
+                new_end_byte: change.new_end_byte,
+                start_position: point(change.start_position),
+                old_end_position: point(change.old_end_position),
+                new_end_position: point(change.new_end_position),
+            });
+        }
+        self.needs_parse = true;
+    }
+
+    /// Reanalisa `content` se houve edições. Retorna `false` se `cancelled` interrompeu
+    /// a análise; a árvore anterior (já ajustada) fica para a próxima tentativa.
+    pub fn parse(&mut self, content: &Rope, cancelled: impl Fn() -> bool) -> bool {
+        if !self.needs_parse {
+            return true;
+        }
+        let mut read = |byte: usize, _: Point| -> &[u8] {
+            if byte >= content.len_bytes() {
+                return &[];
+            }
+            let (chunk, chunk_start, _, _) = content.chunk_at_byte(byte);
+            &chunk.as_bytes()[byte - chunk_start..]
+        };
+        let mut progress = |_: &tree_sitter::ParseState| cancelled();
+        let options = ParseOptions::new().progress_callback(&mut progress);
+
+        // Uma análise interrompida não deve ser retomada: o texto pode ter mudado
+        self.parser.reset();
+        match self.parser.parse_with_options(&mut read, self.tree.as_ref(), Some(options)) {
+            Some(tree) => {
+                self.tree = Some(tree);
+                self.needs_parse = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Estilo de cada captura da consulta no `theme`.
+    pub fn capture_styles(&self, theme: &Theme) -> Vec<Style> {
+        let highlighter = Highlighter::new(theme);
+        self.query.capture_names().iter()
+            .map(|name| {
+                let scope = Scope::from_str(scope_for_capture(name)).unwrap_or_default();
+                highlighter.style_for_stack(&[scope])
+            })
+            .collect()
+    }
+
+    /// Realça as linhas `lines` a partir da árvore atual.
+    ///
+    /// Capturas internas pintam por cima das externas; para o mesmo nó vale o primeiro
+    /// padrão da consulta, como no `tree-sitter highlight`.
+    pub fn highlight_lines(&self, content: &Rope, lines: Range<usize>, styles: &[Style], default_style: Style) -> Vec<(usize, LineSpans)> {
+        let end_line = lines.end.min(content.len_lines());
+        let start_line = lines.start.min(end_line);
+        let start_byte = content.line_to_byte(start_line);
+        let end_byte = content.line_to_byte(end_line);
+
+        // Captura (índice no estilo) de cada byte do trecho
+        let mut painted: Vec<Option<u32>> = vec![None; end_byte - start_byte];
+        if let Some(tree) = &self.tree {
+            let text = |node: Node| content.byte_slice(node.byte_range()).chunks().map(str::as_bytes);
+            let mut cursor = QueryCursor::new();
+            cursor.set_byte_range(start_byte..end_byte);
+
+            let mut captures = Vec::new();
+            let mut matches = cursor.captures(&self.query, tree.root_node(), text);
+            while let Some((query_match, capture_idx)) = matches.next() {
+                let capture = query_match.captures[*capture_idx];
+                captures.push((capture.node.byte_range(), query_match.pattern_index, capture.index));
+            }
+            captures.sort_by(|(a, a_pattern, _), (b, b_pattern, _)| {
+                a.start.cmp(&b.start)
+                    .then(b.end.cmp(&a.end))
+                    .then(b_pattern.cmp(a_pattern))
+            });
+            for (range, _, capture_index) in captures {
+                let from = range.start.clamp(start_byte, end_byte) - start_byte;
+                let to = range.end.clamp(start_byte, end_byte) - start_byte;
+                painted[from..to].fill(Some(capture_index));
+            }
+        }
+
+        (start_line..end_line)
+            .map(|line_idx| {
+                let line_start = content.line_to_byte(line_idx) - start_byte;
+                let line_end = content.line_to_byte(line_idx + 1) - start_byte;
+                let mut spans: LineSpans = Vec::new();
+                for (offset, capture) in painted[line_start..line_end].iter().enumerate() {
+                    let style = capture
+                        .and_then(|idx| styles.get(idx as usize).copied())
+                        .unwrap_or(default_style);
+                    match spans.last_mut() {
+                        Some((last_style, range)) if *last_style == style => range.end = offset + 1,
+                        _ => spans.push((style, offset..offset + 1)),
+                    }
+                }
+                (line_idx, spans)
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for TreeSitterDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TreeSitterDocument")
+            .field("language", &self.language)
+            .field("needs_parse", &self.needs_parse)
+            .finish()
+    }
+}
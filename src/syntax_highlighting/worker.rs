@@ -0,0 +1,286 @@
+// src/syntax_highlighting/worker.rs
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+
+use ropey::Rope;
+use syntect::highlighting::Highlighter;
+
+use crate::core::editor::TextChange;
+use crate::syntax_highlighting::incremental::{splice_line_cache, EngineKey, HighlightCache, HighlightEngine, LineSpans};
+use crate::syntax_highlighting::treesitter::{TreeSitterDocument, TreeSitterLanguage};
+
+/// Linhas realçadas entre verificações de cancelamento.
+const CHUNK_LINES: usize = 256;
+/// Quantas linhas além da parte visível são realçadas antecipadamente.
+const PREFETCH_LINES: usize = 10_000;
+
+enum WorkerMessage {
+    Edit(TextChange),
+    Highlight(HighlightRequest),
+}
+
+struct HighlightRequest {
+    version: u64,
+    engine: HighlightEngine,
+    content: Rope, // Cópia barata (compartilhada) do texto nesta versão
+    visible: Range<usize>,
+}
+
+struct HighlightResult {
+    version: u64,
+    lines: Vec<(usize, LineSpans)>,
+}
+
+/// Canais de uma thread de realce já iniciada.
+#[derive(Debug)]
+struct WorkerHandle {
+    tx: mpsc::Sender<WorkerMessage>,
+    rx: mpsc::Receiver<HighlightResult>,
+    sent: Arc<AtomicU64>, // Mensagens enviadas; a thread cancela o trabalho atual quando há novas
+}
+
+/// Realce de um documento em uma thread própria.
+///
+/// A UI pede o realce das linhas visíveis e desenha texto sem cor enquanto o
+/// resultado não chega. A thread realça primeiro até o fim da parte visível, publica,
+/// e depois adianta as linhas seguintes; qualquer edição ou nova rolagem interrompe o
+/// trabalho em andamento.
+#[derive(Debug, Default)]
+pub struct BackgroundHighlighter {
+    worker: Option<WorkerHandle>,
+    version: u64, // Incrementada a cada edição; resultados de versões antigas são descartados
+    spans: Vec<Option<LineSpans>>, // Último realce recebido de cada linha
+    last_request: Option<(u64, Range<usize>, EngineKey)>,
+}
+
+impl std::fmt::Debug for WorkerMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkerMessage::Edit(change) => write!(f, "Edit({}, {})", change.first_line(), change.total_lines),
+            WorkerMessage::Highlight(request) => write!(f, "Highlight(v{}, {:?})", request.version, request.visible),
+        }
+    }
+}
+
+impl std::fmt::Debug for HighlightResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HighlightResult(v{}, {} linhas)", self.version, self.lines.len())
+    }
+}
+
+impl BackgroundHighlighter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra uma edição. O realce antigo da linha editada é mantido até o novo
+    /// chegar, para evitar que ela pisque sem cor.
+    pub fn edit(&mut self, change: &TextChange) {
+        self.version += 1;
+        splice_line_cache(&mut self.spans, change.first_line(), change.total_lines);
+        self.send(WorkerMessage::Edit(*change));
+    }
+
+    /// Pede o realce das linhas `visible`, se o pedido mudou desde o último frame.
+    pub fn request(&mut self, ctx: &egui::Context, engine: HighlightEngine, content: &Rope, visible: Range<usize>) {
+        let key = (self.version, visible.clone(), engine.key());
+        if self.last_request.as_ref() == Some(&key) {
+            return;
+        }
+        self.last_request = Some(key);
+        if self.worker.is_none() {
+            self.worker = Some(spawn_worker(ctx.clone()));
+        }
+        self.send(WorkerMessage::Highlight(HighlightRequest {
+            version: self.version,
+            engine,
+            content: content.clone(),
+            visible,
+        }));
+    }
+
+    /// Aplica os resultados recebidos. Retorna as linhas cujo realce mudou, para que a
+    /// UI descarte os galleys delas.
+    pub fn receive(&mut self, total_lines: usize) -> Vec<usize> {
+        let Some(worker) = &self.worker else {
+            return Vec::new();
+        };
+        self.spans.resize_with(total_lines, || None);
+
+        let mut changed = Vec::new();
+        for result in worker.rx.try_iter() {
+            if result.version != self.version {
+                continue;
+            }
+            for (line_idx, spans) in result.lines {
+                let Some(slot) = self.spans.get_mut(line_idx) else {
+                    continue;
+                };
+                if slot.as_ref() != Some(&spans) {
+                    *slot = Some(spans);
+                    changed.push(line_idx);
+                }
+            }
+        }
+        changed
+    }
+
+    /// Realce mais recente de uma linha. Depois de uma edição pode não corresponder
+    /// exatamente ao texto atual da linha.
+    pub fn spans(&self, line_idx: usize) -> Option<&LineSpans> {
+        self.spans.get(line_idx)?.as_ref()
+    }
+
+    fn send(&self, message: WorkerMessage) {
+        if let Some(worker) = &self.worker {
+            worker.sent.fetch_add(1, Ordering::SeqCst);
+            let _ = worker.tx.send(message);
+        }
+    }
+}
+
+fn spawn_worker(ctx: egui::Context) -> WorkerHandle {
+    let (tx, worker_rx) = mpsc::channel();
+    let (worker_tx, rx) = mpsc::channel();
+    let sent = Arc::new(AtomicU64::new(0));
+    let worker_sent = Arc::clone(&sent);
+
+    std::thread::Builder::new()
+        .name("lcode-highlight".to_string())
+        .spawn(move || run_worker(worker_rx, worker_tx, worker_sent, ctx))
+        .expect("Falha ao iniciar a thread de realce");
+
+    WorkerHandle { tx, rx, sent }
+}
+
+/// Laço da thread: aplica todas as mensagens pendentes e só então processa o pedido
+/// de realce mais recente. Termina quando o documento é fechado (canal desconectado).
+fn run_worker(rx: mpsc::Receiver<WorkerMessage>, tx: mpsc::Sender<HighlightResult>, sent: Arc<AtomicU64>, ctx: egui::Context) {
+    let mut cache = HighlightCache::new();
+    let mut tree_sitter = TreeSitterState::default();
+    let mut pending: Option<HighlightRequest> = None;
+    let mut received: u64 = 0;
+
+    loop {
+        let message = if pending.is_some() {
+            match rx.try_recv() {
+                Ok(message) => Some(message),
+                Err(mpsc::TryRecvError::Empty) => None,
+                Err(mpsc::TryRecvError::Disconnected) => return,
+            }
+        } else {
+            match rx.recv() {
+                Ok(message) => Some(message),
+                Err(_) => return,
+            }
+        };
+
+        if let Some(message) = message {
+            received += 1;
+            match message {
+                WorkerMessage::Edit(change) => {
+                    cache.edit(&change);
+                    tree_sitter.edit(&change);
+                }
+                WorkerMessage::Highlight(request) => pending = Some(request),
+            }
+            continue;
+        }
+
+        if let Some(request) = pending.take() {
+            let cancelled = || sent.load(Ordering::SeqCst) > received;
+            let publish = |lines: Vec<(usize, LineSpans)>| {
+                if !lines.is_empty() && tx.send(HighlightResult { version: request.version, lines }).is_ok() {
+                    ctx.request_repaint();
+                }
+            };
+            if let Some(document) = tree_sitter.document_for(request.engine.tree_sitter) {
+                process_tree_sitter_request(document, &request, cancelled, publish);
+            } else {
+                process_request(&mut cache, &request, cancelled, publish);
+            }
+        }
+    }
+}
+
+fn process_request(
+    cache: &mut HighlightCache,
+    request: &HighlightRequest,
+    cancelled: impl Fn() -> bool,
+    publish: impl Fn(Vec<(usize, LineSpans)>),
+) {
+    let total_lines = request.content.len_lines();
+    let visible_end = request.visible.end.min(total_lines);
+
+    // 1. Até o fim da parte visível: é o que o usuário está esperando.
+    while cache.valid_up_to() < visible_end {
+        if cancelled() {
+            return;
+        }
+        let end = (cache.valid_up_to() + CHUNK_LINES).min(visible_end);
+        request.engine.update_cache(cache, &request.content, end);
+    }
+    // As linhas visíveis vão sempre, mesmo as que não mudaram: a UI pode ter descartado
+    // um resultado anterior por ser de uma versão antiga.
+    publish(request.engine.highlight_range(cache, &request.content, request.visible.start..visible_end));
+
+    // 2. Adianta as linhas seguintes, em blocos, enquanto nada novo chegar.
+    let prefetch_end = (visible_end + PREFETCH_LINES).min(total_lines);
+    while cache.valid_up_to() < prefetch_end && !cancelled() {
+        let end = (cache.valid_up_to() + CHUNK_LINES).min(prefetch_end);
+        publish(request.engine.update_cache(cache, &request.content, end));
+    }
+}
+
+/// Documento tree-sitter da thread, criado quando o backend é escolhido. Edições
+/// recebidas antes disso não importam: a primeira análise é completa.
+#[derive(Default)]
+struct TreeSitterState {
+    document: Option<TreeSitterDocument>,
+    failed: Option<TreeSitterLanguage>, // Linguagem cuja gramática não carregou; fica no syntect
+}
+
+impl TreeSitterState {
+    fn edit(&mut self, change: &TextChange) {
+        if let Some(document) = &mut self.document {
+            document.edit(change);
+        }
+    }
+
+    /// Documento para a linguagem pedida, ou `None` para usar o syntect.
+    fn document_for(&mut self, language: Option<TreeSitterLanguage>) -> Option<&mut TreeSitterDocument> {
+        let Some(language) = language else {
+            self.document = None;
+            return None;
+        };
+        if self.document.as_ref().map(|d| d.language()) != Some(language) && self.failed != Some(language) {
+            match TreeSitterDocument::new(language) {
+                Ok(document) => self.document = Some(document),
+                Err(e) => {
+                    eprintln!("{}. Usando o syntect.", e);
+                    self.document = None;
+                    self.failed = Some(language);
+                }
+            }
+        }
+        self.document.as_mut()
+    }
+}
+
+/// Com o tree-sitter não há estado entre linhas: depois da reanálise (incremental),
+/// as linhas visíveis são realçadas direto da árvore.
+fn process_tree_sitter_request(
+    document: &mut TreeSitterDocument,
+    request: &HighlightRequest,
+    cancelled: impl Fn() -> bool,
+    publish: impl Fn(Vec<(usize, LineSpans)>),
+) {
+    if !document.parse(&request.content, &cancelled) {
+        return;
+    }
+    let styles = document.capture_styles(&request.engine.theme);
+    let default_style = Highlighter::new(&request.engine.theme).get_default();
+    publish(document.highlight_lines(&request.content, request.visible.clone(), &styles, default_style));
+}
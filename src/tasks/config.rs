@@ -0,0 +1,130 @@
+// src/tasks/config.rs
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::tasks::problems::{PatternStep, ProblemMatcher, Severity};
+
+/// Caminho do arquivo de tarefas, relativo à raiz do projeto.
+pub const TASKS_FILE: &str = ".lcode/tasks.toml";
+
+/// Conteúdo de `.lcode/tasks.toml`.
+///
+/// ```toml
+/// [[task]]
+/// name = "build"
+/// command = "cargo build"
+/// problem_matcher = "rustc"
+///
+/// [[matcher]]
+/// name = "eslint"
+/// [[matcher.pattern]]
+/// regex = '^(.+): line (\d+), col (\d+), (Error|Warning) - (.+)$'
+/// file = 1
+/// line = 2
+/// column = 3
+/// severity = 4
+/// message = 5
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TasksFile {
+    #[serde(default, rename = "task")]
+    pub tasks: Vec<TaskDefinition>,
+    #[serde(default, rename = "matcher")]
+    pub matchers: Vec<MatcherDefinition>,
+}
+
+/// Comando nomeado executável a partir da UI.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TaskDefinition {
+    pub name: String,
+    pub command: String,
+    pub cwd: Option<PathBuf>, // Relativo à raiz do projeto
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub problem_matcher: Vec<String>, // Nomes de matchers embutidos ou definidos no arquivo
+}
+
+/// Problem matcher definido pelo usuário.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MatcherDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub severity: Option<String>, // Severidade quando o padrão não captura uma
+    pub pattern: Vec<PatternDefinition>,
+}
+
+/// Uma linha de um matcher; padrões consecutivos casam linhas consecutivas da saída.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PatternDefinition {
+    pub regex: String,
+    pub file: Option<usize>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub severity: Option<usize>,
+    pub message: Option<usize>,
+}
+
+impl TasksFile {
+    /// Lê o arquivo de tarefas do projeto. A ausência do arquivo resulta em uma lista vazia.
+    pub fn load(project_dir: &Path) -> io::Result<Self> {
+        let path = project_dir.join(TASKS_FILE);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        toml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))
+    }
+
+    /// Resolve os matchers de uma tarefa, procurando primeiro no arquivo e depois nos embutidos.
+    pub fn matchers_for(&self, task: &TaskDefinition) -> Result<Vec<ProblemMatcher>, String> {
+        task.problem_matcher
+            .iter()
+            .map(|name| match self.matchers.iter().find(|m| &m.name == name) {
+                Some(definition) => definition.compile(),
+                None => ProblemMatcher::builtin(name).ok_or_else(|| format!("Problem matcher desconhecido: '{}'", name)),
+            })
+            .collect()
+    }
+}
+
+impl MatcherDefinition {
+    fn compile(&self) -> Result<ProblemMatcher, String> {
+        let default_severity = self.severity.as_deref().and_then(Severity::parse).unwrap_or(Severity::Error);
+        let steps = self.pattern
+            .iter()
+            .map(|p| {
+                let regex = regex::Regex::new(&p.regex)
+                    .map_err(|e| format!("Regex inválida no matcher '{}': {}", self.name, e))?;
+                Ok(PatternStep {
+                    regex,
+                    file: p.file,
+                    line: p.line,
+                    column: p.column,
+                    severity: p.severity,
+                    message: p.message,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if steps.is_empty() {
+            return Err(format!("O matcher '{}' não tem padrões", self.name));
+        }
+        Ok(ProblemMatcher { name: self.name.clone(), default_severity, steps })
+    }
+}
+
+/// Aceita tanto `problem_matcher = "rustc"` quanto `problem_matcher = ["rustc", "gcc"]`.
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(name) => vec![name],
+        OneOrMany::Many(names) => names,
+    })
+}
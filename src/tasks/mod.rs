@@ -0,0 +1,5 @@
+// src/tasks/mod.rs
+
+pub mod config; // Leitura de .lcode/tasks.toml
+pub mod problems; // Problem matchers e diagnósticos
+pub mod runner; // Execução das tarefas no terminal integrado
@@ -0,0 +1,309 @@
+// src/tasks/problems.rs
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Sequências de escape ANSI (cores, movimento do cursor, OSC), removidas antes do casamento.
+static ANSI_ESCAPE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[()][A-Za-z0-9]").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    /// Interpreta o texto capturado pelo matcher ("error", "Warning", "note"...).
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "error" | "fatal error" | "err" | "e" => Some(Severity::Error),
+            "warning" | "warn" | "w" => Some(Severity::Warning),
+            "info" | "note" | "help" | "hint" | "i" => Some(Severity::Info),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Error => "erro",
+            Severity::Warning => "aviso",
+            Severity::Info => "info",
+        }
+    }
+
+    pub fn color(&self) -> egui::Color32 {
+        match self {
+            Severity::Error => egui::Color32::from_rgb(0xf1, 0x4c, 0x4c),
+            Severity::Warning => egui::Color32::from_rgb(0xcc, 0xa7, 0x00),
+            Severity::Info => egui::Color32::from_rgb(0x37, 0x94, 0xff),
+        }
+    }
+}
+
+/// Problema reportado pela saída de uma tarefa. Linha e coluna começam em 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+    pub source: String, // Nome da tarefa que gerou o diagnóstico
+}
+
+/// Uma linha de um matcher. Cada campo é o índice do grupo de captura correspondente.
+#[derive(Debug, Clone)]
+pub struct PatternStep {
+    pub regex: Regex,
+    pub file: Option<usize>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub severity: Option<usize>,
+    pub message: Option<usize>,
+}
+
+/// Extrai diagnósticos de linhas de saída com uma sequência de padrões.
+///
+/// Com mais de um padrão, o primeiro casa a linha inicial e os seguintes precisam
+/// casar as linhas imediatamente posteriores (como o `error:` / `-->` do rustc).
+#[derive(Debug, Clone)]
+pub struct ProblemMatcher {
+    pub name: String,
+    pub default_severity: Severity,
+    pub steps: Vec<PatternStep>,
+}
+
+impl ProblemMatcher {
+    /// Matchers embutidos: `rustc` (também cobre o cargo) e `gcc` (gcc, clang, tsc --pretty false...).
+    pub fn builtin(name: &str) -> Option<Self> {
+        let step = |regex: &str, file, line, column, severity, message| PatternStep {
+            regex: Regex::new(regex).unwrap(),
+            file,
+            line,
+            column,
+            severity,
+            message,
+        };
+        match name {
+            "rustc" => Some(Self {
+                name: name.to_string(),
+                default_severity: Severity::Error,
+                steps: vec![
+                    step(r"^(error|warning|note|help)(?:\[\w+\])?: (.+)$", None, None, None, Some(1), Some(2)),
+                    step(r"^\s*--> (.+?):(\d+):(\d+)$", Some(1), Some(2), Some(3), None, None),
+                ],
+            }),
+            "gcc" => Some(Self {
+                name: name.to_string(),
+                default_severity: Severity::Error,
+                steps: vec![step(
+                    r"^(.+?):(\d+):(?:(\d+):)?\s+(?:fatal )?(error|warning|note):\s+(.+)$",
+                    Some(1), Some(2), Some(3), Some(4), Some(5),
+                )],
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Campos acumulados enquanto os padrões de um matcher casam linhas consecutivas.
+#[derive(Debug, Default)]
+struct PartialMatch {
+    file: Option<String>,
+    line: Option<usize>,
+    column: Option<usize>,
+    severity: Option<Severity>,
+    message: Option<String>,
+}
+
+/// Alimenta os matchers de uma tarefa com a saída bruta do PTY, linha a linha.
+pub struct OutputParser {
+    matchers: Vec<ProblemMatcher>,
+    progress: Vec<(usize, PartialMatch)>, // Próximo padrão esperado de cada matcher
+    pending: Vec<u8>, // Bytes da linha ainda incompleta
+    base_dir: PathBuf, // Diretório usado para resolver caminhos relativos
+    source: String,
+}
+
+impl OutputParser {
+    pub fn new(matchers: Vec<ProblemMatcher>, base_dir: PathBuf, source: String) -> Self {
+        let progress = matchers.iter().map(|_| (0, PartialMatch::default())).collect();
+        Self { matchers, progress, pending: Vec::new(), base_dir, source }
+    }
+
+    /// Processa um trecho de saída e retorna os diagnósticos completados por ele.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Diagnostic> {
+        self.pending.extend_from_slice(bytes);
+        let mut diagnostics = Vec::new();
+        while let Some(newline) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=newline).collect();
+            self.process_line(&String::from_utf8_lossy(&line), &mut diagnostics);
+        }
+        diagnostics
+    }
+
+    /// Processa a última linha (sem quebra de linha final), ao término da tarefa.
+    pub fn finish(&mut self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        if !self.pending.is_empty() {
+            let line = std::mem::take(&mut self.pending);
+            self.process_line(&String::from_utf8_lossy(&line), &mut diagnostics);
+        }
+        diagnostics
+    }
+
+    fn process_line(&mut self, raw: &str, diagnostics: &mut Vec<Diagnostic>) {
+        let line = ANSI_ESCAPE.replace_all(raw, "");
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        for (matcher, (step_idx, partial)) in self.matchers.iter().zip(self.progress.iter_mut()) {
+            // Uma linha que não continua a sequência pode ser o início de outra.
+            let mut caps = matcher.steps[*step_idx].regex.captures(line);
+            if caps.is_none() && *step_idx > 0 {
+                *step_idx = 0;
+                *partial = PartialMatch::default();
+                caps = matcher.steps[0].regex.captures(line);
+            }
+            let Some(caps) = caps else {
+                continue;
+            };
+
+            let step = &matcher.steps[*step_idx];
+            let group = |idx: Option<usize>| idx.and_then(|i| caps.get(i)).map(|m| m.as_str().to_string());
+            let number = |idx: Option<usize>| group(idx).and_then(|s| s.parse().ok());
+            if let Some(file) = group(step.file) {
+                partial.file = Some(file);
+            }
+            if let Some(line) = number(step.line) {
+                partial.line = Some(line);
+            }
+            if let Some(column) = number(step.column) {
+                partial.column = Some(column);
+            }
+            if let Some(severity) = group(step.severity).as_deref().and_then(Severity::parse) {
+                partial.severity = Some(severity);
+            }
+            if let Some(message) = group(step.message) {
+                partial.message = Some(message);
+            }
+
+            *step_idx += 1;
+            if *step_idx < matcher.steps.len() {
+                continue;
+            }
+            let done = std::mem::take(partial);
+            *step_idx = 0;
+            if let (Some(file), Some(line)) = (done.file, done.line) {
+                diagnostics.push(Diagnostic {
+                    path: resolve(&self.base_dir, &file),
+                    line,
+                    column: done.column,
+                    severity: done.severity.unwrap_or(matcher.default_severity),
+                    message: done.message.unwrap_or_default(),
+                    source: self.source.clone(),
+                });
+            }
+        }
+    }
+}
+
+fn resolve(base_dir: &Path, file: &str) -> PathBuf {
+    let path = Path::new(file.trim());
+    if path.is_absolute() { path.to_path_buf() } else { base_dir.join(path) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUSTC_OUTPUT: &str = "   Compiling demo v0.1.0 (/proj)\r
+\x1b[0m\x1b[1m\x1b[33mwarning\x1b[0m\x1b[0m\x1b[1m: unused variable: `x`\x1b[0m\r
+\x1b[0m \x1b[0m\x1b[0m\x1b[1m\x1b[38;5;12m--> \x1b[0m\x1b[0msrc/main.rs:2:9\x1b[0m\r
+  |\r
+2 |     let x = 5;\r
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`\r
+  |\r
+  = note: `#[warn(unused_variables)]` on by default\r
+\r
+\x1b[0m\x1b[1m\x1b[38;5;9merror[E0308]\x1b[0m\x1b[0m\x1b[1m: mismatched types\x1b[0m\r
+\x1b[0m \x1b[0m\x1b[0m\x1b[1m\x1b[38;5;12m--> \x1b[0m\x1b[0msrc/main.rs:3:18\x1b[0m\r
+  |\r
+3 |     let y: u32 = \"é\";\r
+  |            ---   ^^^ expected `u32`, found `&str`\r
+\r
+\x1b[0m\x1b[1m\x1b[38;5;9merror\x1b[0m\x1b[0m\x1b[1m: aborting due to 1 previous error; 1 warning emitted\x1b[0m\r
+";
+
+    const GCC_OUTPUT: &str = "main.c: In function \u{2018}main\u{2019}:
+\x1b[01m\x1b[Kmain.c:3:5:\x1b[m\x1b[K \x1b[01;31m\x1b[Kerror: \x1b[m\x1b[K\u{2018}x\u{2019} undeclared (first use in this function)
+    3 |     x = 1;
+      |     ^
+\x1b[01m\x1b[Kmain.c:3:5:\x1b[m\x1b[K \x1b[01;36m\x1b[Knote: \x1b[m\x1b[Keach undeclared identifier is reported only once
+/usr/include/stdio.h:12: warning: old header
+lib/util.c:7:1: fatal error: util.h: No such file or directory";
+
+    fn diagnostic(path: &str, line: usize, column: Option<usize>, severity: Severity, message: &str, source: &str) -> Diagnostic {
+        Diagnostic {
+            path: PathBuf::from(path),
+            line,
+            column,
+            severity,
+            message: message.to_string(),
+            source: source.to_string(),
+        }
+    }
+
+    /// Diagnósticos da saída entregue em pedaços de `chunk_size` bytes, como chega do PTY.
+    fn parse(matcher: &str, output: &str, chunk_size: usize) -> Vec<Diagnostic> {
+        let matcher = ProblemMatcher::builtin(matcher).unwrap();
+        let mut parser = OutputParser::new(vec![matcher], PathBuf::from("/proj"), "build".to_string());
+        let mut diagnostics = Vec::new();
+        for chunk in output.as_bytes().chunks(chunk_size) {
+            diagnostics.extend(parser.feed(chunk));
+        }
+        diagnostics.extend(parser.finish());
+        diagnostics
+    }
+
+    #[test]
+    fn rustc_matches_message_then_location() {
+        let expected = vec![
+            diagnostic("/proj/src/main.rs", 2, Some(9), Severity::Warning, "unused variable: `x`", "build"),
+            diagnostic("/proj/src/main.rs", 3, Some(18), Severity::Error, "mismatched types", "build"),
+        ];
+        for chunk_size in [1, 2, 3, 7, 64, RUSTC_OUTPUT.len()] {
+            assert_eq!(parse("rustc", RUSTC_OUTPUT, chunk_size), expected, "pedaços de {} bytes", chunk_size);
+        }
+    }
+
+    #[test]
+    fn rustc_restarts_when_the_sequence_breaks() {
+        let output = "error: first\nwarning: second\n  --> src/lib.rs:7:1\nerror: third\n\n  --> src/lib.rs:9:1\n";
+        assert_eq!(parse("rustc", output, 5), vec![
+            diagnostic("/proj/src/lib.rs", 7, Some(1), Severity::Warning, "second", "build"),
+        ]);
+    }
+
+    #[test]
+    fn gcc_matches_single_lines() {
+        let expected = vec![
+            diagnostic("/proj/main.c", 3, Some(5), Severity::Error, "\u{2018}x\u{2019} undeclared (first use in this function)", "build"),
+            diagnostic("/proj/main.c", 3, Some(5), Severity::Info, "each undeclared identifier is reported only once", "build"),
+            diagnostic("/usr/include/stdio.h", 12, None, Severity::Warning, "old header", "build"),
+            diagnostic("/proj/lib/util.c", 7, Some(1), Severity::Error, "util.h: No such file or directory", "build"),
+        ];
+        for chunk_size in [1, 4, 13, GCC_OUTPUT.len()] {
+            assert_eq!(parse("gcc", GCC_OUTPUT, chunk_size), expected, "pedaços de {} bytes", chunk_size);
+        }
+    }
+
+    #[test]
+    fn strips_ansi_escapes() {
+        let line = "\x1b]0;title\x07\x1b(B\x1b[1;31merror\x1b[0m: x\x1b[K";
+        assert_eq!(ANSI_ESCAPE.replace_all(line, ""), "error: x");
+    }
+}
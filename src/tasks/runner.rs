@@ -0,0 +1,95 @@
+// src/tasks/runner.rs
+
+use std::path::{Path, PathBuf};
+
+use crate::tasks::config::{TaskDefinition, TasksFile};
+use crate::tasks::problems::{Diagnostic, OutputParser};
+use crate::terminal::manager::TerminalManager;
+use crate::terminal::pty_integration::SessionStatus;
+
+/// Tarefa em execução em uma sessão do terminal integrado.
+struct RunningTask {
+    name: String,
+    session_id: usize,
+    parser: OutputParser,
+}
+
+/// Executa as tarefas do projeto no terminal integrado e coleta os diagnósticos
+/// encontrados pelos problem matchers.
+#[derive(Default)]
+pub struct TaskRunner {
+    pub tasks_file: TasksFile,
+    pub error: Option<String>, // Erro ao ler o arquivo de tarefas ou ao iniciar uma tarefa
+    pub diagnostics: Vec<Diagnostic>,
+    running: Vec<RunningTask>,
+}
+
+impl TaskRunner {
+    /// Relê `.lcode/tasks.toml` do projeto aberto.
+    pub fn reload(&mut self, project_dir: Option<&Path>) {
+        self.error = None;
+        self.tasks_file = match project_dir.map(TasksFile::load) {
+            Some(Ok(tasks_file)) => tasks_file,
+            Some(Err(e)) => {
+                self.error = Some(e.to_string());
+                TasksFile::default()
+            },
+            None => TasksFile::default(),
+        };
+    }
+
+    pub fn is_running(&self, name: &str) -> bool {
+        self.running.iter().any(|task| task.name == name)
+    }
+
+    /// Inicia a tarefa em uma nova aba do terminal, descartando os diagnósticos da
+    /// execução anterior dela.
+    pub fn run(&mut self, task: &TaskDefinition, ctx: &egui::Context, terminals: &mut TerminalManager, project_dir: Option<&Path>) {
+        let matchers = match self.tasks_file.matchers_for(task) {
+            Ok(matchers) => matchers,
+            Err(e) => {
+                self.error = Some(e);
+                return;
+            },
+        };
+        self.error = None;
+        self.diagnostics.retain(|d| d.source != task.name);
+
+        let cwd: Option<PathBuf> = match (project_dir, &task.cwd) {
+            (Some(project), Some(cwd)) => Some(project.join(cwd)),
+            (Some(project), None) => Some(project.to_path_buf()),
+            (None, cwd) => cwd.clone(),
+        };
+        let base_dir = cwd.clone().or_else(|| std::env::current_dir().ok()).unwrap_or_default();
+        let session_id = terminals.run_task(ctx, &task.name, &task.command, cwd);
+        self.running.push(RunningTask {
+            name: task.name.clone(),
+            session_id,
+            parser: OutputParser::new(matchers, base_dir, task.name.clone()),
+        });
+    }
+
+    /// Passa a saída nova das tarefas pelos matchers. Deve ser chamado a cada frame,
+    /// depois de `TerminalManager::process_output`.
+    pub fn poll(&mut self, terminals: &mut TerminalManager) {
+        let diagnostics = &mut self.diagnostics;
+        self.running.retain_mut(|task| {
+            let Some(terminal) = terminals.session_by_id_mut(task.session_id) else {
+                // A aba foi fechada antes do fim da tarefa
+                diagnostics.extend(task.parser.finish());
+                return false;
+            };
+            diagnostics.extend(task.parser.feed(&terminal.take_captured_output()));
+            if matches!(terminal.status, SessionStatus::Exited(_)) {
+                diagnostics.extend(task.parser.finish());
+                return false;
+            }
+            true
+        });
+    }
+
+    /// Diagnósticos de um arquivo, para o editor.
+    pub fn diagnostics_for<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = &'a Diagnostic> + 'a {
+        self.diagnostics.iter().filter(move |d| d.path == path)
+    }
+}
@@ -0,0 +1,196 @@
+// src/terminal/emulator.rs
+
+use std::sync::mpsc;
+
+use alacritty_terminal::event::{Event, EventListener};
+use alacritty_terminal::grid::{Dimensions, Scroll};
+use alacritty_terminal::term::{Config, Term};
+use alacritty_terminal::vte::ansi::{Color, NamedColor, Processor, Rgb};
+
+/// Quantidade padrão de linhas mantidas no histórico de rolagem de cada sessão.
+pub const DEFAULT_SCROLLBACK_LINES: usize = 10_000;
+
+/// Dimensões da grade do terminal (colunas x linhas visíveis).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalSize {
+    pub cols: usize,
+    pub rows: usize,
+}
+
+impl Dimensions for TerminalSize {
+    fn total_lines(&self) -> usize {
+        self.rows
+    }
+
+    fn screen_lines(&self) -> usize {
+        self.rows
+    }
+
+    fn columns(&self) -> usize {
+        self.cols
+    }
+}
+
+/// Encaminha os eventos gerados pelo `Term` (título, respostas para o PTY, etc.) para a UI.
+#[derive(Clone)]
+pub struct EventProxy {
+    tx: mpsc::Sender<Event>,
+}
+
+impl EventListener for EventProxy {
+    fn send_event(&self, event: Event) {
+        let _ = self.tx.send(event);
+    }
+}
+
+/// Estado de emulação VT100/xterm de uma sessão de terminal.
+///
+/// Os bytes lidos do PTY passam pelo parser `vte` e atualizam a grade do `Term`
+/// do alacritty, que cuida de cores, atributos, cursor e tela alternativa.
+pub struct TerminalEmulator {
+    pub term: Term<EventProxy>,
+    parser: Processor,
+    events_rx: mpsc::Receiver<Event>,
+}
+
+impl TerminalEmulator {
+    /// Cria a grade com `scrollback_lines` linhas de histórico.
+    ///
+    /// O histórico do `Grid` do alacritty é um buffer circular: ao atingir o limite,
+    /// as linhas mais antigas são descartadas, mantendo a memória limitada.
+    pub fn new(size: TerminalSize, scrollback_lines: usize) -> Self {
+        let (tx, events_rx) = mpsc::channel();
+        let config = Config {
+            scrolling_history: scrollback_lines,
+            ..Config::default()
+        };
+        let term = Term::new(config, &size, EventProxy { tx });
+
+        Self {
+            term,
+            parser: Processor::new(),
+            events_rx,
+        }
+    }
+
+    /// Alimenta o parser com bytes vindos do PTY.
+    pub fn process(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.parser.advance(&mut self.term, *byte);
+        }
+    }
+
+    /// Retorna os eventos pendentes gerados pelo `Term` desde a última chamada.
+    pub fn drain_events(&mut self) -> Vec<Event> {
+        self.events_rx.try_iter().collect()
+    }
+
+    /// Altera o limite do histórico de rolagem, descartando linhas excedentes.
+    pub fn set_scrollback_lines(&mut self, scrollback_lines: usize) {
+        let config = Config {
+            scrolling_history: scrollback_lines,
+            ..Config::default()
+        };
+        self.term.set_options(config);
+    }
+
+    /// Rola a visualização pelo histórico (positivo = para cima).
+    pub fn scroll_lines(&mut self, lines: i32) {
+        self.term.scroll_display(Scroll::Delta(lines));
+    }
+
+    /// Volta a visualização para a parte inferior (saída mais recente).
+    pub fn scroll_to_bottom(&mut self) {
+        if self.term.grid().display_offset() != 0 {
+            self.term.scroll_display(Scroll::Bottom);
+        }
+    }
+
+    pub fn size(&self) -> TerminalSize {
+        TerminalSize {
+            cols: self.term.columns(),
+            rows: self.term.screen_lines(),
+        }
+    }
+
+    /// Converte uma cor do terminal para uma cor do Egui, respeitando cores
+    /// redefinidas pelo programa (OSC 4/10/11) antes da paleta padrão.
+    pub fn resolve_color(&self, color: Color) -> egui::Color32 {
+        let rgb = match color {
+            Color::Spec(rgb) => rgb,
+            Color::Named(named) => self.term.colors()[named].unwrap_or_else(|| default_named_color(named)),
+            Color::Indexed(idx) => self.term.colors()[idx as usize].unwrap_or_else(|| default_indexed_color(idx)),
+        };
+        egui::Color32::from_rgb(rgb.r, rgb.g, rgb.b)
+    }
+}
+
+/// Paleta ANSI padrão (cores 0..16).
+const ANSI_COLORS: [Rgb; 16] = [
+    Rgb { r: 0x1d, g: 0x1f, b: 0x21 },
+    Rgb { r: 0xcc, g: 0x66, b: 0x66 },
+    Rgb { r: 0xb5, g: 0xbd, b: 0x68 },
+    Rgb { r: 0xf0, g: 0xc6, b: 0x74 },
+    Rgb { r: 0x81, g: 0xa2, b: 0xbe },
+    Rgb { r: 0xb2, g: 0x94, b: 0xbb },
+    Rgb { r: 0x8a, g: 0xbe, b: 0xb7 },
+    Rgb { r: 0xc5, g: 0xc8, b: 0xc6 },
+    Rgb { r: 0x66, g: 0x66, b: 0x66 },
+    Rgb { r: 0xd5, g: 0x4e, b: 0x53 },
+    Rgb { r: 0xb9, g: 0xca, b: 0x4a },
+    Rgb { r: 0xe7, g: 0xc5, b: 0x47 },
+    Rgb { r: 0x7a, g: 0xa6, b: 0xda },
+    Rgb { r: 0xc3, g: 0x97, b: 0xd8 },
+    Rgb { r: 0x70, g: 0xc0, b: 0xb1 },
+    Rgb { r: 0xea, g: 0xea, b: 0xea },
+];
+
+const DEFAULT_FOREGROUND: Rgb = Rgb { r: 0xd8, g: 0xd8, b: 0xd8 };
+const DEFAULT_BACKGROUND: Rgb = Rgb { r: 0x1b, g: 0x1b, b: 0x1b };
+
+fn dim(rgb: Rgb) -> Rgb {
+    Rgb {
+        r: (rgb.r as u16 * 2 / 3) as u8,
+        g: (rgb.g as u16 * 2 / 3) as u8,
+        b: (rgb.b as u16 * 2 / 3) as u8,
+    }
+}
+
+fn default_named_color(named: NamedColor) -> Rgb {
+    match named {
+        NamedColor::Foreground | NamedColor::BrightForeground | NamedColor::Cursor => DEFAULT_FOREGROUND,
+        NamedColor::Background => DEFAULT_BACKGROUND,
+        NamedColor::DimForeground => dim(DEFAULT_FOREGROUND),
+        NamedColor::DimBlack
+        | NamedColor::DimRed
+        | NamedColor::DimGreen
+        | NamedColor::DimYellow
+        | NamedColor::DimBlue
+        | NamedColor::DimMagenta
+        | NamedColor::DimCyan
+        | NamedColor::DimWhite => dim(ANSI_COLORS[named.to_bright() as usize]),
+        _ => ANSI_COLORS[named as usize],
+    }
+}
+
+/// Cor padrão da paleta de 256 cores do xterm.
+fn default_indexed_color(idx: u8) -> Rgb {
+    match idx {
+        0..=15 => ANSI_COLORS[idx as usize],
+        16..=231 => {
+            // Cubo de cores 6x6x6
+            let idx = idx - 16;
+            let level = |v: u8| if v == 0 { 0 } else { v * 40 + 55 };
+            Rgb {
+                r: level(idx / 36),
+                g: level((idx / 6) % 6),
+                b: level(idx % 6),
+            }
+        },
+        _ => {
+            // Rampa de tons de cinza
+            let value = (idx - 232) * 10 + 8;
+            Rgb { r: value, g: value, b: value }
+        },
+    }
+}
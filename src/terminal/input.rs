@@ -0,0 +1,118 @@
+// src/terminal/input.rs
+
+use alacritty_terminal::term::TermMode;
+use egui::{Key, Modifiers};
+
+/// Parâmetro de modificadores do xterm (`CSI 1 ; <mods> X`), ou `None` sem modificadores.
+fn modifier_param(modifiers: &Modifiers) -> Option<u8> {
+    let value = 1 + modifiers.shift as u8 + 2 * modifiers.alt as u8 + 4 * modifiers.ctrl as u8;
+    (value > 1).then_some(value)
+}
+
+/// Sequência para teclas de cursor (setas, Home, End, F1-F4).
+///
+/// Sem modificadores, respeita o modo de cursor da aplicação (`ESC O x` vs `ESC [ x`).
+fn cursor_key(suffix: char, modifiers: &Modifiers, ss3: bool) -> Vec<u8> {
+    match modifier_param(modifiers) {
+        Some(m) => format!("\x1b[1;{}{}", m, suffix).into_bytes(),
+        None if ss3 => format!("\x1bO{}", suffix).into_bytes(),
+        None => format!("\x1b[{}", suffix).into_bytes(),
+    }
+}
+
+/// Sequência `CSI <n> ~` usada por Insert, Delete, PageUp/Down e F5-F12.
+fn tilde_key(code: u8, modifiers: &Modifiers) -> Vec<u8> {
+    match modifier_param(modifiers) {
+        Some(m) => format!("\x1b[{};{}~", code, m).into_bytes(),
+        None => format!("\x1b[{}~", code).into_bytes(),
+    }
+}
+
+/// Código de controle (Ctrl+tecla) para letras e os símbolos clássicos do ASCII.
+fn control_code(key: Key) -> Option<u8> {
+    let name = key.name();
+    if name.len() == 1 {
+        let ch = name.as_bytes()[0];
+        if ch.is_ascii_alphabetic() {
+            return Some(ch.to_ascii_uppercase() - b'A' + 1);
+        }
+    }
+    match key {
+        Key::Space | Key::Num2 => Some(0x00),
+        Key::OpenBracket | Key::Num3 => Some(0x1b),
+        Key::Backslash | Key::Num4 => Some(0x1c),
+        Key::CloseBracket | Key::Num5 => Some(0x1d),
+        Key::Num6 => Some(0x1e),
+        Key::Minus | Key::Num7 => Some(0x1f),
+        Key::Num8 => Some(0x7f),
+        _ => None,
+    }
+}
+
+/// Traduz um evento de tecla do egui para os bytes que um xterm enviaria ao PTY.
+///
+/// Retorna `None` para teclas que chegam como `egui::Event::Text` (caracteres imprimíveis
+/// sem Ctrl/Alt) ou que não têm representação no terminal.
+pub fn key_to_bytes(key: Key, modifiers: &Modifiers, mode: TermMode) -> Option<Vec<u8>> {
+    let app_cursor = mode.contains(TermMode::APP_CURSOR);
+
+    let bytes = match key {
+        Key::Enter => if modifiers.alt { b"\x1b\r".to_vec() } else { b"\r".to_vec() },
+        Key::Backspace => {
+            let code = if modifiers.ctrl { 0x08 } else { 0x7f };
+            if modifiers.alt { vec![0x1b, code] } else { vec![code] }
+        },
+        Key::Tab => if modifiers.shift { b"\x1b[Z".to_vec() } else { b"\t".to_vec() },
+        Key::Escape => b"\x1b".to_vec(),
+        Key::ArrowUp => cursor_key('A', modifiers, app_cursor),
+        Key::ArrowDown => cursor_key('B', modifiers, app_cursor),
+        Key::ArrowRight => cursor_key('C', modifiers, app_cursor),
+        Key::ArrowLeft => cursor_key('D', modifiers, app_cursor),
+        Key::Home => cursor_key('H', modifiers, app_cursor),
+        Key::End => cursor_key('F', modifiers, app_cursor),
+        Key::Insert => tilde_key(2, modifiers),
+        Key::Delete => tilde_key(3, modifiers),
+        Key::PageUp => tilde_key(5, modifiers),
+        Key::PageDown => tilde_key(6, modifiers),
+        Key::F1 => cursor_key('P', modifiers, true),
+        Key::F2 => cursor_key('Q', modifiers, true),
+        Key::F3 => cursor_key('R', modifiers, true),
+        Key::F4 => cursor_key('S', modifiers, true),
+        Key::F5 => tilde_key(15, modifiers),
+        Key::F6 => tilde_key(17, modifiers),
+        Key::F7 => tilde_key(18, modifiers),
+        Key::F8 => tilde_key(19, modifiers),
+        Key::F9 => tilde_key(20, modifiers),
+        Key::F10 => tilde_key(21, modifiers),
+        Key::F11 => tilde_key(23, modifiers),
+        Key::F12 => tilde_key(24, modifiers),
+        _ if modifiers.ctrl => {
+            let code = control_code(key)?;
+            if modifiers.alt { vec![0x1b, code] } else { vec![code] }
+        },
+        _ if modifiers.alt => {
+            // Alt+tecla envia ESC como prefixo (meta), como no xterm.
+            let name = key.name();
+            if name.len() != 1 {
+                return None;
+            }
+            let ch = name.chars().next()?;
+            let ch = if modifiers.shift { ch.to_ascii_uppercase() } else { ch.to_ascii_lowercase() };
+            format!("\x1b{}", ch).into_bytes()
+        },
+        _ => return None,
+    };
+    Some(bytes)
+}
+
+/// Prepara um texto colado para o PTY, envolvendo-o no modo bracketed paste quando ativo.
+pub fn paste_to_bytes(text: &str, mode: TermMode) -> Vec<u8> {
+    if mode.contains(TermMode::BRACKETED_PASTE) {
+        // Remove marcadores embutidos para que o conteúdo não encerre o paste antes da hora.
+        let sanitized = text.replace("\x1b[201~", "").replace("\x1b[200~", "");
+        format!("\x1b[200~{}\x1b[201~", sanitized).into_bytes()
+    } else {
+        // Sem bracketed paste, quebras de linha viram Enter (`\r`).
+        text.replace("\r\n", "\r").replace('\n', "\r").into_bytes()
+    }
+}
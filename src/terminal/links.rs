@@ -0,0 +1,108 @@
+// src/terminal/links.rs
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Column, Line};
+use alacritty_terminal::term::cell::Flags;
+use alacritty_terminal::term::Term;
+use regex::Regex;
+
+/// URLs com esquema explícito (http, https, file).
+static URL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?:https?|file)://[^\s<>"'`]+"#).unwrap());
+
+/// Caminhos no formato do rustc/cargo e da maioria dos compiladores: `caminho[:linha[:coluna]]`.
+static FILE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?P<path>(?:~|\.{1,2})?/?(?:[\w.\-]+/)*[\w\-]+\.[\w]+)(?::(?P<line>\d+))?(?::(?P<col>\d+))?").unwrap()
+});
+
+/// Posição em um arquivo apontada pela saída do terminal. Linha e coluna começam em 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLocation {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// Destino de um link detectado na saída do terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    File(FileLocation),
+    Url(String),
+}
+
+/// Link encontrado em uma linha visível da grade.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminalLink {
+    pub row: usize,             // Linha da viewport
+    pub columns: Range<usize>,  // Colunas ocupadas pelo link
+    pub target: LinkTarget,
+}
+
+/// Procura um link sob a célula (`row`, `col`) da viewport.
+///
+/// Caminhos relativos são resolvidos contra cada diretório de `base_dirs`, em ordem;
+/// só viram link se o arquivo existir.
+pub fn link_at<T>(term: &Term<T>, row: usize, col: usize, base_dirs: &[PathBuf]) -> Option<TerminalLink> {
+    let (text, byte_cols) = viewport_line_text(term, row)?;
+    let to_columns = |bytes: Range<usize>| byte_cols[bytes.start]..byte_cols[bytes.end - 1] + 1;
+
+    for m in URL_REGEX.find_iter(&text) {
+        let url = m.as_str().trim_end_matches(['.', ',', ';', ':', ')', ']', '}', '\'', '"']);
+        let columns = to_columns(m.start()..m.start() + url.len());
+        if columns.contains(&col) {
+            return Some(TerminalLink { row, columns, target: LinkTarget::Url(url.to_string()) });
+        }
+    }
+
+    for caps in FILE_REGEX.captures_iter(&text) {
+        let whole = caps.get(0)?;
+        let columns = to_columns(whole.range());
+        if !columns.contains(&col) {
+            continue;
+        }
+        let path = resolve_path(&caps["path"], base_dirs)?;
+        let number = |name: &str| caps.name(name).and_then(|n| n.as_str().parse().ok());
+        return Some(TerminalLink {
+            row,
+            columns,
+            target: LinkTarget::File(FileLocation { path, line: number("line"), column: number("col") }),
+        });
+    }
+    None
+}
+
+/// Texto de uma linha da viewport e, para cada byte do texto, a coluna de origem.
+fn viewport_line_text<T>(term: &Term<T>, row: usize) -> Option<(String, Vec<usize>)> {
+    if row >= term.screen_lines() {
+        return None;
+    }
+    let line = Line(row as i32 - term.grid().display_offset() as i32);
+    let grid_row = &term.grid()[line];
+
+    let mut text = String::new();
+    let mut byte_cols = Vec::new();
+    for col in 0..term.columns() {
+        let cell = &grid_row[Column(col)];
+        if cell.flags.intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER) {
+            continue;
+        }
+        text.push(cell.c);
+        byte_cols.resize(text.len(), col);
+    }
+    Some((text, byte_cols))
+}
+
+fn resolve_path(raw: &str, base_dirs: &[PathBuf]) -> Option<PathBuf> {
+    let path = match raw.strip_prefix("~/") {
+        Some(rest) => std::env::var_os("HOME").map(|home| Path::new(&home).join(rest))?,
+        None => PathBuf::from(raw),
+    };
+    if path.is_absolute() {
+        return path.is_file().then_some(path);
+    }
+    base_dirs.iter().map(|dir| dir.join(&path)).find(|candidate| candidate.is_file())
+}
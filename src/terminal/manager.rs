@@ -0,0 +1,209 @@
+// src/terminal/manager.rs
+
+use std::path::PathBuf;
+
+use crate::terminal::emulator::DEFAULT_SCROLLBACK_LINES;
+use crate::terminal::links::FileLocation;
+use crate::terminal::pty_integration::{SessionStatus, Terminal};
+
+/// Gerencia as sessões de terminal abertas, suas abas e a divisão do painel.
+///
+/// Ocultar o painel não encerra nenhuma sessão: os shells continuam rodando e a
+/// saída deles continua sendo processada em segundo plano.
+pub struct TerminalManager {
+    pub sessions: Vec<Terminal>,
+    pub active_idx: Option<usize>,
+    pub split_idx: Option<usize>, // Sessão exibida ao lado da ativa, se o painel estiver dividido
+    pub is_open: bool,
+    pub scrollback_lines: usize, // Limite do histórico de rolagem de cada sessão
+    next_id: usize,
+}
+
+impl Default for TerminalManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TerminalManager {
+    pub fn new() -> Self {
+        Self {
+            sessions: Vec::new(),
+            active_idx: None,
+            split_idx: None,
+            is_open: false,
+            scrollback_lines: DEFAULT_SCROLLBACK_LINES,
+            next_id: 1,
+        }
+    }
+
+    /// Mostra ou oculta o painel, criando a primeira sessão quando necessário (FR.3.2.2).
+    pub fn toggle(&mut self, ctx: &egui::Context, current_dir: Option<PathBuf>) {
+        self.is_open = !self.is_open;
+        if self.is_open && self.sessions.is_empty() {
+            self.new_session(ctx, current_dir);
+        }
+    }
+
+    /// Cria e inicia uma nova sessão, tornando-a a aba ativa.
+    pub fn new_session(&mut self, ctx: &egui::Context, current_dir: Option<PathBuf>) -> usize {
+        let terminal = Terminal::new(self.next_id, self.scrollback_lines);
+        self.add_session(ctx, terminal, current_dir)
+    }
+
+    /// Executa uma tarefa em uma nova aba, abrindo o painel. Retorna o id da sessão.
+    pub fn run_task(&mut self, ctx: &egui::Context, name: &str, command: &str, cwd: Option<PathBuf>) -> usize {
+        let mut terminal = Terminal::new(self.next_id, self.scrollback_lines);
+        terminal.set_task(name, command);
+        let id = terminal.id;
+        self.add_session(ctx, terminal, cwd);
+        self.is_open = true;
+        id
+    }
+
+    fn add_session(&mut self, ctx: &egui::Context, mut terminal: Terminal, current_dir: Option<PathBuf>) -> usize {
+        self.next_id += 1;
+        terminal.start(ctx, current_dir);
+        self.sessions.push(terminal);
+
+        let idx = self.sessions.len() - 1;
+        self.active_idx = Some(idx);
+        idx
+    }
+
+    /// Sessão com o id dado, se ainda estiver aberta.
+    pub fn session_by_id_mut(&mut self, id: usize) -> Option<&mut Terminal> {
+        self.sessions.iter_mut().find(|terminal| terminal.id == id)
+    }
+
+    /// Encerra o shell de uma sessão e remove a sua aba.
+    pub fn close_session(&mut self, idx: usize) {
+        if idx >= self.sessions.len() {
+            return;
+        }
+        let mut terminal = self.sessions.remove(idx);
+        terminal.stop();
+
+        let shift = |slot: Option<usize>| match slot {
+            Some(i) if i == idx => None,
+            Some(i) if i > idx => Some(i - 1),
+            other => other,
+        };
+        self.split_idx = shift(self.split_idx);
+        self.active_idx = shift(self.active_idx).or_else(|| {
+            (!self.sessions.is_empty()).then(|| idx.min(self.sessions.len() - 1))
+        });
+        if self.split_idx == self.active_idx {
+            self.split_idx = None;
+        }
+        if self.sessions.is_empty() {
+            self.is_open = false;
+        }
+    }
+
+    /// Divide o painel, abrindo uma nova sessão ao lado da ativa.
+    pub fn split(&mut self, ctx: &egui::Context, current_dir: Option<PathBuf>) {
+        let previous = self.active_idx;
+        let idx = self.new_session(ctx, current_dir);
+        self.active_idx = previous.or(Some(idx));
+        self.split_idx = Some(idx).filter(|_| previous.is_some());
+    }
+
+    /// Altera o limite do histórico de rolagem de todas as sessões.
+    pub fn set_scrollback_lines(&mut self, scrollback_lines: usize) {
+        self.scrollback_lines = scrollback_lines;
+        for terminal in &mut self.sessions {
+            terminal.emulator.set_scrollback_lines(scrollback_lines);
+        }
+    }
+
+    /// Alguma sessão visível tem o foco do teclado.
+    pub fn has_focus(&self, ctx: &egui::Context) -> bool {
+        self.is_open && ctx.memory(|m| self.sessions.iter().any(|terminal| m.has_focus(terminal.focus_id())))
+    }
+
+    /// Sessão ativa, se houver.
+    pub fn active_session(&self) -> Option<&Terminal> {
+        self.active_idx.and_then(|idx| self.sessions.get(idx))
+    }
+
+    /// Processa a saída pendente de todas as sessões, visíveis ou não.
+    pub fn process_output(&mut self, ctx: &egui::Context) {
+        for terminal in &mut self.sessions {
+            terminal.process_pty_output(ctx);
+        }
+    }
+
+    /// Desenha a barra de abas e a(s) sessão(ões) visível(is).
+    ///
+    /// Retorna o arquivo pedido via Ctrl+clique em algum link da saída, se houver.
+    pub fn ui(&mut self, ui: &mut egui::Ui, current_dir: Option<PathBuf>) -> Option<FileLocation> {
+        let ctx = ui.ctx().clone();
+        let mut session_to_close: Option<usize> = None;
+
+        ui.horizontal(|ui_tabs| {
+            for (i, terminal) in self.sessions.iter().enumerate() {
+                let is_visible = self.active_idx == Some(i) || self.split_idx == Some(i);
+                let label = egui::RichText::new(terminal.display_title());
+                let label = if terminal.status == SessionStatus::Running { label } else { label.weak() };
+                let response = ui_tabs
+                    .selectable_label(is_visible, label)
+                    .on_hover_text(terminal.current_cwd().map(|p| p.display().to_string()).unwrap_or_default());
+                if response.clicked() {
+                    if self.split_idx == Some(i) {
+                        self.split_idx = self.active_idx;
+                    }
+                    self.active_idx = Some(i);
+                }
+                if ui_tabs.add(egui::Button::new("x").small()).clicked() {
+                    session_to_close = Some(i);
+                }
+            }
+
+            ui_tabs.separator();
+            if ui_tabs.button("+").on_hover_text("Novo terminal").clicked() {
+                self.new_session(&ctx, current_dir.clone());
+            }
+            let split_label = if self.split_idx.is_some() { "Juntar" } else { "Dividir" };
+            if ui_tabs.button(split_label).clicked() {
+                if self.split_idx.is_some() {
+                    self.split_idx = None;
+                } else {
+                    self.split(&ctx, current_dir.clone());
+                }
+            }
+            if ui_tabs.button("Buscar").on_hover_text("Buscar na saída (Ctrl+Shift+F)").clicked()
+                && let Some(terminal) = self.active_idx.and_then(|idx| self.sessions.get_mut(idx))
+            {
+                terminal.search.open();
+            }
+            if ui_tabs.button("Ocultar").clicked() {
+                self.is_open = false;
+            }
+        });
+        ui.separator();
+
+        if let Some(idx) = session_to_close {
+            self.close_session(idx);
+        }
+
+        match (self.active_idx, self.split_idx) {
+            (Some(active), Some(split)) if active != split && split < self.sessions.len() => {
+                ui.columns(2, |columns| {
+                    self.sessions[active].ui(&mut columns[0], current_dir.as_deref());
+                    self.sessions[split].ui(&mut columns[1], current_dir.as_deref());
+                });
+            },
+            (Some(active), _) => {
+                if let Some(terminal) = self.sessions.get_mut(active) {
+                    terminal.ui(ui, current_dir.as_deref());
+                }
+            },
+            _ => {
+                ui.label("Nenhuma sessão de terminal aberta.");
+            },
+        }
+
+        self.sessions.iter_mut().find_map(Terminal::take_link_to_open)
+    }
+}
@@ -1,3 +1,9 @@
 // src/terminal/mod.rs
 
-pub mod pty_integration;
\ No newline at end of file
+pub mod pty_integration;
+pub mod emulator; // Emulação VT100/xterm sobre o alacritty_terminal
+pub mod renderer; // Desenho da grade do terminal no egui
+pub mod input; // Tradução de teclas do egui para sequências do xterm
+pub mod manager; // Sessões, abas e divisão do painel de terminal
+pub mod search; // Busca na saída e no histórico de rolagem
+pub mod links; // Detecção de caminhos arquivo:linha e URLs na saída
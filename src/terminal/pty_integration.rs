@@ -3,25 +3,38 @@
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use async_trait::async_trait;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
-use egui::Widget;
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use futures::StreamExt;
-use bytes::Buf;
 
-use portable_pty::{PtySize, CommandBuilder, PtySystem, native_pty_system, MasterPty, PtyPair, Child as PortablePtyChild};
+use alacritty_terminal::event::Event;
+use alacritty_terminal::index::Direction;
+use alacritty_terminal::term::TermMode;
+use portable_pty::{PtySize, CommandBuilder, PtySystem, native_pty_system, MasterPty, Child as PortablePtyChild};
+
+use crate::terminal::emulator::{TerminalEmulator, TerminalSize};
+use crate::terminal::input::{key_to_bytes, paste_to_bytes};
+use crate::terminal::links::{link_at, FileLocation, LinkTarget};
+use crate::terminal::renderer::{draw_link_underline, draw_terminal, CellMetrics, SearchHighlights};
+use crate::terminal::search::{SearchAction, TerminalSearch};
+
+// Tamanho mínimo da grade, para que o shell continue utilizável em painéis muito baixos
+const MIN_COLS: usize = 20;
+const MIN_ROWS: usize = 2;
+
+/// Leitor e escritor assíncronos sobre o master do PTY.
+type PtyStreams = (Pin<Box<dyn tokio::io::AsyncRead + Send>>, Pin<Box<dyn tokio::io::AsyncWrite + Send>>);
 
 struct PtyAsyncWriter {
     writer: Box<dyn std::io::Write + Send>,
 }
 
 impl PtyAsyncWriter {
-    fn new(master: Box<dyn MasterPty + Send>) -> io::Result<Self> {
+    fn new(master: &dyn MasterPty) -> io::Result<Self> {
         let writer = master.take_writer()
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+            .map_err(|e| io::Error::other(e.to_string()))?;
         Ok(Self { writer })
     }
 }
@@ -63,11 +76,14 @@ pub trait PseudoTerminal {
     async fn write_to_pty(&mut self, data: &[u8]) -> io::Result<usize>;
     async fn read_from_pty(&mut self) -> io::Result<Vec<u8>>;
     fn output_receiver(&mut self) -> mpsc::Receiver<TerminalOutput>;
+    /// Ajusta o tamanho do PTY (o kernel envia SIGWINCH ao processo em primeiro plano).
+    fn resize(&mut self, rows: u16, cols: u16) -> io::Result<()>;
 }
 
 pub struct PortablePtyTerminal {
     pty_system: Box<dyn PtySystem + Send>,
     master_pty: Option<Box<dyn MasterPty + Send>>,
+    size: PtySize, // Tamanho atual, usado também ao abrir o PTY
     reader: Option<Pin<Box<dyn tokio::io::AsyncRead + Send>>>,
     writer: Option<Pin<Box<dyn tokio::io::AsyncWrite + Send>>>,
     shell_child: Option<Box<dyn PortablePtyChild + Send + Sync>>,
@@ -77,6 +93,10 @@ pub struct PortablePtyTerminal {
     write_tx: mpsc::Sender<Vec<u8>>,
     write_rx: Option<mpsc::Receiver<Vec<u8>>>,
     write_task_handle: Option<JoinHandle<()>>,
+    // Contexto da UI acordado pela tarefa de leitura quando chega saída nova
+    repaint_ctx: Option<egui::Context>,
+    // Comando executado no lugar de um shell interativo (tarefas)
+    command: Option<String>,
 }
 
 impl PortablePtyTerminal {
@@ -87,6 +107,12 @@ impl PortablePtyTerminal {
         let instance = Self {
             pty_system: native_pty_system(),
             master_pty: None,
+            size: PtySize {
+                rows: 24,
+                cols: 80,
+                pixel_width: 0,
+                pixel_height: 0,
+            },
             reader: None,
             writer: None,
             shell_child: None,
@@ -95,13 +121,43 @@ impl PortablePtyTerminal {
             write_tx: write_tx.clone(), // NOVO: Clone do sender para a própria struct
             write_rx: Some(write_rx), // NOVO: Receiver para a struct
             write_task_handle: None, // NOVO: Handle para a tarefa de escrita
+            repaint_ctx: None,
+            command: None,
         };
         (instance, output_rx, write_tx) // NOVO: Retorna o write_tx
     }
 
+    /// Define o contexto do egui a ser redesenhado sempre que o PTY produzir saída,
+    /// mesmo que o painel do terminal esteja oculto.
+    pub fn set_repaint_context(&mut self, ctx: egui::Context) {
+        self.repaint_ctx = Some(ctx);
+    }
+
+    /// Executa `command` via `$SHELL -c` em vez de abrir um shell interativo.
+    /// Precisa ser chamado antes de `spawn_shell`.
+    pub fn set_command(&mut self, command: String) {
+        self.command = Some(command);
+    }
+
+    /// Código de saída do shell, se ele já terminou.
+    pub fn exit_code(&mut self) -> Option<i32> {
+        let child = self.shell_child.as_mut()?;
+        match child.try_wait() {
+            Ok(Some(status)) => Some(status.exit_code() as i32),
+            _ => None,
+        }
+    }
+
+    /// Diretório de trabalho atual do shell (lido de `/proc` no Linux).
+    pub fn shell_cwd(&self) -> Option<PathBuf> {
+        let pid = self.shell_child.as_ref()?.process_id()?;
+        std::fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+    }
+
     fn spawn_read_task(&mut self) {
         if let Some(mut reader) = self.reader.take() {
             let tx = self.output_tx.clone();
+            let repaint_ctx = self.repaint_ctx.clone();
 
             let handle = tokio::spawn(async move {
                 let mut buffer = vec![0; 4096];
@@ -114,12 +170,18 @@ impl PortablePtyTerminal {
                                     if let Err(e) = tx.send(TerminalOutput::Exited(None)).await {
                                         eprintln!("Erro ao enviar TerminalOutput::Exited: {}", e);
                                     }
+                                    if let Some(ctx) = &repaint_ctx {
+                                        ctx.request_repaint();
+                                    }
                                     break;
                                 },
                                 Ok(n) => {
                                     if let Err(e) = tx.send(TerminalOutput::Data(buffer[..n].to_vec())).await {
                                         eprintln!("Erro ao enviar TerminalOutput::Data: {}", e);
                                     }
+                                    if let Some(ctx) = &repaint_ctx {
+                                        ctx.request_repaint();
+                                    }
                                 },
                                 Err(e) => {
                                     eprintln!("Erro de leitura do PTY mestre: {}", e);
@@ -139,32 +201,30 @@ impl PortablePtyTerminal {
 
     // NOVO: Tarefa para lidar com a escrita no PTY
     fn spawn_write_task(&mut self) {
-        if let Some(mut writer) = self.writer.take() {
-            if let Some(mut rx) = self.write_rx.take() {
-                let handle = tokio::spawn(async move {
-                    while let Some(data) = rx.recv().await {
-                        if let Err(e) = writer.write_all(&data).await {
-                            eprintln!("Erro ao escrever no PTY: {}", e);
-                            break;
-                        }
-                        if let Err(e) = writer.flush().await {
-                            eprintln!("Erro ao dar flush no PTY: {}", e);
-                            break;
-                        }
+        if let (Some(mut writer), Some(mut rx)) = (self.writer.take(), self.write_rx.take()) {
+            let handle = tokio::spawn(async move {
+                while let Some(data) = rx.recv().await {
+                    if let Err(e) = writer.write_all(&data).await {
+                        eprintln!("Erro ao escrever no PTY: {}", e);
+                        break;
                     }
-                    eprintln!("Tarefa de escrita do PTY encerrada.");
-                });
-                self.write_task_handle = Some(handle);
-            }
+                    if let Err(e) = writer.flush().await {
+                        eprintln!("Erro ao dar flush no PTY: {}", e);
+                        break;
+                    }
+                }
+                eprintln!("Tarefa de escrita do PTY encerrada.");
+            });
+            self.write_task_handle = Some(handle);
         }
     }
 
-    fn create_async_streams(master: Box<dyn MasterPty + Send>) -> io::Result<(Pin<Box<dyn tokio::io::AsyncRead + Send>>, Pin<Box<dyn tokio::io::AsyncWrite + Send>>)> {
+    fn create_async_streams(master: &dyn MasterPty) -> io::Result<PtyStreams> {
         let reader = master.try_clone_reader()
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+            .map_err(|e| io::Error::other(e.to_string()))?;
         let async_writer = PtyAsyncWriter::new(master)?;
 
-        let (tx, mut rx) = tokio::sync::mpsc::channel::<Vec<u8>>(1024);
+        let (tx, rx) = tokio::sync::mpsc::channel::<Vec<u8>>(1024);
 
         let _read_handle = tokio::task::spawn_blocking(move || {
             use std::io::Read;
@@ -228,30 +288,31 @@ impl Drop for PortablePtyTerminal {
 #[async_trait]
 impl PseudoTerminal for PortablePtyTerminal {
     async fn spawn_shell(&mut self, working_directory: Option<PathBuf>) -> io::Result<()> {
-        let pty_pair = self.pty_system.openpty(PtySize {
-            rows: 24,
-            cols: 80,
-            pixel_width: 0,
-            pixel_height: 0,
-        })
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+        let pty_pair = self.pty_system.openpty(self.size)
+            .map_err(|e| io::Error::other(e.to_string()))?;
 
         let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string());
         eprintln!("Spawning shell: {}", shell);
 
         let mut cmd_builder = CommandBuilder::new(&shell);
+        if let Some(command) = &self.command {
+            eprintln!("Running command: {}", command);
+            cmd_builder.arg("-c");
+            cmd_builder.arg(command);
+        }
         if let Some(dir) = working_directory {
             eprintln!("Setting working directory to: {:?}", dir);
             cmd_builder.cwd(dir);
         }
 
         let shell_child = pty_pair.slave.spawn_command(cmd_builder)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+            .map_err(|e| io::Error::other(e.to_string()))?;
 
         self.shell_child = Some(shell_child);
 
-        let (reader_stream, writer_stream) = Self::create_async_streams(pty_pair.master)?;
+        let (reader_stream, writer_stream) = Self::create_async_streams(pty_pair.master.as_ref())?;
 
+        self.master_pty = Some(pty_pair.master); // Mantido para redimensionar o PTY
         self.reader = Some(reader_stream);
         self.writer = Some(writer_stream);
 
@@ -264,7 +325,7 @@ impl PseudoTerminal for PortablePtyTerminal {
 
     async fn write_to_pty(&mut self, data: &[u8]) -> io::Result<usize> {
         // Agora, a escrita é feita através do canal para a tarefa de escrita
-        if let Err(_) = self.write_tx.send(data.to_vec()).await {
+        if self.write_tx.send(data.to_vec()).await.is_err() {
             Err(io::Error::new(io::ErrorKind::BrokenPipe, "Failed to send data to PTY writer task"))
         } else {
             Ok(data.len())
@@ -272,131 +333,356 @@ impl PseudoTerminal for PortablePtyTerminal {
     }
 
     async fn read_from_pty(&mut self) -> io::Result<Vec<u8>> {
-        Err(io::Error::new(io::ErrorKind::Other, "read_from_pty should not be called directly"))
+        Err(io::Error::other("read_from_pty should not be called directly"))
     }
 
     fn output_receiver(&mut self) -> mpsc::Receiver<TerminalOutput> {
         panic!("output_receiver() não pode ser chamado, o Receiver é movido na construção de PortablePtyTerminal");
     }
+
+    fn resize(&mut self, rows: u16, cols: u16) -> io::Result<()> {
+        self.size.rows = rows;
+        self.size.cols = cols;
+        if let Some(master) = &self.master_pty {
+            master.resize(self.size)
+                .map_err(|e| io::Error::other(e.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Situação do processo do shell de uma sessão.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionStatus {
+    NotStarted,
+    Running,
+    Exited(Option<i32>),
 }
 
-// Struct para o estado do terminal na UI
+// Struct para o estado de uma sessão de terminal na UI
 pub struct Terminal {
+    pub id: usize, // Identificador estável da sessão, usado em ids do egui
     pub pty: PortablePtyTerminal,
-    pub input_buffer: String,
-    pub output_buffer: String,
-    pub is_open: bool,
-    pub scroll_offset: f32,
+    pub emulator: TerminalEmulator, // Grade VT100/xterm alimentada pela saída do PTY
+    pub title: Option<String>, // Título definido pelo shell via OSC 0/2
+    pub cwd: Option<PathBuf>, // Diretório em que a sessão foi iniciada
+    pub status: SessionStatus,
+    pub search: TerminalSearch, // Busca na saída e no histórico de rolagem
+    pub scroll_offset: f32, // Acumula frações de linha da roda do mouse
+    link_to_open: Option<FileLocation>, // Arquivo pedido via Ctrl+clique, aguardando o editor
+    captured_output: Option<Vec<u8>>, // Saída guardada para os problem matchers, se for uma tarefa
     pub terminal_output_rx_ui: mpsc::Receiver<TerminalOutput>,
     pub command_tx: mpsc::Sender<String>, // Este ainda é o canal da UI para a lógica de `Terminal`
-    command_rx_pty: mpsc::Receiver<String>,
+    _command_rx_pty: mpsc::Receiver<String>, // Mantém o canal aberto para `command_tx`
     pty_write_tx: mpsc::Sender<Vec<u8>>, // NOVO: Sender para a tarefa de escrita do PTY
 }
 
 impl Terminal {
-    pub fn new() -> Self {
+    pub fn new(id: usize, scrollback_lines: usize) -> Self {
         let (pty_instance, output_rx_from_pty, pty_write_tx) = PortablePtyTerminal::new(); // NOVO: Captura o pty_write_tx
         let (command_tx, command_rx_pty) = mpsc::channel(100);
 
         Terminal {
+            id,
             pty: pty_instance,
-            input_buffer: String::new(),
-            output_buffer: String::new(),
-            is_open: false,
+            emulator: TerminalEmulator::new(TerminalSize { cols: 80, rows: 24 }, scrollback_lines),
+            title: None,
+            cwd: None,
+            status: SessionStatus::NotStarted,
+            search: TerminalSearch::default(),
+            link_to_open: None,
+            captured_output: None,
             scroll_offset: 0.0,
             terminal_output_rx_ui: output_rx_from_pty,
             command_tx,
-            command_rx_pty,
+            _command_rx_pty: command_rx_pty,
             pty_write_tx, // NOVO: Inicializa o pty_write_tx
         }
     }
 
-    /// Desenha a interface do terminal.
-    pub fn ui(&mut self, ui: &mut egui::Ui, current_dir: Option<PathBuf>) {
-        ui.heading("Terminal Integrado");
-        ui.separator();
+    /// Transforma a sessão em uma tarefa: `command` roda no lugar do shell e a sua
+    /// saída fica disponível em `take_captured_output`. Precisa ser chamado antes de `start`.
+    pub fn set_task(&mut self, name: &str, command: &str) {
+        self.pty.set_command(command.to_string());
+        self.title = Some(name.to_string());
+        self.captured_output = Some(Vec::new());
+    }
 
-        egui::ScrollArea::vertical()
-            .auto_shrink([false; 2])
-            .stick_to_bottom(true)
-            .show(ui, |ui_scroll| {
-                ui_scroll.add(egui::Label::new(egui::RichText::new(&self.output_buffer).monospace()));
-            });
+    /// Saída recebida desde a última chamada (vazia se a sessão não for uma tarefa).
+    pub fn take_captured_output(&mut self) -> Vec<u8> {
+        self.captured_output.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Nome exibido na aba da sessão.
+    pub fn display_title(&self) -> String {
+        let title = self.title.clone().unwrap_or_else(|| format!("Terminal {}", self.id));
+        match self.status {
+            SessionStatus::Exited(Some(code)) => format!("{} [saiu: {}]", title, code),
+            SessionStatus::Exited(None) => format!("{} [encerrado]", title),
+            _ => title,
+        }
+    }
+
+    /// Diretório de trabalho atual da sessão, caindo para o diretório inicial.
+    pub fn current_cwd(&self) -> Option<PathBuf> {
+        self.pty.shell_cwd().or_else(|| self.cwd.clone())
+    }
+
+    /// Desenha a grade do terminal ocupando todo o espaço disponível.
+    ///
+    /// `project_dir` é usado, depois do cwd do shell, para resolver caminhos relativos
+    /// encontrados na saída.
+    pub fn ui(&mut self, ui: &mut egui::Ui, project_dir: Option<&Path>) {
+        if self.search.is_open {
+            match self.search.ui(ui) {
+                SearchAction::Next => self.search.find(&mut self.emulator.term, Direction::Left),
+                SearchAction::Previous => self.search.find(&mut self.emulator.term, Direction::Right),
+                SearchAction::Close => {
+                    self.search.close();
+                    ui.memory_mut(|m| m.request_focus(self.focus_id()));
+                },
+                SearchAction::None => {},
+            }
+        }
 
-        ui.separator();
+        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+        self.fit_to_available_size(ui, &font_id);
 
-        ui.horizontal(|ui_input| {
-            let text_edit_response = egui::TextEdit::singleline(&mut self.input_buffer)
-                .desired_width(ui_input.available_width() - 50.0)
-                .lock_focus(true)
-                .hint_text("Digite comandos aqui...")
-                .ui(ui_input);
+        let has_focus = ui.memory(|m| m.has_focus(self.focus_id()));
+        let matches = self.search.visible_matches(&self.emulator.term);
+        let highlights = SearchHighlights {
+            matches: &matches,
+            current: self.search.current_match.as_ref(),
+        };
+        let response = draw_terminal(ui, &self.emulator, &font_id, has_focus, highlights);
 
-            if text_edit_response.lost_focus() && ui_input.input(|i| i.key_pressed(egui::Key::Enter)) {
-                let command = self.input_buffer.clone();
-                self.output_buffer.push_str(&format!("> {}\n", command));
-                self.input_buffer.clear();
+        // A grade é registrada com um id fixo para que o foco sobreviva entre frames.
+        let response = ui.interact(response.rect, self.focus_id(), egui::Sense::click());
+        self.handle_links(ui, &response, CellMetrics::from_font(ui, &font_id), project_dir);
+        self.handle_input(ui, &response);
+    }
 
-                // NOVO: Envia o comando diretamente para a tarefa de escrita do PTY
-                let pty_write_tx_clone = self.pty_write_tx.clone();
-                let command_with_newline = format!("{}\n", command);
-                let data = command_with_newline.as_bytes().to_vec();
+    /// Arquivo pedido via Ctrl+clique em um link, se houver, para ser aberto no editor.
+    pub fn take_link_to_open(&mut self) -> Option<FileLocation> {
+        self.link_to_open.take()
+    }
 
-                tokio::spawn(async move {
-                    if let Err(e) = pty_write_tx_clone.send(data).await {
-                        eprintln!("Erro ao enviar comando para a tarefa de escrita do PTY: {}", e);
-                    }
-                });
-                text_edit_response.request_focus();
+    /// Sublinha o link sob o ponteiro e o abre com Ctrl+clique: URLs no navegador
+    /// do sistema, arquivos no editor.
+    fn handle_links(&mut self, ui: &egui::Ui, response: &egui::Response, metrics: CellMetrics, project_dir: Option<&Path>) {
+        let Some((row, col)) = response.hover_pos().and_then(|pos| metrics.cell_at(response.rect, pos)) else {
+            return;
+        };
+        let mut base_dirs: Vec<PathBuf> = self.current_cwd().into_iter().collect();
+        base_dirs.extend(project_dir.map(Path::to_path_buf));
+        let Some(link) = link_at(&self.emulator.term, row, col, &base_dirs) else {
+            return;
+        };
+
+        draw_link_underline(ui, response.rect, metrics, &link);
+        let ctrl_held = ui.input(|i| i.modifiers.command);
+        if ctrl_held {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+        }
+        if response.clicked() && ctrl_held {
+            match link.target {
+                LinkTarget::Url(url) => ui.ctx().open_url(egui::OpenUrl::new_tab(url)),
+                LinkTarget::File(location) => self.link_to_open = Some(location),
+            }
+        }
+    }
+
+    /// Rola o histórico com a roda do mouse. Na tela alternativa (vim, less...) com
+    /// rolagem alternativa ativa, envia setas ao programa, como o xterm.
+    fn handle_scroll(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        if !response.hovered() {
+            return;
+        }
+        let delta = ui.input(|i| i.smooth_scroll_delta.y);
+        if delta == 0.0 {
+            return;
+        }
+
+        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+        self.scroll_offset += delta / CellMetrics::from_font(ui, &font_id).height;
+        let lines = self.scroll_offset.trunc() as i32;
+        if lines == 0 {
+            return;
+        }
+        self.scroll_offset -= lines as f32;
+
+        let mode = *self.emulator.term.mode();
+        if mode.contains(TermMode::ALT_SCREEN | TermMode::ALTERNATE_SCROLL) {
+            let key = if lines > 0 { egui::Key::ArrowUp } else { egui::Key::ArrowDown };
+            let bytes = key_to_bytes(key, &egui::Modifiers::NONE, mode).unwrap_or_default();
+            let _ = self.pty_write_tx.try_send(bytes.repeat(lines.unsigned_abs() as usize));
+        } else {
+            self.emulator.scroll_lines(lines);
+        }
+    }
+
+    /// Recalcula linhas/colunas a partir do espaço livre no painel e propaga a mudança
+    /// para a grade do emulador e para o PTY.
+    fn fit_to_available_size(&mut self, ui: &egui::Ui, font_id: &egui::FontId) {
+        let metrics = CellMetrics::from_font(ui, font_id);
+        let available = ui.available_size();
+        let new_size = TerminalSize {
+            cols: ((available.x / metrics.width).floor() as usize).clamp(MIN_COLS, u16::MAX as usize),
+            rows: ((available.y / metrics.height).floor() as usize).clamp(MIN_ROWS, u16::MAX as usize),
+        };
+
+        if new_size != self.emulator.size() {
+            self.emulator.term.resize(new_size);
+            if let Err(e) = self.pty.resize(new_size.rows as u16, new_size.cols as u16) {
+                eprintln!("Erro ao redimensionar o PTY: {}", e);
+            }
+        }
+    }
+
+    pub fn focus_id(&self) -> egui::Id {
+        egui::Id::new(("integrated_terminal_grid", self.id))
+    }
+
+    /// Encaminha cada tecla, texto e colagem recebidos pelo painel focado para o PTY.
+    fn handle_input(&mut self, ui: &mut egui::Ui, response: &egui::Response) {
+        if response.clicked() {
+            response.request_focus();
+        }
+        self.handle_scroll(ui, response);
+        if !response.has_focus() {
+            return;
+        }
+
+        // Tab, setas e Esc pertencem ao shell, não à navegação de foco do egui.
+        ui.memory_mut(|m| m.set_focus_lock_filter(response.id, egui::EventFilter {
+            tab: true,
+            horizontal_arrows: true,
+            vertical_arrows: true,
+            escape: true,
+        }));
+
+        let mode = *self.emulator.term.mode();
+        let mut to_write: Vec<u8> = Vec::new();
+        let mut history_scroll = 0;
+        // Bytes do último atalho Ctrl/Alt enviado, para não repetir o `Text` da mesma tecla.
+        let mut chord: Option<std::ops::Range<usize>> = None;
+        ui.input(|i| {
+            for event in &i.events {
+                match event {
+                    // Atalhos do próprio painel, que não chegam ao shell
+                    egui::Event::Key { key: egui::Key::F, pressed: true, modifiers, .. } if modifiers.ctrl && modifiers.shift => {
+                        self.search.open();
+                    },
+                    egui::Event::Key { key: egui::Key::PageUp, pressed: true, modifiers, .. } if modifiers.shift => {
+                        history_scroll += self.emulator.size().rows as i32;
+                    },
+                    egui::Event::Key { key: egui::Key::PageDown, pressed: true, modifiers, .. } if modifiers.shift => {
+                        history_scroll -= self.emulator.size().rows as i32;
+                    },
+                    egui::Event::Text(text) => match chord.take() {
+                        // Ctrl+Alt com texto imprimível é AltGr (`@`, `{`, `€`): o caractere
+                        // substitui a sequência de controle gerada pela tecla.
+                        Some(range) if i.modifiers.ctrl && i.modifiers.alt && range.end == to_write.len() => {
+                            to_write.truncate(range.start);
+                            to_write.extend_from_slice(text.as_bytes());
+                        },
+                        // A sequência Ctrl/Alt da mesma tecla já foi enviada.
+                        Some(_) => {},
+                        None => to_write.extend_from_slice(text.as_bytes()),
+                    },
+                    egui::Event::Key { key, pressed: true, modifiers, .. } => {
+                        chord = None;
+                        if let Some(bytes) = key_to_bytes(*key, modifiers, mode) {
+                            let start = to_write.len();
+                            to_write.extend(bytes);
+                            if modifiers.ctrl || modifiers.command || modifiers.alt {
+                                chord = Some(start..to_write.len());
+                            }
+                        }
+                    },
+                    // O egui converte Ctrl+C/Ctrl+X em eventos de área de transferência.
+                    egui::Event::Copy => {
+                        if i.modifiers.shift {
+                            if let Some(text) = self.emulator.term.selection_to_string() {
+                                ui.ctx().copy_text(text);
+                            }
+                        } else {
+                            to_write.push(0x03);
+                        }
+                    },
+                    egui::Event::Cut => to_write.push(0x18),
+                    egui::Event::Paste(text) => to_write.extend(paste_to_bytes(text, mode)),
+                    _ => {},
+                }
             }
         });
 
-        // Processar mensagens do PTY no loop de update da UI
+        if history_scroll != 0 {
+            self.emulator.scroll_lines(history_scroll);
+            ui.ctx().request_repaint();
+        }
+
+        if !to_write.is_empty() {
+            // Digitar volta a visualização para a saída mais recente.
+            self.emulator.scroll_to_bottom();
+            if let Err(e) = self.pty_write_tx.try_send(to_write) {
+                eprintln!("Erro ao enviar entrada para a tarefa de escrita do PTY: {}", e);
+            }
+            ui.ctx().request_repaint();
+        }
+    }
+
+    /// Alimenta o emulador com a saída pendente do PTY e trata os eventos gerados por ele.
+    ///
+    /// Deve ser chamado a cada frame, mesmo com o painel oculto, para que o shell
+    /// não fique bloqueado escrevendo em um canal cheio.
+    pub fn process_pty_output(&mut self, ctx: &egui::Context) {
         while let Ok(msg) = self.terminal_output_rx_ui.try_recv() {
             match msg {
                 TerminalOutput::Data(data) => {
-                    if let Ok(s) = String::from_utf8(data) {
-                        self.output_buffer.push_str(&s);
-                        ui.ctx().request_repaint();
-                    } else {
-                        eprintln!("Received non-UTF8 data from PTY");
+                    self.emulator.process(&data);
+                    if let Some(captured) = &mut self.captured_output {
+                        captured.extend_from_slice(&data);
                     }
                 },
                 TerminalOutput::Exited(code) => {
-                    self.output_buffer.push_str(&format!("\nShell exited with code: {:?}\n", code));
-                    ui.ctx().request_repaint();
+                    let code = code.or_else(|| self.pty.exit_code());
+                    self.status = SessionStatus::Exited(code);
+                    self.emulator.process(format!("\r\nShell exited with code: {:?}\r\n", code).as_bytes());
                 },
                 TerminalOutput::Error(e) => {
-                    self.output_buffer.push_str(&format!("\nTerminal Error: {}\n", e));
-                    ui.ctx().request_repaint();
+                    self.emulator.process(format!("\r\nTerminal Error: {}\r\n", e).as_bytes());
                 }
             }
+            ctx.request_repaint();
         }
 
-        // REMOVIDO: Este bloco agora é desnecessário, pois a escrita é feita diretamente acima
-        // while let Ok(command) = self.command_rx_pty.try_recv() {
-        //     let command_with_newline = format!("{}\n", command);
-        //     let data = command_with_newline.as_bytes().to_vec();
-        //     let mut pty_clone = &mut self.pty;
-        //     let data_clone = data.clone();
-        //     if let Some(_) = &mut pty_clone.writer {
-        //         let rt = tokio::runtime::Handle::current();
-        //         if let Err(e) = rt.block_on(pty_clone.write_to_pty(&data_clone)) {
-        //             eprintln!("Erro ao escrever no PTY: {}", e);
-        //         }
-        //     }
-        // }
+        for event in self.emulator.drain_events() {
+            match event {
+                // Respostas a consultas do programa (DA, DSR, cores...) voltam para o PTY
+                Event::PtyWrite(text) => {
+                    if let Err(e) = self.pty_write_tx.try_send(text.into_bytes()) {
+                        eprintln!("Erro ao responder ao PTY: {}", e);
+                    }
+                },
+                Event::Title(title) => self.title = Some(title),
+                Event::ResetTitle => self.title = None,
+                Event::ClipboardStore(_, text) => ctx.copy_text(text),
+                _ => {},
+            }
+        }
     }
 
     /// Inicia o terminal com o diretório de trabalho especificado.
-    pub fn start(&mut self, current_dir: Option<PathBuf>) {
+    pub fn start(&mut self, ctx: &egui::Context, current_dir: Option<PathBuf>) {
         if self.pty.shell_child.is_none() {
             let runtime = tokio::runtime::Handle::current();
-            let mut pty = &mut self.pty;
+            self.pty.set_repaint_context(ctx.clone());
+            self.cwd = current_dir.clone();
 
-            match runtime.block_on(pty.spawn_shell(current_dir)) {
+            match runtime.block_on(self.pty.spawn_shell(current_dir)) {
                 Ok(()) => {
-                    self.is_open = true;
+                    self.status = SessionStatus::Running;
                     eprintln!("Terminal iniciado.");
                 },
                 Err(e) => {
@@ -428,7 +714,7 @@ impl Terminal {
         self.pty.master_pty = None;
         self.pty.reader = None;
         self.pty.writer = None;
-        self.is_open = false;
+        self.status = SessionStatus::Exited(None);
         eprintln!("Terminal parado.");
     }
 }
\ No newline at end of file
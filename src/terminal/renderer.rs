@@ -0,0 +1,275 @@
+// src/terminal/renderer.rs
+
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Line, Point};
+use alacritty_terminal::term::cell::{Cell, Flags};
+use alacritty_terminal::term::point_to_viewport;
+use alacritty_terminal::term::search::Match;
+use alacritty_terminal::vte::ansi::{Color, CursorShape, NamedColor};
+use egui::text::LayoutJob;
+
+use crate::terminal::emulator::TerminalEmulator;
+use crate::terminal::links::TerminalLink;
+
+/// Tamanho, em pontos, de uma célula da grade para a fonte monoespaçada usada.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellMetrics {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl CellMetrics {
+    pub fn from_font(ui: &egui::Ui, font_id: &egui::FontId) -> Self {
+        ui.fonts(|f| Self {
+            width: f.glyph_width(font_id, 'M'),
+            height: f.row_height(font_id),
+        })
+    }
+
+    /// Célula (linha, coluna) da viewport sob `pos`, para uma grade desenhada em `rect`.
+    pub fn cell_at(&self, rect: egui::Rect, pos: egui::Pos2) -> Option<(usize, usize)> {
+        if !rect.contains(pos) {
+            return None;
+        }
+        let offset = pos - rect.min;
+        Some(((offset.y / self.height) as usize, (offset.x / self.width) as usize))
+    }
+}
+
+/// Atributos visuais de uma célula, já com as cores resolvidas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CellStyle {
+    fg: egui::Color32,
+    bg: Option<egui::Color32>,
+    italics: bool,
+    underline: bool,
+    strikeout: bool,
+}
+
+fn cell_style(emulator: &TerminalEmulator, cell: &Cell, is_block_cursor: bool) -> CellStyle {
+    let mut fg = cell.fg;
+    // Negrito com cores nomeadas usa a variante brilhante, como no xterm.
+    if let Color::Named(named) = fg {
+        if cell.flags.contains(Flags::BOLD) {
+            fg = Color::Named(named.to_bright());
+        } else if cell.flags.contains(Flags::DIM) {
+            fg = Color::Named(named.to_dim());
+        }
+    }
+
+    let mut fg_color = emulator.resolve_color(fg);
+    let mut bg_color = match cell.bg {
+        Color::Named(NamedColor::Background) => None,
+        bg => Some(emulator.resolve_color(bg)),
+    };
+
+    if cell.flags.contains(Flags::INVERSE) != is_block_cursor {
+        let default_bg = emulator.resolve_color(Color::Named(NamedColor::Background));
+        let new_bg = fg_color;
+        fg_color = bg_color.unwrap_or(default_bg);
+        bg_color = Some(new_bg);
+    }
+
+    if cell.flags.contains(Flags::HIDDEN) {
+        fg_color = bg_color.unwrap_or(egui::Color32::TRANSPARENT);
+    }
+
+    CellStyle {
+        fg: fg_color,
+        bg: bg_color,
+        italics: cell.flags.contains(Flags::ITALIC),
+        underline: cell.flags.intersects(Flags::ALL_UNDERLINES),
+        strikeout: cell.flags.contains(Flags::STRIKEOUT),
+    }
+}
+
+/// Trecho contíguo de células com o mesmo estilo dentro de uma linha.
+struct Run {
+    column: usize,
+    cells: usize,
+    text: String,
+    style: CellStyle,
+    // Trechos terminados em caractere largo não crescem mais, para não desalinhar a grade.
+    closed: bool,
+}
+
+/// Ocorrências de busca a destacar sobre a grade.
+#[derive(Default)]
+pub struct SearchHighlights<'a> {
+    pub matches: &'a [Match],
+    pub current: Option<&'a Match>,
+}
+
+/// Desenha a grade visível do terminal, célula a célula, e retorna a resposta da área.
+///
+/// Apenas as linhas da janela visível são percorridas, independentemente do tamanho
+/// do histórico de rolagem.
+pub fn draw_terminal(
+    ui: &mut egui::Ui,
+    emulator: &TerminalEmulator,
+    font_id: &egui::FontId,
+    has_focus: bool,
+    highlights: SearchHighlights,
+) -> egui::Response {
+    let metrics = CellMetrics::from_font(ui, font_id);
+    let size = emulator.size();
+    let desired_size = egui::vec2(size.cols as f32 * metrics.width, size.rows as f32 * metrics.height);
+    let (rect, response) = ui.allocate_exact_size(desired_size, egui::Sense::hover());
+
+    let painter = ui.painter_at(rect);
+    let default_bg = emulator.resolve_color(Color::Named(NamedColor::Background));
+    painter.rect_filled(rect, 0.0, default_bg);
+
+    let content = emulator.term.renderable_content();
+    let cursor_point = point_to_viewport(content.display_offset, content.cursor.point);
+    let cursor_shape = if has_focus { content.cursor.shape } else { CursorShape::HollowBlock };
+
+    let mut lines: Vec<Vec<Run>> = (0..emulator.term.screen_lines()).map(|_| Vec::new()).collect();
+    for indexed in content.display_iter {
+        let Some(point) = point_to_viewport(content.display_offset, indexed.point) else {
+            continue;
+        };
+        if indexed.cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+            // A célula anterior já ocupa esta coluna.
+            if let Some(run) = lines[point.line].last_mut() {
+                run.cells += 1;
+            }
+            continue;
+        }
+
+        let is_block_cursor = cursor_shape == CursorShape::Block && cursor_point == Some(point);
+        let style = cell_style(emulator, indexed.cell, is_block_cursor);
+        let is_wide = indexed.cell.flags.contains(Flags::WIDE_CHAR);
+
+        let runs = &mut lines[point.line];
+        match runs.last_mut() {
+            Some(run) if run.style == style && !run.closed => {
+                run.text.push(indexed.cell.c);
+                run.cells += 1;
+                run.closed = is_wide;
+            },
+            _ => runs.push(Run {
+                column: point.column.0,
+                cells: 1,
+                text: indexed.cell.c.to_string(),
+                style,
+                closed: is_wide,
+            }),
+        }
+        if let Some(zerowidth) = indexed.cell.zerowidth()
+            && let Some(run) = runs.last_mut()
+        {
+            run.text.extend(zerowidth);
+        }
+    }
+
+    for (line_idx, runs) in lines.into_iter().enumerate() {
+        let y = rect.top() + line_idx as f32 * metrics.height;
+        for run in runs {
+            let run_rect = egui::Rect::from_min_size(
+                egui::pos2(rect.left() + run.column as f32 * metrics.width, y),
+                egui::vec2(run.cells as f32 * metrics.width, metrics.height),
+            );
+            if let Some(bg) = run.style.bg {
+                painter.rect_filled(run_rect, 0.0, bg);
+            }
+            if run.text.trim().is_empty() && !run.style.underline && !run.style.strikeout {
+                continue;
+            }
+
+            let stroke = |enabled: bool| if enabled { egui::Stroke::new(1.0, run.style.fg) } else { egui::Stroke::NONE };
+            let mut job = LayoutJob::single_section(run.text, egui::TextFormat {
+                font_id: font_id.clone(),
+                color: run.style.fg,
+                italics: run.style.italics,
+                underline: stroke(run.style.underline),
+                strikethrough: stroke(run.style.strikeout),
+                ..Default::default()
+            });
+            job.wrap.max_width = f32::INFINITY;
+            let galley = ui.fonts(|f| f.layout_job(job));
+            painter.galley(run_rect.min, galley, run.style.fg);
+        }
+    }
+
+    for search_match in highlights.matches {
+        let is_current = highlights.current == Some(search_match);
+        let color = if is_current {
+            egui::Color32::from_rgba_unmultiplied(255, 165, 0, 110)
+        } else {
+            egui::Color32::from_rgba_unmultiplied(255, 255, 0, 60)
+        };
+        for match_rect in match_rects(search_match, content.display_offset, size.cols, rect.min, metrics) {
+            painter.rect_filled(match_rect, 0.0, color);
+        }
+    }
+
+    if content.display_offset > 0 {
+        // Indica que a visualização está no histórico, e não na saída mais recente.
+        let indicator = format!("↑ {}/{}", content.display_offset, emulator.term.history_size());
+        painter.text(
+            rect.right_top() + egui::vec2(-4.0, 2.0),
+            egui::Align2::RIGHT_TOP,
+            indicator,
+            font_id.clone(),
+            ui.visuals().weak_text_color(),
+        );
+    }
+
+    if let Some(point) = cursor_point {
+        let cursor_rect = egui::Rect::from_min_size(
+            egui::pos2(
+                rect.left() + point.column.0 as f32 * metrics.width,
+                rect.top() + point.line as f32 * metrics.height,
+            ),
+            egui::vec2(metrics.width, metrics.height),
+        );
+        let cursor_color = emulator.resolve_color(Color::Named(NamedColor::Cursor));
+        match cursor_shape {
+            CursorShape::Beam => {
+                painter.rect_filled(egui::Rect::from_min_size(cursor_rect.min, egui::vec2(2.0, metrics.height)), 0.0, cursor_color);
+            },
+            CursorShape::Underline => {
+                painter.rect_filled(
+                    egui::Rect::from_min_max(egui::pos2(cursor_rect.left(), cursor_rect.bottom() - 2.0), cursor_rect.max),
+                    0.0,
+                    cursor_color,
+                );
+            },
+            CursorShape::HollowBlock => {
+                painter.rect_stroke(cursor_rect, 0.0, egui::Stroke::new(1.0, cursor_color), egui::StrokeKind::Inside);
+            },
+            // O cursor em bloco é desenhado invertendo as cores da célula.
+            CursorShape::Block | CursorShape::Hidden => {},
+        }
+    }
+
+    response
+}
+
+/// Retângulos (um por linha visível) cobertos por uma ocorrência de busca.
+fn match_rects(search_match: &Match, display_offset: usize, cols: usize, origin: egui::Pos2, metrics: CellMetrics) -> Vec<egui::Rect> {
+    let start = *search_match.start();
+    let end = *search_match.end();
+    let mut rects = Vec::new();
+    for line in start.line.0..=end.line.0 {
+        let Some(viewport) = point_to_viewport(display_offset, Point::new(Line(line), start.column)) else {
+            continue;
+        };
+        let first_col = if line == start.line.0 { start.column.0 } else { 0 };
+        let last_col = if line == end.line.0 { end.column.0 } else { cols.saturating_sub(1) };
+        rects.push(egui::Rect::from_min_size(
+            origin + egui::vec2(first_col as f32 * metrics.width, viewport.line as f32 * metrics.height),
+            egui::vec2((last_col + 1 - first_col) as f32 * metrics.width, metrics.height),
+        ));
+    }
+    rects
+}
+
+/// Sublinha um link detectado na saída (o link sob o ponteiro).
+pub fn draw_link_underline(ui: &egui::Ui, rect: egui::Rect, metrics: CellMetrics, link: &TerminalLink) {
+    let y = rect.top() + (link.row + 1) as f32 * metrics.height - 1.0;
+    let x_start = rect.left() + link.columns.start as f32 * metrics.width;
+    let x_end = rect.left() + link.columns.end as f32 * metrics.width;
+    ui.painter_at(rect).hline(x_start..=x_end, y, egui::Stroke::new(1.0, ui.visuals().hyperlink_color));
+}
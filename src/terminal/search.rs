@@ -0,0 +1,148 @@
+// src/terminal/search.rs
+
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Boundary, Column, Direction, Line, Point, Side};
+use alacritty_terminal::term::search::{Match, RegexIter, RegexSearch};
+use alacritty_terminal::term::Term;
+use alacritty_terminal::event::EventListener;
+
+/// Estado da busca na saída (tela + scrollback) de uma sessão de terminal.
+#[derive(Default)]
+pub struct TerminalSearch {
+    pub is_open: bool,
+    pub query: String,
+    pub use_regex: bool,
+    pub case_sensitive: bool,
+    pub error: Option<String>,
+    pub current_match: Option<Match>,
+    regex: Option<RegexSearch>,
+    compiled_for: Option<(String, bool, bool)>, // Consulta/opções usadas para compilar `regex`
+    request_focus: bool,
+    not_found: bool, // A última navegação não encontrou nenhuma ocorrência
+}
+
+/// Ação pedida pela barra de busca neste frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchAction {
+    None,
+    Next,
+    Previous,
+    Close,
+}
+
+impl TerminalSearch {
+    pub fn open(&mut self) {
+        self.is_open = true;
+        self.request_focus = true;
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.current_match = None;
+    }
+
+    /// Recompila a expressão se a consulta ou as opções mudaram.
+    ///
+    /// Em modo texto simples a consulta é escapada; a sensibilidade a maiúsculas é
+    /// forçada com flags inline porque o `RegexSearch` usa "smart case" por padrão.
+    fn ensure_compiled(&mut self) -> Option<&mut RegexSearch> {
+        let key = (self.query.clone(), self.use_regex, self.case_sensitive);
+        if self.compiled_for.as_ref() != Some(&key) {
+            self.compiled_for = Some(key);
+            self.regex = None;
+            self.error = None;
+            self.current_match = None;
+            self.not_found = false;
+
+            if !self.query.is_empty() {
+                let pattern = if self.use_regex { self.query.clone() } else { regex::escape(&self.query) };
+                let flags = if self.case_sensitive { "(?-i)" } else { "(?i)" };
+                match RegexSearch::new(&format!("{}{}", flags, pattern)) {
+                    Ok(regex) => self.regex = Some(regex),
+                    Err(e) => self.error = Some(e.to_string()),
+                }
+            }
+        }
+        self.regex.as_mut()
+    }
+
+    /// Avança para a próxima (ou anterior) ocorrência e rola a tela até ela.
+    pub fn find<T: EventListener>(&mut self, term: &mut Term<T>, direction: Direction) {
+        let origin = match (&self.current_match, direction) {
+            (Some(m), Direction::Right) => m.end().add(term, Boundary::None, 1),
+            (Some(m), Direction::Left) => m.start().sub(term, Boundary::None, 1),
+            // Sem ocorrência atual, a busca parte do fim (para trás) ou do topo (para frente).
+            (None, Direction::Left) => Point::new(term.bottommost_line(), term.last_column()),
+            (None, Direction::Right) => Point::new(term.topmost_line(), Column(0)),
+        };
+        let side = if direction == Direction::Right { Side::Left } else { Side::Right };
+
+        let Some(regex) = self.ensure_compiled() else {
+            return;
+        };
+        let found = term.search_next(regex, origin, direction, side, None);
+        if let Some(m) = &found {
+            term.scroll_to_point(*m.start());
+        }
+        self.not_found = found.is_none();
+        self.current_match = found;
+    }
+
+    /// Ocorrências dentro das linhas visíveis, para destaque.
+    pub fn visible_matches<T>(&mut self, term: &Term<T>) -> Vec<Match> {
+        if !self.is_open {
+            return Vec::new();
+        }
+        let display_offset = term.grid().display_offset() as i32;
+        let start = Point::new(Line(-display_offset), Column(0));
+        let end = Point::new(Line(term.screen_lines() as i32 - 1 - display_offset), term.last_column());
+        let Some(regex) = self.ensure_compiled() else {
+            return Vec::new();
+        };
+        RegexIter::new(start, end, Direction::Right, term, regex).collect()
+    }
+
+    /// Desenha a barra de busca e retorna a ação escolhida pelo usuário.
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> SearchAction {
+        let mut action = SearchAction::None;
+        ui.horizontal(|ui_bar| {
+            let response = ui_bar.add(
+                egui::TextEdit::singleline(&mut self.query)
+                    .desired_width(220.0)
+                    .hint_text("Buscar na saída..."),
+            );
+            if self.request_focus {
+                response.request_focus();
+                self.request_focus = false;
+            }
+            if response.lost_focus() && ui_bar.input(|i| i.key_pressed(egui::Key::Enter)) {
+                action = if ui_bar.input(|i| i.modifiers.shift) { SearchAction::Previous } else { SearchAction::Next };
+                response.request_focus();
+            }
+            // O TextEdit perde o foco ao receber Esc, então o frame atual ainda conta.
+            if (response.has_focus() || response.lost_focus()) && ui_bar.input(|i| i.key_pressed(egui::Key::Escape)) {
+                action = SearchAction::Close;
+            }
+
+            ui_bar.checkbox(&mut self.use_regex, ".*").on_hover_text("Expressão regular");
+            ui_bar.checkbox(&mut self.case_sensitive, "Aa").on_hover_text("Diferenciar maiúsculas/minúsculas");
+            // Como no alacritty, "próxima" sobe em direção à saída mais antiga.
+            if ui_bar.button("↑").on_hover_text("Próxima, acima (Enter)").clicked() {
+                action = SearchAction::Next;
+            }
+            if ui_bar.button("↓").on_hover_text("Anterior, abaixo (Shift+Enter)").clicked() {
+                action = SearchAction::Previous;
+            }
+            if ui_bar.add(egui::Button::new("x").small()).clicked() {
+                action = SearchAction::Close;
+            }
+
+            if let Some(error) = &self.error {
+                ui_bar.colored_label(ui_bar.visuals().error_fg_color, error.lines().next().unwrap_or_default());
+            } else if self.not_found {
+                ui_bar.weak("Sem resultados");
+            }
+        });
+        action
+    }
+}
@@ -3,25 +3,46 @@
 use eframe::egui;
 use ropey::Rope;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use rfd::AsyncFileDialog;
 use pollster;
 
 // Importar a função de salvamento do nosso módulo core
 use crate::core::file_handler;
-use crate::syntax_highlighting::highlighter::SyntaxHighlighter;
-use egui::text::LayoutJob; // Importar LayoutJob
-use crate::terminal::pty_integration::Terminal; // Apenas Terminal, não precisamos de TerminalOutput aqui
-use egui::TextWrapMode; 
+use crate::commands::builtin::register_builtin_commands;
+use crate::commands::keymap::{FocusState, KeyPress, Keymap, SEQUENCE_TIMEOUT};
+use crate::commands::registry::CommandRegistry;
+use crate::file_explorer::quick_open::QuickOpen;
+use crate::core::project_search::{apply_replacements, FilePreview, ProjectSearch, ReplacePreview};
+use crate::syntax_highlighting::highlighter::{HighlightBackend, SyntaxHighlighter};
+use crate::syntax_highlighting::themes::{user_themes_dir, visuals_for_theme};
+use crate::syntax_highlighting::syntaxes::user_syntaxes_dir;
+use crate::syntax_highlighting::worker::BackgroundHighlighter;
+use crate::terminal::links::FileLocation;
+use crate::terminal::manager::TerminalManager;
+use crate::tasks::runner::TaskRunner;
+use crate::ui::problems_panel::show_problems_panel;
+use crate::ui::search_panel::{show_search_panel, SearchPanelAction};
 use crate::ui::editor_ui::EditorPanel;
+use crate::ui::command_palette::CommandPalette;
+use crate::ui::settings_window::{SettingsAction, SettingsWindow};
+use crate::core::settings::{Settings, SettingsStore};
+use crate::core::session::{DirtyBuffer, LayoutSession, Session, SessionStore, TabSession};
+use crate::core::swap::{discard_swap, RecoveredSwap, SwapFiles};
+use crate::ui::recovery_dialog::{RecoveryAction, RecoveryDialog};
+use crate::ui::encoding_picker::{encoding_list, EncodingPicker, EncodingPickerMode};
+use crate::core::encoding::FileEncoding;
+use crate::core::line_ending::{detect_line_ending, line_ending_replacements, LineEnding};
+use crate::ui::find_bar::{FindAction, FindBar};
 use crate::core::editor::TextEditor;
+use crate::core::vim::{ExCommand, VimKey, VimMode, VimState};
 use std::sync::Arc;
 
-
-// Constantes de layout (melhor definidas aqui ou em um módulo de config)
-const LINE_HEIGHT: f32 = 16.0;
-const LINE_NUMBER_GUTTER_WIDTH: f32 = 60.0;
-const SIDE_PANEL_WIDTH: f32 = 200.0;
+// Ids dos painéis redimensionáveis, cujos tamanhos são guardados na sessão
+const EXPLORER_PANEL_ID: &str = "file_explorer_panel";
+const TERMINAL_PANEL_ID: &str = "integrated_terminal_panel";
+const PROBLEMS_PANEL_ID: &str = "problems_panel";
+const SEARCH_PANEL_ID: &str = "project_search_panel";
 
 /// Representa um item do sistema de arquivos (arquivo ou diretório).
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -38,20 +59,31 @@ pub struct EditorTab {
     pub is_modified: bool,
     pub editor_state: TextEditor,
     pub galley_cache: Vec<Option<Arc<egui::Galley>>>, // NOVO: Cache de galleys
-    pub last_content_len: usize, // NOVO: Para detectar mudanças de tamanho do conteúdo
+    pub highlight: BackgroundHighlighter, // Realce feito em outra thread
+    pub find: FindBar, // Busca/substituição neste arquivo
+    pub file_error: Option<String>, // Erro ou aviso da última leitura/gravação do arquivo, mostrado no cabeçalho
+    pub encoding: FileEncoding, // Codificação do arquivo no disco, usada ao salvar
+    pub mixed_line_endings: bool, // O arquivo misturava fins de linha; ao salvar, todos viram o da aba
 }
 
 impl EditorTab {
     /// Cria uma nova aba do editor.
     pub fn new(path: PathBuf, content: Rope) -> Self {
         let initial_len = content.len_lines();
+        let (line_ending, mixed_line_endings) = detect_line_ending(&content);
+        let mut editor_state = TextEditor::new();
+        editor_state.line_ending = line_ending;
         Self {
             path,
             content,
             is_modified: false,
-            editor_state: TextEditor::new(),
+            editor_state,
             galley_cache: vec![None; initial_len], // Inicializa o cache com o número de linhas
-            last_content_len: initial_len, // Guarda o comprimento inicial
+            highlight: BackgroundHighlighter::new(),
+            find: FindBar::default(),
+            file_error: None,
+            encoding: FileEncoding::UTF8,
+            mixed_line_endings,
         }
     }
 
@@ -78,12 +110,37 @@ pub struct MyApp {
     pub dialog_tab_idx_to_close: Option<usize>,
     pub highlighter: SyntaxHighlighter,
     pub editor_scroll_offset: egui::Vec2, // Para controlar o scroll do editor manualmente
-    pub terminal: Terminal, // Adicionar o terminal aqui
+    pub terminals: TerminalManager, // Sessões do terminal integrado
+    pub tasks: TaskRunner, // Tarefas de .lcode/tasks.toml e seus diagnósticos
+    pub show_problems: bool,
+    pub project_search: ProjectSearch, // Busca em todos os arquivos do diretório aberto
+    pub show_search: bool,
+    pub quick_open: QuickOpen, // Ctrl+P
+    pub commands: CommandRegistry, // Todas as ações, com nome e atalho
+    pub keymap: Keymap, // Atalhos padrão e de ~/.config/lcode/keybindings.toml
+    pub command_palette: CommandPalette, // Ctrl+Shift+P
+    pub vim: VimState, // Modo, registradores e repetição do modo Vim
+    pub vim_enabled: bool,
+    vim_tab: Option<usize>, // Aba que recebeu as últimas teclas do Vim
+    pub settings: SettingsStore, // ~/.config/lcode/settings.toml e .lcode/settings.toml
+    pub settings_window: SettingsWindow, // Ctrl+,
+    applied_settings: Option<(u64, Settings)>, // Revisão e valores das configurações já aplicadas
+    pub session: SessionStore, // Abas, painéis e backups de hot exit por diretório
+    restored_layout: LayoutSession, // Tamanhos dos painéis na sessão restaurada
+    session_restored: bool,
+    pub swap: SwapFiles, // Cópias das abas modificadas, para recuperar após uma queda
+    pub recovery: RecoveryDialog,
+    pub encoding_picker: EncodingPicker,
+    editor_focused: bool, // O texto do editor tinha o foco no último frame (atalhos do editor)
+    applied_highlight_revision: Option<u64>, // Tema/sintaxes já aplicados à interface e aos galleys
 }
 
 impl Default for MyApp {
     fn default() -> Self {
         let (tx, rx) = std::sync::mpsc::channel();
+        let mut commands = CommandRegistry::new();
+        register_builtin_commands(&mut commands);
+        let keymap = Keymap::load(&commands);
         let _initial_text = "Hello, lcode!\n\nEste é o nosso editor de código minimalista.\n\nClique em 'Abrir Diretório' para começar.\n".to_string();
 
 
@@ -98,22 +155,76 @@ impl Default for MyApp {
             dialog_tab_idx_to_close: None,
             highlighter: SyntaxHighlighter::new(),
             editor_scroll_offset: egui::Vec2::ZERO,
-            terminal: Terminal::new(), // Inicializar o terminal
+            terminals: TerminalManager::new(),
+            tasks: TaskRunner::default(),
+            show_problems: false,
+            project_search: ProjectSearch::default(),
+            show_search: false,
+            quick_open: QuickOpen::default(),
+            commands,
+            keymap,
+            command_palette: CommandPalette::default(),
+            vim: VimState::new(),
+            vim_enabled: false,
+            vim_tab: None,
+            settings: SettingsStore::load(None),
+            settings_window: SettingsWindow::default(),
+            applied_settings: None,
+            session: SessionStore::default(),
+            restored_layout: LayoutSession::default(),
+            session_restored: false,
+            swap: SwapFiles::default(),
+            recovery: RecoveryDialog::default(),
+            encoding_picker: EncodingPicker::default(),
+            editor_focused: false,
+            applied_highlight_revision: None,
         }
     }
 }
 
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.settings.reload_if_changed();
+        if self.applied_settings.as_ref().map(|(revision, _)| *revision) != Some(self.settings.revision()) {
+            self.apply_settings();
+        }
+
+        // A sessão anterior volta no primeiro frame, já com as configurações aplicadas
+        if !self.session_restored {
+            self.session_restored = true;
+            if let Some(session) = self.session.load_last() {
+                self.restore_session(ctx, session);
+            }
+            self.find_leftover_swaps();
+        }
+
+        // A interface e os galleys acompanham o tema e as sintaxes: no primeiro frame e a
+        // cada troca ou recarga
+        if self.applied_highlight_revision != Some(self.highlighter.revision()) {
+            self.applied_highlight_revision = Some(self.highlighter.revision());
+            ctx.set_visuals(visuals_for_theme(self.highlighter.current_theme()));
+            for tab in &mut self.open_tabs {
+                tab.galley_cache.clear();
+            }
+        }
+
+        self.dispatch_shortcuts(ctx);
+
         if let Ok(path) = self.picked_folder_rx.try_recv() {
-            self.current_dir = Some(path);
-            self.expanded_dirs.clear();
-            eprintln!("Diretório selecionado: {:?}", self.current_dir);
+            // A sessão do diretório anterior é guardada; a do novo só volta se não houver
+            // abas abertas
+            self.save_session(ctx);
+            self.open_workspace(ctx, path);
+            if self.open_tabs.is_empty()
+                && let Some(session) = self.session.load(self.current_dir.as_deref())
+            {
+                self.restore_session(ctx, session);
+            }
         }
 
-        egui::SidePanel::left("file_explorer_panel")
-            .min_width(SIDE_PANEL_WIDTH)
-            .default_width(SIDE_PANEL_WIDTH)
+        egui::SidePanel::left(EXPLORER_PANEL_ID)
+            .min_width(self.settings.values.side_panel_width)
+            .default_width(self.restored_layout.side_panel_width.unwrap_or(self.settings.values.side_panel_width))
             .show(ctx, |ui| {
                 ui.heading("Explorador de Arquivos");
                 ui.separator();
@@ -139,27 +250,158 @@ impl eframe::App for MyApp {
         // Botão para abrir/fechar o terminal (FR.3.1.1)
         egui::TopBottomPanel::bottom("terminal_panel_toggle").show(ctx, |ui| {
             ui.horizontal(|ui_horizontal| {
-                if ui_horizontal.button("Abrir Terminal").clicked() {
-                    self.terminal.is_open = !self.terminal.is_open;
-                    if self.terminal.is_open {
-                        self.terminal.start(self.current_dir.clone()); // Iniciar o terminal ao abrir (FR.3.2.2)
-                    } else {
-                        self.terminal.stop(); // Parar o terminal ao fechar
+                // Ocultar o painel não encerra as sessões abertas
+                let label = if self.terminals.is_open { "Ocultar Terminal" } else { "Abrir Terminal" };
+                if ui_horizontal.button(label).clicked() {
+                    self.terminals.toggle(ctx, self.current_dir.clone());
+                }
+
+                let mut task_to_run = None;
+                ui_horizontal.menu_button("Tarefas", |ui_menu| {
+                    if self.tasks.tasks_file.tasks.is_empty() {
+                        ui_menu.label("Nenhuma tarefa em .lcode/tasks.toml");
+                    }
+                    for task in &self.tasks.tasks_file.tasks {
+                        let running = self.tasks.is_running(&task.name);
+                        let label = if running { format!("{} (executando)", task.name) } else { task.name.clone() };
+                        if ui_menu.button(label).on_hover_text(&task.command).clicked() {
+                            task_to_run = Some(task.clone());
+                            ui_menu.close_menu();
+                        }
+                    }
+                    ui_menu.separator();
+                    if ui_menu.button("Recarregar tarefas").clicked() {
+                        self.tasks.reload(self.current_dir.as_deref());
+                        ui_menu.close_menu();
+                    }
+                });
+                if let Some(task) = task_to_run {
+                    self.tasks.run(&task, ctx, &mut self.terminals, self.current_dir.as_deref());
+                }
+
+                let problems_label = format!("Problemas ({})", self.tasks.diagnostics.len());
+                if ui_horizontal.selectable_label(self.show_problems, problems_label).clicked() {
+                    self.show_problems = !self.show_problems;
+                }
+                if ui_horizontal.selectable_label(self.show_search, "Buscar em arquivos").clicked() {
+                    self.show_search = !self.show_search;
+                }
+                ui_horizontal.menu_button("Tema", |ui_menu| {
+                    let current = self.highlighter.current_theme_name().to_string();
+                    let mut theme_to_set = None;
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui_menu, |ui_scroll| {
+                        for name in self.highlighter.available_themes() {
+                            if ui_scroll.selectable_label(name == current, &name).clicked() {
+                                theme_to_set = Some(name);
+                            }
+                        }
+                    });
+                    if let Some(name) = theme_to_set {
+                        self.highlighter.set_theme(&name);
+                        ctx.request_repaint();
+                        ui_menu.close_menu();
+                    }
+                    ui_menu.separator();
+                    let themes_dir = user_themes_dir().map(|dir| dir.display().to_string()).unwrap_or_default();
+                    if ui_menu.button("Recarregar temas").on_hover_text(themes_dir).clicked() {
+                        self.highlighter.reload_themes();
+                        ctx.request_repaint();
+                        ui_menu.close_menu();
+                    }
+                    for error in &self.highlighter.theme_errors {
+                        ui_menu.colored_label(ui_menu.visuals().error_fg_color, error);
+                    }
+                });
+
+                if let Some(error) = &self.tasks.error {
+                    ui_horizontal.colored_label(ui_horizontal.visuals().error_fg_color, error);
+                }
+
+                if self.vim_enabled {
+                    let status = match self.vim.mode {
+                        VimMode::CommandLine => format!(":{}", self.vim.command_line),
+                        _ => format!("{} {}", self.vim.mode_label(), self.vim.pending_keys()),
+                    };
+                    ui_horizontal.monospace(status.trim_end());
+                }
+                // Erros nos arquivos de configuração; o clique abre a tela de configurações
+                if !self.settings.errors.is_empty() {
+                    let text = egui::RichText::new(format!("Configurações: {} problema(s)", self.settings.errors.len()))
+                        .color(ui_horizontal.visuals().warn_fg_color);
+                    if ui_horizontal.add(egui::Label::new(text).sense(egui::Sense::click())).on_hover_text(self.settings.errors.join("\n")).clicked() {
+                        self.settings_window.open(&self.settings);
+                    }
+                }
+                if let Some(pending) = self.keymap.pending_label(ctx) {
+                    ui_horizontal.label(format!("({}) pressionado. Aguardando a próxima tecla...", pending));
+                    ctx.request_repaint_after(SEQUENCE_TIMEOUT); // A mensagem some quando a sequência expira
+                }
+                if let Some((encoding, is_modified)) = self.current_tab_mut().map(|tab| (tab.encoding, tab.is_modified)) {
+                    self.show_encoding_menu(ui_horizontal, ctx, encoding, is_modified);
+                    self.show_line_ending_menu(ui_horizontal);
+                }
+                // Erros no arquivo de atalhos e conflitos; o clique abre o arquivo
+                let keymap_problems: Vec<&String> = self.keymap.errors.iter().chain(&self.keymap.conflicts).collect();
+                if !keymap_problems.is_empty() {
+                    let text = egui::RichText::new(format!("Atalhos: {} problema(s)", keymap_problems.len()))
+                        .color(ui_horizontal.visuals().warn_fg_color);
+                    let details = keymap_problems.iter().map(|problem| problem.as_str()).collect::<Vec<_>>().join("\n");
+                    if ui_horizontal.add(egui::Label::new(text).sense(egui::Sense::click())).on_hover_text(details).clicked() {
+                        self.run_command(ctx, "prefs.open_keybindings");
                     }
                 }
             });
         });
 
+        // As sessões continuam processando a saída do shell mesmo com o painel oculto
+        self.terminals.process_output(ctx);
+        self.tasks.poll(&mut self.terminals);
+        self.project_search.poll();
+
         // O painel principal do editor/terminal
         egui::CentralPanel::default().show(ctx, |ui| {
             // Se o terminal estiver aberto, dividimos o espaço
-            if self.terminal.is_open {
-                egui::TopBottomPanel::bottom("integrated_terminal_panel")
+            if self.terminals.is_open {
+                let link_to_open = restored_height(egui::TopBottomPanel::bottom(TERMINAL_PANEL_ID), self.restored_layout.terminal_height)
                     .resizable(true) // FR.3.1.2
                     .min_height(50.0)
                     .show_inside(ui, |ui_terminal| {
-                        self.terminal.ui(ui_terminal, self.current_dir.clone());
-                    });
+                        self.terminals.ui(ui_terminal, self.current_dir.clone())
+                    })
+                    .inner;
+                // Ctrl+clique em um caminho arquivo:linha:coluna na saída do terminal
+                if let Some(location) = link_to_open {
+                    self.open_file_at(&location);
+                }
+            }
+
+            if self.show_problems {
+                let problem_to_open = restored_height(egui::TopBottomPanel::bottom(PROBLEMS_PANEL_ID), self.restored_layout.problems_height)
+                    .resizable(true)
+                    .min_height(50.0)
+                    .show_inside(ui, |ui_problems| {
+                        show_problems_panel(ui_problems, &self.tasks.diagnostics, self.current_dir.as_deref())
+                    })
+                    .inner;
+                if let Some(location) = problem_to_open {
+                    self.open_file_at(&location);
+                }
+            }
+
+            if self.show_search {
+                let search_action = restored_height(egui::TopBottomPanel::bottom(SEARCH_PANEL_ID), self.restored_layout.search_height)
+                    .resizable(true)
+                    .min_height(80.0)
+                    .show_inside(ui, |ui_search| {
+                        show_search_panel(ui_search, &mut self.project_search, self.current_dir.as_deref())
+                    })
+                    .inner;
+                match search_action {
+                    Some(SearchPanelAction::Open(location)) => self.open_file_at(&location),
+                    Some(SearchPanelAction::PreviewReplace) => self.preview_project_replace(),
+                    Some(SearchPanelAction::ApplyReplace) => self.apply_project_replace(ctx),
+                    None => {}
+                }
             }
 
             // O conteúdo do editor principal ocupa o espaço restante do CentralPanel
@@ -212,9 +454,77 @@ impl eframe::App for MyApp {
             // Conteúdo do Editor para a aba selecionada
                         if let Some(selected_idx) = self.selected_tab_idx {
                 if let Some(current_tab) = self.open_tabs.get_mut(selected_idx) {
-                    ui.heading(format!("Editor: {}", current_tab.name()));
+                    ui.horizontal(|ui_header| {
+                        ui_header.heading(format!("Editor: {}", current_tab.name()));
+                        if let Some(error) = &current_tab.file_error {
+                            ui_header.colored_label(ui_header.visuals().error_fg_color, error);
+                            if ui_header.small_button("x").on_hover_text("Ocultar o erro").clicked() {
+                                current_tab.file_error = None;
+                            }
+                        }
+
+                        let syntax_name = self.highlighter.syntax_name_for(&current_tab.path, &current_tab.content);
+                        let is_override = self.highlighter.language_override(&current_tab.path).is_some();
+                        let mut language_to_set = None;
+                        ui_header.menu_button(format!("Linguagem: {}", syntax_name), |ui_menu| {
+                            if ui_menu.selectable_label(!is_override, "Detectar automaticamente").clicked() {
+                                language_to_set = Some(None);
+                            }
+                            ui_menu.separator();
+                            egui::ScrollArea::vertical().max_height(300.0).show(ui_menu, |ui_scroll| {
+                                for name in self.highlighter.syntax_names() {
+                                    if ui_scroll.selectable_label(is_override && name == syntax_name, &name).clicked() {
+                                        language_to_set = Some(Some(name));
+                                    }
+                                }
+                            });
+                            if language_to_set.is_some() {
+                                ui_menu.close_menu();
+                            }
+                            ui_menu.separator();
+                            let syntaxes_dir = user_syntaxes_dir().map(|dir| dir.display().to_string()).unwrap_or_default();
+                            if ui_menu.button("Recarregar sintaxes").on_hover_text(syntaxes_dir).clicked() {
+                                self.highlighter.reload_syntaxes();
+                                ui_menu.close_menu();
+                            }
+                            for error in &self.highlighter.syntax_errors {
+                                ui_menu.colored_label(ui_menu.visuals().error_fg_color, error);
+                            }
+                        });
+                        if let Some(language) = language_to_set {
+                            self.highlighter.set_language_override(&current_tab.path, language);
+                        }
+
+                        // Linguagens com gramática tree-sitter podem trocar de backend de realce
+                        if let Some(language) = self.highlighter.tree_sitter_language(&current_tab.path) {
+                            let current = self.highlighter.backend(language);
+                            let mut selected = current;
+                            egui::ComboBox::from_id_salt("highlight_backend")
+                                .selected_text(selected.label())
+                                .show_ui(ui_header, |ui_combo| {
+                                    for backend in HighlightBackend::ALL {
+                                        ui_combo.selectable_value(&mut selected, backend, backend.label());
+                                    }
+                                })
+                                .response
+                                .on_hover_text(format!("Backend de realce para {}", language.name()));
+                            if selected != current {
+                                self.highlighter.set_backend(language, selected);
+                            }
+                        }
+                    });
+
+                    if current_tab.find.is_open {
+                        let action = current_tab.find.ui(ui);
+                        if action != FindAction::None
+                            && current_tab.find.apply(action, &mut current_tab.content, &mut current_tab.editor_state)
+                        {
+                            current_tab.is_modified = true;
+                        }
+                    }
                     ui.separator();
 
+                    let diagnostics: Vec<_> = self.tasks.diagnostics_for(&current_tab.path).collect();
                     // NOVO: Criar e mostrar o EditorPanel
                     let mut editor_panel = EditorPanel::new(
                         &mut current_tab.content,
@@ -223,55 +533,692 @@ impl eframe::App for MyApp {
                         &self.highlighter,
                         &mut current_tab.is_modified,
                         &mut current_tab.galley_cache, // NOVO
-                        &mut current_tab.last_content_len, // NOVO
-                    );
-                    editor_panel.show(ui);
-
-                    // FR.2.3.2: Salvar arquivos usando Ctrl+S
-                    if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::S)) {
-                        eprintln!("Ctrl+S pressionado.");
-                        if current_tab.is_modified {
-                            self.save_current_tab(ctx);
-                        } else {
-                            eprintln!("Arquivo não modificado, não há o que salvar.");
-                        }
-                    }
-
+                        &mut current_tab.highlight,
+                    )
+                    .with_diagnostics(&diagnostics)
+                    .with_search(current_tab.find.search_query())
+                    .with_block_cursor(self.vim_enabled && self.vim.block_cursor())
+                    .with_settings(&self.settings.values);
+                    self.editor_focused = editor_panel.show(ui);
                 } else {
                     self.selected_tab_idx = None;
                 }
             }
         });
 
+        if let Some(path) = self.quick_open.ui(ctx) {
+            self.open_file(&path);
+        }
+        if let Some(id) = self.command_palette.ui(ctx, &self.commands, &self.keymap) {
+            self.run_command(ctx, id);
+        }
+        let themes = self.highlighter.available_themes();
+        match self.settings_window.ui(ctx, &self.settings, &themes) {
+            Some(SettingsAction::Save(settings)) => {
+                if let Err(e) = self.settings.save_user(&settings) {
+                    eprintln!("Erro ao salvar as configurações: {}", e);
+                    self.settings.errors.push(format!("Erro ao salvar: {}", e));
+                }
+            },
+            Some(SettingsAction::OpenUserFile) => {
+                self.run_command(ctx, "prefs.open_settings_file");
+            },
+            Some(SettingsAction::OpenWorkspaceFile) => {
+                self.run_command(ctx, "prefs.open_workspace_settings");
+            },
+            None => {}
+        }
+
+        let current_encoding = self.current_tab_mut().map(|tab| (tab.encoding, tab.is_modified));
+        match current_encoding {
+            Some((encoding, is_modified)) => {
+                if let Some((mode, chosen)) = self.encoding_picker.ui(ctx, encoding, is_modified) {
+                    self.apply_encoding_choice(ctx, mode, chosen);
+                }
+            },
+            None => self.encoding_picker.mode = None,
+        }
+
+        match self.recovery.ui(ctx) {
+            Some(RecoveryAction::Compare(idx)) => {
+                let path = self.recovery.entries[idx].path.clone();
+                let current = self.file_content(&path).map(String::from).unwrap_or_default();
+                self.recovery.show_comparison(idx, &current);
+            },
+            Some(RecoveryAction::Restore(recovered)) => self.restore_swap(recovered),
+            Some(RecoveryAction::Discard(recovered)) => {
+                if let Err(e) = discard_swap(&recovered.swap_path) {
+                    eprintln!("Erro ao apagar {}: {}", recovered.swap_path.display(), e);
+                }
+            },
+            None => {}
+        }
+
         // Diálogo de confirmação para alterações não salvas (FR.2.3.3)
         if self.show_unsaved_changes_dialog {
             self.draw_unsaved_changes_dialog(ctx);
         }
+
+        if self.swap.is_due() {
+            let dirty = dirty_buffers(&self.open_tabs);
+            self.swap.snapshot(&dirty);
+        }
+        // Hot exit: ao fechar a janela, as abas não salvas ficam no backup da sessão e os
+        // arquivos de swap, que só servem para quedas, são apagados
+        let closing = ctx.input(|i| i.viewport().close_requested());
+        if closing || self.session.is_due() {
+            self.save_session(ctx);
+        }
+        if closing {
+            self.session.shutdown();
+            self.swap.shutdown();
+        }
+    }
+}
+
+/// Abas com alterações não salvas, para os backups e os arquivos de swap.
+fn dirty_buffers(tabs: &[EditorTab]) -> Vec<DirtyBuffer<'_>> {
+    tabs.iter()
+        .filter(|tab| tab.is_modified)
+        .map(|tab| DirtyBuffer { path: &tab.path, content: &tab.content, version: tab.editor_state.version() })
+        .collect()
+}
+
+/// Usa a altura da sessão restaurada como altura inicial do painel.
+fn restored_height(panel: egui::TopBottomPanel, height: Option<f32>) -> egui::TopBottomPanel {
+    match height {
+        Some(height) => panel.default_height(height),
+        None => panel,
     }
 }
 
 // Métodos auxiliares para MyApp
 impl MyApp {
-    // Nova função para salvar a aba atualmente selecionada
-    fn save_current_tab(&mut self, ctx: &egui::Context) {
-        if let Some(selected_idx) = self.selected_tab_idx {
-            if let Some(current_tab) = self.open_tabs.get_mut(selected_idx) {
-                eprintln!("Salvando arquivo: {}", current_tab.path.display());
-                match file_handler::save_rope_to_file(&current_tab.path, &current_tab.content) {
-                    Ok(_) => {
-                        current_tab.is_modified = false;
-                        eprintln!("Arquivo salvo com sucesso!");
-                        ctx.request_repaint(); // Força a UI a atualizar para remover o '*'
-                    },
-                    Err(e) => {
-                        eprintln!("Erro ao salvar arquivo: {}", e);
-                        // TODO: Exibir erro para o usuário na UI
+    /// Executa um comando do registro pelo id (atalhos, paleta ou scripts). Retorna
+    /// `false` se o comando não existe.
+    pub fn run_command(&mut self, ctx: &egui::Context, id: &str) -> bool {
+        let Some(handler) = self.commands.get(id).map(|command| command.handler) else {
+            eprintln!("Comando desconhecido: {}", id);
+            return false;
+        };
+        eprintln!("Executando comando: {}", id);
+        handler(self, ctx);
+        ctx.request_repaint();
+        true
+    }
+
+    /// Executa os comandos cujos atalhos foram pressionados neste frame, consumindo as
+    /// teclas usadas. Uma tecla que completa ou continua uma sequência também descarta o
+    /// texto que ela geraria (o "c" de Ctrl+K C não chega ao editor).
+    ///
+    /// Com o modo Vim ativo e o foco no editor, o texto e as teclas de edição vão antes
+    /// para o Vim; atalhos com Ctrl continuam passando pelo keymap.
+    fn dispatch_shortcuts(&mut self, ctx: &egui::Context) {
+        self.keymap.reload_if_changed(&self.commands);
+        let focus = FocusState {
+            editor: self.editor_focused && self.selected_tab_idx.is_some(),
+            terminal: self.terminals.has_focus(ctx),
+            find_bar: self.selected_tab_idx
+                .and_then(|idx| self.open_tabs.get(idx))
+                .is_some_and(|tab| tab.find.has_focus(ctx)),
+        };
+
+        let vim_tab = self.selected_tab_idx.filter(|_| self.vim_enabled);
+        if vim_tab != self.vim_tab {
+            // O que estava em andamento na aba anterior não continua na nova
+            if let Some(tab) = self.vim_tab.and_then(|idx| self.open_tabs.get_mut(idx)) {
+                self.vim.reset(&mut tab.editor_state);
+            }
+            self.vim_tab = vim_tab;
+        }
+
+        let events = ctx.input(|i| i.events.clone());
+        let mut consumed = vec![false; events.len()];
+        let mut triggered = Vec::new();
+        let mut ex_commands = Vec::new();
+        let mut swallow_text = false;
+        for (idx, event) in events.iter().enumerate() {
+            if focus.editor
+                && let Some(tab) = vim_tab.and_then(|tab_idx| self.open_tabs.get_mut(tab_idx))
+                && let Some(keys) = vim_keys_for_event(event, self.vim.mode)
+            {
+                for key in keys {
+                    let effect = self.vim.handle_key(key, &mut tab.editor_state, &mut tab.content);
+                    tab.is_modified |= effect.modified;
+                    ex_commands.extend(effect.command);
+                }
+                consumed[idx] = true;
+                continue;
+            }
+            match event {
+                egui::Event::Key { pressed: true, .. } | egui::Event::Copy | egui::Event::Cut => {
+                    swallow_text = false;
+                    match self.keymap.press_event(event, focus) {
+                        KeyPress::Run(id) => triggered.push(id),
+                        KeyPress::Pending => {},
+                        KeyPress::Unbound => {
+                            eprintln!("A sequência de teclas não corresponde a nenhum comando.");
+                            continue;
+                        },
+                        KeyPress::Ignored => continue,
                     }
+                    consumed[idx] = true;
+                    swallow_text = true;
+                },
+                egui::Event::Text(_) if swallow_text => {
+                    consumed[idx] = true;
+                    swallow_text = false;
+                },
+                _ => {},
+            }
+        }
+
+        if consumed.contains(&true) {
+            ctx.input_mut(|i| {
+                let mut idx = 0;
+                i.events.retain(|_| {
+                    idx += 1;
+                    !consumed[idx - 1]
+                });
+            });
+        }
+        for id in triggered {
+            self.run_command(ctx, id);
+        }
+        for command in ex_commands {
+            self.run_ex_command(ctx, command);
+        }
+    }
+
+    /// `:w`, `:q`, `:q!`, `:wq` do modo Vim, na aba selecionada. `:q` com alterações
+    /// não salvas abre o mesmo diálogo do botão de fechar.
+    fn run_ex_command(&mut self, ctx: &egui::Context, command: ExCommand) {
+        let Some(idx) = self.selected_tab_idx else {
+            return;
+        };
+        let is_modified = self.open_tabs.get(idx).is_some_and(|tab| tab.is_modified);
+        match command {
+            ExCommand::Save => {
+                self.save_current_tab(ctx);
+            }
+            ExCommand::Close { force: false } if is_modified => {
+                self.show_unsaved_changes_dialog = true;
+                self.dialog_tab_idx_to_close = Some(idx);
+            }
+            ExCommand::Close { .. } => self.close_tab(idx),
+            ExCommand::SaveAndClose => {
+                if is_modified {
+                    self.save_current_tab(ctx);
+                }
+                if !self.open_tabs.get(idx).is_some_and(|tab| tab.is_modified) {
+                    self.close_tab(idx);
                 }
             }
         }
     }
 
+    /// Aplica as configurações recém-lidas. Tema e modo Vim só mudam quando a
+    /// configuração deles muda, para não desfazer as escolhas feitas pelos menus.
+    fn apply_settings(&mut self) {
+        let values = self.settings.values.clone();
+        let previous = self.applied_settings.replace((self.settings.revision(), values.clone())).map(|(_, settings)| settings);
+
+        if !self.highlighter.available_themes().contains(&values.theme) {
+            self.settings.errors.push(format!("theme: tema '{}' não encontrado", values.theme));
+        } else if previous.as_ref().is_none_or(|previous| previous.theme != values.theme) {
+            self.highlighter.set_theme(&values.theme);
+        }
+        if previous.as_ref().is_none_or(|previous| previous.vim_mode != values.vim_mode) {
+            self.vim_enabled = values.vim_mode;
+            if let Some(tab) = self.selected_tab_idx.and_then(|idx| self.open_tabs.get_mut(idx)) {
+                self.vim.reset(&mut tab.editor_state);
+            }
+        }
+        self.terminals.set_scrollback_lines(values.terminal_scrollback);
+        // Fonte e altura de linha mudam o desenho de todas as linhas
+        for tab in &mut self.open_tabs {
+            tab.galley_cache.clear();
+        }
+    }
+
+    /// Abre o diretório no explorador: índice do Ctrl+P, tarefas e configurações do projeto.
+    fn open_workspace(&mut self, ctx: &egui::Context, path: PathBuf) {
+        self.quick_open.index(ctx, &path);
+        self.current_dir = Some(path);
+        self.expanded_dirs.clear();
+        self.tasks.reload(self.current_dir.as_deref());
+        self.settings.set_workspace(self.current_dir.as_deref());
+        eprintln!("Diretório selecionado: {:?}", self.current_dir);
+    }
+
+    /// Estado atual para a sessão do diretório aberto.
+    fn current_session(&self, ctx: &egui::Context) -> Session {
+        let panel_size = |id: &str| egui::containers::panel::PanelState::load(ctx, egui::Id::new(id)).map(|state| state.size());
+        let mut expanded_dirs: Vec<PathBuf> = self.expanded_dirs.iter()
+            .filter(|(_, expanded)| **expanded)
+            .map(|(path, _)| path.clone())
+            .collect();
+        expanded_dirs.sort();
+
+        Session {
+            workspace: self.current_dir.clone(),
+            tabs: self.open_tabs.iter()
+                .map(|tab| TabSession {
+                    path: tab.path.clone(),
+                    cursor: tab.editor_state.cursor,
+                    selection: tab.editor_state.selection,
+                    scroll_offset: [tab.editor_state.scroll_offset.x, tab.editor_state.scroll_offset.y],
+                    has_backup: tab.is_modified,
+                    encoding: Some(tab.encoding.encoding.name().to_string()),
+                    bom: tab.encoding.bom,
+                })
+                .collect(),
+            selected_tab: self.selected_tab_idx,
+            expanded_dirs,
+            layout: LayoutSession {
+                side_panel_width: panel_size(EXPLORER_PANEL_ID).map(|size| size.x).or(self.restored_layout.side_panel_width),
+                terminal_open: self.terminals.is_open,
+                terminal_height: panel_size(TERMINAL_PANEL_ID).map(|size| size.y).or(self.restored_layout.terminal_height),
+                show_problems: self.show_problems,
+                problems_height: panel_size(PROBLEMS_PANEL_ID).map(|size| size.y).or(self.restored_layout.problems_height),
+                show_search: self.show_search,
+                search_height: panel_size(SEARCH_PANEL_ID).map(|size| size.y).or(self.restored_layout.search_height),
+            },
+        }
+    }
+
+    /// Grava a sessão e os backups das abas com alterações não salvas.
+    fn save_session(&mut self, ctx: &egui::Context) {
+        let session = self.current_session(ctx);
+        let dirty = dirty_buffers(&self.open_tabs);
+        if let Err(e) = self.session.save(&session, &dirty) {
+            eprintln!("Erro ao gravar a sessão: {}", e);
+        }
+    }
+
+    /// Procura swaps de uma execução que caiu. Os que já batem com o conteúdo atual
+    /// (restaurado pelo backup da sessão ou salvo no disco) são apagados; os demais vão
+    /// para o diálogo de recuperação.
+    fn find_leftover_swaps(&mut self) {
+        for recovered in self.swap.find_leftovers() {
+            if self.file_content(&recovered.path).is_ok_and(|content| content == recovered.content.as_str()) {
+                let _ = discard_swap(&recovered.swap_path);
+                continue;
+            }
+            eprintln!("Alterações recuperáveis encontradas para {}", recovered.path.display());
+            self.recovery.entries.push(recovered);
+        }
+    }
+
+    /// Substitui o conteúdo da aba do arquivo pelo recuperado (a troca pode ser desfeita)
+    /// e apaga o swap. Um arquivo que não existe mais volta como aba nova.
+    fn restore_swap(&mut self, recovered: RecoveredSwap) {
+        let existing = self.open_tabs.iter().position(|tab| tab.path == recovered.path);
+        let idx = match existing {
+            Some(idx) => Some(idx),
+            None if recovered.path.exists() => self.open_file(&recovered.path),
+            None => None,
+        };
+        match idx.and_then(|idx| self.open_tabs.get_mut(idx).map(|tab| (idx, tab))) {
+            Some((idx, tab)) => {
+                let whole = 0..tab.content.len_chars();
+                tab.editor_state.replace_ranges(&mut tab.content, &[(whole, recovered.content)]);
+                tab.is_modified = true;
+                self.selected_tab_idx = Some(idx);
+            },
+            None => {
+                let mut tab = EditorTab::new(recovered.path.clone(), Rope::from(recovered.content.as_str()));
+                tab.is_modified = true;
+                self.open_tabs.push(tab);
+                self.selected_tab_idx = Some(self.open_tabs.len() - 1);
+            },
+        }
+        if let Err(e) = discard_swap(&recovered.swap_path) {
+            eprintln!("Erro ao apagar {}: {}", recovered.swap_path.display(), e);
+        }
+        eprintln!("Alterações de {} restauradas.", recovered.path.display());
+    }
+
+    /// Reabre o diretório, as abas (com as alterações não salvas do backup) e os painéis
+    /// de uma sessão anterior. Arquivos que não existem mais e não têm backup são pulados.
+    fn restore_session(&mut self, ctx: &egui::Context, session: Session) {
+        if let Some(dir) = session.workspace.clone()
+            && self.current_dir.as_ref() != Some(&dir)
+            && dir.is_dir()
+        {
+            self.open_workspace(ctx, dir);
+        }
+        self.expanded_dirs = session.expanded_dirs.into_iter().map(|dir| (dir, true)).collect();
+
+        let mut restored_indices = Vec::with_capacity(session.tabs.len());
+        for tab_session in &session.tabs {
+            restored_indices.push(self.restore_tab(session.workspace.as_deref(), tab_session));
+        }
+        // O índice salvo é o da aba na sessão; abas puladas mudam a posição das seguintes
+        if let Some(idx) = session.selected_tab.and_then(|selected| restored_indices.get(selected).copied().flatten()) {
+            self.selected_tab_idx = Some(idx);
+        }
+
+        self.restored_layout = session.layout;
+        self.show_problems = self.restored_layout.show_problems;
+        self.show_search = self.restored_layout.show_search;
+        if self.restored_layout.terminal_open && !self.terminals.is_open {
+            self.terminals.toggle(ctx, self.current_dir.clone());
+        }
+        eprintln!("Sessão restaurada: {} aba(s) em {:?}", self.open_tabs.len(), self.current_dir);
+    }
+
+    fn restore_tab(&mut self, workspace: Option<&Path>, tab_session: &TabSession) -> Option<usize> {
+        let already_open = self.open_tabs.iter().any(|tab| tab.path == tab_session.path);
+        let backup = if tab_session.has_backup && !already_open {
+            self.session.read_backup(workspace, &tab_session.path)
+                .map_err(|e| eprintln!("Backup de '{}' não encontrado: {}", tab_session.path.display(), e))
+                .ok()
+        } else {
+            None
+        };
+        let encoding = tab_session.encoding.as_deref().and_then(|name| FileEncoding::for_name(name, tab_session.bom));
+        let idx = match backup {
+            Some(text) => {
+                let mut tab = EditorTab::new(tab_session.path.clone(), Rope::from(text));
+                tab.is_modified = true;
+                tab.encoding = encoding.unwrap_or_default();
+                self.open_tabs.push(tab);
+                self.selected_tab_idx = Some(self.open_tabs.len() - 1);
+                self.open_tabs.len() - 1
+            },
+            None => {
+                let idx = self.open_file(&tab_session.path)?;
+                // Uma codificação escolhida pelo usuário vale mais que o palpite da
+                // detecção; arquivos com BOM são lidos sempre pelo BOM
+                let detected = self.open_tabs[idx].encoding;
+                if !already_open
+                    && !detected.bom
+                    && let Some(encoding) = encoding.filter(|encoding| *encoding != detected)
+                    && let Err(e) = self.reopen_tab_with_encoding(idx, encoding)
+                {
+                    eprintln!("Erro ao reabrir '{}' como {}: {}", tab_session.path.display(), encoding.label(), e);
+                }
+                idx
+            },
+        };
+        let tab = &mut self.open_tabs[idx];
+        tab.editor_state.restore_cursor(&tab.content, tab_session.cursor, tab_session.selection);
+        tab.editor_state.scroll_offset = egui::vec2(tab_session.scroll_offset[0], tab_session.scroll_offset[1]);
+        Some(idx)
+    }
+
+    /// Aba selecionada, se houver.
+    pub fn current_tab_mut(&mut self) -> Option<&mut EditorTab> {
+        self.open_tabs.get_mut(self.selected_tab_idx?)
+    }
+
+    /// Abre o arquivo em uma nova aba, ou foca a aba existente. Retorna o índice da aba.
+    pub fn open_file(&mut self, path: &Path) -> Option<usize> {
+        self.quick_open.note_opened(path);
+        if let Some(idx) = self.open_tabs.iter().position(|tab| tab.path == path) {
+            self.selected_tab_idx = Some(idx);
+            eprintln!("Arquivo '{}' já aberto, focando na aba existente.", path.display());
+            return Some(idx);
+        }
+        match file_handler::load_file_into_rope(path, self.settings.values.mmap_threshold) {
+            Ok(loaded) => {
+                let warning = loaded.lossy_warning(path);
+                let mut new_tab = EditorTab::new(path.to_path_buf(), loaded.content);
+                new_tab.encoding = loaded.encoding;
+                new_tab.file_error = warning;
+                self.open_tabs.push(new_tab);
+                self.selected_tab_idx = Some(self.open_tabs.len() - 1);
+                eprintln!("Arquivo '{}' carregado e nova aba criada.", path.display());
+                Some(self.open_tabs.len() - 1)
+            },
+            Err(e) => {
+                eprintln!("Erro ao carregar o arquivo '{}': {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Abre o arquivo e posiciona o cursor na linha/coluna indicadas (base 1).
+    pub fn open_file_at(&mut self, location: &FileLocation) {
+        let Some(idx) = self.open_file(&location.path) else {
+            return;
+        };
+        let tab = &mut self.open_tabs[idx];
+        let line = location.line.unwrap_or(1).saturating_sub(1);
+        let column = location.column.unwrap_or(1).saturating_sub(1);
+        tab.editor_state.go_to(&tab.content, line, column);
+    }
+
+    /// Conteúdo atual de um arquivo: o da aba, se ele estiver aberto, ou o do disco.
+    fn file_content(&self, path: &Path) -> std::io::Result<Rope> {
+        match self.open_tabs.iter().find(|tab| tab.path == path) {
+            Some(tab) => Ok(tab.content.clone()),
+            None => file_handler::load_file_into_rope(path, self.settings.values.mmap_threshold).map(|loaded| loaded.content),
+        }
+    }
+
+    /// Calcula a prévia da substituição nos arquivos marcados da busca no projeto.
+    fn preview_project_replace(&mut self) {
+        let replacement = self.project_search.replacement.clone();
+        let mut files = Vec::new();
+        for path in self.project_search.selected_paths() {
+            match self.file_content(&path) {
+                Ok(content) => {
+                    let changes = self.project_search.preview_changes(&content, &replacement);
+                    if !changes.is_empty() {
+                        files.push(FilePreview { path, changes });
+                    }
+                },
+                Err(e) => eprintln!("Erro ao ler '{}': {}", path.display(), e),
+            }
+        }
+        self.project_search.preview = Some(ReplacePreview { replacement, files });
+    }
+
+    /// Aplica a substituição nos arquivos marcados. Arquivos abertos são alterados na
+    /// aba (e podem ser desfeitos); os demais são gravados direto no disco. O texto de
+    /// substituição é o da prévia confirmada. Depois a busca é refeita.
+    fn apply_project_replace(&mut self, ctx: &egui::Context) {
+        let Some(ReplacePreview { replacement, .. }) = self.project_search.preview.take() else {
+            return;
+        };
+        let mut errors = Vec::new();
+        for path in self.project_search.selected_paths() {
+            if let Some(tab) = self.open_tabs.iter_mut().find(|tab| tab.path == path) {
+                let replacements = self.project_search.replacements_in(&tab.content, &replacement);
+                if !replacements.is_empty() {
+                    tab.editor_state.replace_ranges(&mut tab.content, &replacements);
+                    tab.is_modified = true;
+                }
+                continue;
+            }
+            // O arquivo é gravado de volta na codificação em que foi lido; se a leitura
+            // perdeu bytes, ele não é gravado
+            let result = file_handler::load_file_into_rope(&path, self.settings.values.mmap_threshold).and_then(|mut loaded| {
+                let replacements = self.project_search.replacements_in(&loaded.content, &replacement);
+                if replacements.is_empty() {
+                    return Ok(());
+                }
+                if let Some(warning) = loaded.lossy_warning(&path) {
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, warning));
+                }
+                apply_replacements(&mut loaded.content, &replacements);
+                file_handler::save_rope_to_file(&path, &loaded.content, loaded.encoding, None)
+            });
+            if let Err(e) = result {
+                errors.push(format!("{}: {}", path.display(), e));
+            }
+        }
+
+        if let Some(dir) = self.current_dir.clone() {
+            self.project_search.start(ctx, &dir);
+        }
+        if !errors.is_empty() {
+            eprintln!("Erros na substituição: {:?}", errors);
+            self.project_search.error = Some(errors.join("\n"));
+        }
+    }
+
+    /// Salva a aba selecionada. Um erro fica na aba, para o cabeçalho do editor mostrar.
+    /// Quebras de linha diferentes da aba (de um arquivo misto ou de texto colado) são
+    /// convertidas na gravação, para o arquivo sair com um fim de linha só; o texto da
+    /// aba não muda. Retorna se o arquivo foi salvo.
+    pub fn save_current_tab(&mut self, ctx: &egui::Context) -> bool {
+        let Some(current_tab) = self.current_tab_mut() else {
+            return false;
+        };
+        eprintln!("Salvando arquivo: {}", current_tab.path.display());
+        ctx.request_repaint(); // Força a UI a atualizar o '*' ou mostrar o erro
+        let line_ending = Some(current_tab.editor_state.line_ending);
+        match file_handler::save_rope_to_file(&current_tab.path, &current_tab.content, current_tab.encoding, line_ending) {
+            Ok(_) => {
+                current_tab.is_modified = false;
+                current_tab.mixed_line_endings = false;
+                current_tab.file_error = None;
+                eprintln!("Arquivo salvo com sucesso!");
+                true
+            },
+            Err(e) => {
+                eprintln!("Erro ao salvar arquivo: {}", e);
+                current_tab.file_error = Some(format!("Não foi possível salvar {}: {}", current_tab.path.display(), e));
+                false
+            }
+        }
+    }
+
+    /// Converte a aba selecionada para outra codificação e salva. Se a gravação falhar
+    /// (por exemplo, um caractere que não existe na codificação), a aba continua com a
+    /// codificação anterior.
+    pub fn save_current_tab_with_encoding(&mut self, ctx: &egui::Context, encoding: FileEncoding) -> bool {
+        let Some(current_tab) = self.current_tab_mut() else {
+            return false;
+        };
+        let previous = std::mem::replace(&mut current_tab.encoding, encoding);
+        let saved = self.save_current_tab(ctx);
+        if !saved && let Some(current_tab) = self.current_tab_mut() {
+            current_tab.encoding = previous;
+        }
+        saved
+    }
+
+    /// Lê de novo o arquivo da aba selecionada com a codificação escolhida, para os
+    /// arquivos em que a detecção errou. Abas com alterações não salvas não são relidas.
+    /// Recusas e erros ficam na aba, para o cabeçalho do editor mostrar.
+    pub fn reopen_current_tab_with_encoding(&mut self, encoding: FileEncoding) {
+        let Some(idx) = self.selected_tab_idx else {
+            return;
+        };
+        if self.open_tabs[idx].is_modified {
+            let tab = &mut self.open_tabs[idx];
+            tab.file_error = Some(format!(
+                "{} tem alterações não salvas; salve ou desfaça antes de reabrir como {}",
+                tab.path.display(),
+                encoding.label(),
+            ));
+            return;
+        }
+        if let Err(e) = self.reopen_tab_with_encoding(idx, encoding) {
+            let tab = &mut self.open_tabs[idx];
+            eprintln!("Erro ao reabrir '{}': {}", tab.path.display(), e);
+            tab.file_error = Some(format!("Não foi possível reabrir {} como {}: {}", tab.path.display(), encoding.label(), e));
+        }
+    }
+
+    /// Troca o conteúdo da aba pelo arquivo lido com `encoding`, mantendo o cursor e a
+    /// rolagem.
+    fn reopen_tab_with_encoding(&mut self, idx: usize, encoding: FileEncoding) -> std::io::Result<()> {
+        let mmap_threshold = self.settings.values.mmap_threshold;
+        let tab = &mut self.open_tabs[idx];
+        let loaded = file_handler::load_file_with_encoding(&tab.path, mmap_threshold, encoding)?;
+        let warning = loaded.lossy_warning(&tab.path);
+        let mut reopened = EditorTab::new(tab.path.clone(), loaded.content);
+        reopened.encoding = encoding;
+        reopened.file_error = warning;
+        reopened.editor_state.restore_cursor(&reopened.content, tab.editor_state.cursor, tab.editor_state.selection);
+        reopened.editor_state.scroll_offset = tab.editor_state.scroll_offset;
+        *tab = reopened;
+        eprintln!("'{}' reaberto como {}.", tab.path.display(), encoding.label());
+        Ok(())
+    }
+
+    fn apply_encoding_choice(&mut self, ctx: &egui::Context, mode: EncodingPickerMode, encoding: FileEncoding) {
+        match mode {
+            EncodingPickerMode::Reopen => self.reopen_current_tab_with_encoding(encoding),
+            EncodingPickerMode::Save => {
+                self.save_current_tab_with_encoding(ctx, encoding);
+            },
+        }
+    }
+
+    /// Codificação da aba selecionada na barra de status; o menu reabre ou salva o
+    /// arquivo em outra codificação.
+    fn show_encoding_menu(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, encoding: FileEncoding, is_modified: bool) {
+        let mut choice = None;
+        ui.menu_button(encoding.label(), |ui_menu| {
+            ui_menu.add_enabled_ui(!is_modified, |ui_enabled| {
+                ui_enabled.menu_button("Reabrir com codificação", |ui_submenu| {
+                    if let Some(chosen) = encoding_list(ui_submenu, encoding) {
+                        choice = Some((EncodingPickerMode::Reopen, chosen));
+                    }
+                });
+            }).response.on_disabled_hover_text("Salve ou desfaça as alterações antes de reabrir o arquivo.");
+            ui_menu.menu_button("Salvar com codificação", |ui_submenu| {
+                if let Some(chosen) = encoding_list(ui_submenu, encoding) {
+                    choice = Some((EncodingPickerMode::Save, chosen));
+                }
+            });
+            if choice.is_some() {
+                ui_menu.close_menu();
+            }
+        }).response.on_hover_text("Codificação do arquivo");
+        if let Some((mode, chosen)) = choice {
+            self.apply_encoding_choice(ctx, mode, chosen);
+        }
+    }
+
+    /// Converte todas as quebras de linha da aba selecionada para `line_ending`, que
+    /// passa a ser usado nas linhas novas. A conversão pode ser desfeita.
+    pub fn convert_current_tab_line_endings(&mut self, line_ending: LineEnding) {
+        let Some(current_tab) = self.current_tab_mut() else {
+            return;
+        };
+        let replacements = line_ending_replacements(&current_tab.content, line_ending);
+        if !replacements.is_empty() {
+            current_tab.editor_state.replace_ranges(&mut current_tab.content, &replacements);
+            current_tab.is_modified = true;
+        }
+        current_tab.editor_state.line_ending = line_ending;
+        current_tab.mixed_line_endings = false;
+        eprintln!("{} linha(s) convertida(s) para {}.", replacements.len(), line_ending.label());
+    }
+
+    /// Fim de linha da aba selecionada na barra de status; o menu converte o arquivo.
+    fn show_line_ending_menu(&mut self, ui: &mut egui::Ui) {
+        let Some(current_tab) = self.current_tab_mut() else {
+            return;
+        };
+        let current = current_tab.editor_state.line_ending;
+        let (label, hover) = if current_tab.mixed_line_endings {
+            (format!("{} (misto)", current.label()), "O arquivo mistura fins de linha; ao salvar, todos viram o indicado aqui")
+        } else {
+            (current.label().to_string(), "Fim de linha do arquivo")
+        };
+        let mut chosen = None;
+        ui.menu_button(label, |ui_menu| {
+            ui_menu.label("Converter fins de linha para:");
+            for line_ending in LineEnding::ALL {
+                if ui_menu.selectable_label(line_ending == current, line_ending.label()).clicked() {
+                    chosen = Some(line_ending);
+                    ui_menu.close_menu();
+                }
+            }
+        }).response.on_hover_text(hover);
+        if let Some(line_ending) = chosen {
+            self.convert_current_tab_line_endings(line_ending);
+        }
+    }
+
     // Nova função para fechar uma aba pelo índice
     fn close_tab(&mut self, idx_to_close: usize) {
         self.open_tabs.remove(idx_to_close);
@@ -301,8 +1248,10 @@ impl MyApp {
                     if ui_buttons.button("Salvar").clicked() {
                         if let Some(idx) = self.dialog_tab_idx_to_close {
                             self.selected_tab_idx = Some(idx); // Seleciona a aba para salvá-la
-                            self.save_current_tab(ctx);
-                            self.close_tab(idx); // Fecha após salvar
+                            // Se a gravação falhar a aba fica aberta, com o erro no cabeçalho
+                            if self.save_current_tab(ctx) {
+                                self.close_tab(idx); // Fecha após salvar
+                            }
                         }
                         open = false;
                     }
@@ -324,66 +1273,30 @@ impl MyApp {
     }
 }
 
-fn apply_string_diff_to_rope(
-    rope: &mut Rope,
-    old_s: &str,
-    new_s: &str,
-) {
-    let mut common_prefix_len = 0;
-    for (old_char, new_char) in old_s.chars().zip(new_s.chars()) {
-        if old_char == new_char {
-            common_prefix_len += old_char.len_utf8();
-        } else {
-            break;
-        }
-    }
-
-    let mut common_suffix_len = 0;
-    for (old_char, new_char) in old_s.chars().rev().zip(new_s.chars().rev()) {
-        if old_char == new_char {
-            common_suffix_len += old_char.len_utf8();
-        } else {
-            break;
+/// Teclas do Vim geradas por um evento, ou `None` se o evento não é dele (atalhos com
+/// Ctrl, teclas de função...). Ctrl+R só é do Vim fora do modo de inserção.
+fn vim_keys_for_event(event: &egui::Event, mode: VimMode) -> Option<Vec<VimKey>> {
+    match event {
+        egui::Event::Text(text) => Some(text.chars().filter(|ch| !ch.is_control()).map(VimKey::Char).collect()),
+        egui::Event::Key { key, pressed, modifiers, .. } => {
+            if modifiers.command || modifiers.ctrl {
+                return (*key == egui::Key::R && *pressed && mode != VimMode::Insert).then(|| vec![VimKey::Ctrl('r')]);
+            }
+            let vim_key = match key {
+                egui::Key::Escape => VimKey::Escape,
+                egui::Key::Enter => VimKey::Enter,
+                egui::Key::Backspace => VimKey::Backspace,
+                egui::Key::Delete => VimKey::Delete,
+                egui::Key::ArrowLeft => VimKey::Left,
+                egui::Key::ArrowRight => VimKey::Right,
+                egui::Key::ArrowUp => VimKey::Up,
+                egui::Key::ArrowDown => VimKey::Down,
+                _ => return None,
+            };
+            // A tecla solta também é consumida, sem gerar outra tecla
+            Some(if *pressed { vec![vim_key] } else { Vec::new() })
         }
+        _ => None,
     }
+}
 
-    if common_prefix_len + common_suffix_len > old_s.len().min(new_s.len()) {
-        return;
-    }
-
-    // Calcular os índices de byte da parte alterada no old_s
-    let old_diff_start_byte = common_prefix_len;
-    let old_diff_end_byte = old_s.len() - common_suffix_len;
-
-    // Calcular os índices de byte da parte alterada no new_s
-    let new_diff_start_byte = common_prefix_len;
-    let new_diff_end_byte = new_s.len() - common_suffix_len;
-
-    // Converter índices de byte para índices de caractere para o Rope
-    let old_diff_start_char = rope.byte_to_char(old_diff_start_byte);
-    let old_diff_end_char = rope.byte_to_char(old_diff_end_byte);
-
-    // A parte que foi removida (se houver)
-    // Garanta que old_diff_start_char <= old_diff_end_char para evitar overflow
-    if old_diff_start_char < old_diff_end_char { // Modificado para evitar overflow
-        rope.remove(old_diff_start_char..old_diff_end_char);
-        eprintln!("Removidos {} chars a partir do byte {}", old_diff_end_char - old_diff_start_char, old_diff_start_char);
-    }
-    // else { // Debugging:
-    //     eprintln!("Não removeu: start_char {} >= end_char {}", old_diff_start_char, old_diff_end_char);
-    //     eprintln!("old_s len: {}, new_s len: {}", old_s.len(), new_s.len());
-    //     eprintln!("common_prefix_len: {}, common_suffix_len: {}", common_prefix_len, common_suffix_len);
-    //     eprintln!("old_diff_start_byte: {}, old_diff_end_byte: {}", old_diff_start_byte, old_diff_end_byte);
-    // }
-
-
-    // A parte que foi inserida (se houver)
-    let chars_to_insert = &new_s[new_diff_start_byte..new_diff_end_byte];
-
-    if !chars_to_insert.is_empty() {
-        // A inserção ocorre no `old_diff_start_char` porque é onde a parte removida começaria
-        // ou onde a nova parte deve ser inserida se nada foi removido mas algo foi adicionado.
-        rope.insert(old_diff_start_char, chars_to_insert);
-        eprintln!("Inseridos '{}' a partir do byte {}", chars_to_insert, old_diff_start_byte);
-    }
-}
\ No newline at end of file
@@ -0,0 +1,130 @@
+// src/ui/command_palette.rs
+
+use eframe::egui;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+
+use crate::commands::keymap::Keymap;
+use crate::commands::registry::CommandRegistry;
+
+/// Paleta de comandos (Ctrl+Shift+P): lista os comandos registrados, filtrados pela
+/// busca aproximada no título e no id, com o atalho de cada um no keymap.
+#[derive(Debug, Default)]
+pub struct CommandPalette {
+    pub is_open: bool,
+    query: String,
+    selected: usize,
+    request_focus: bool,
+}
+
+impl CommandPalette {
+    pub fn toggle(&mut self) {
+        if self.is_open {
+            self.is_open = false;
+        } else {
+            self.is_open = true;
+            self.query.clear();
+            self.selected = 0;
+            self.request_focus = true;
+        }
+    }
+
+    /// Desenha a paleta por cima do editor. Retorna o id do comando escolhido.
+    pub fn ui(&mut self, ctx: &egui::Context, registry: &CommandRegistry, keymap: &Keymap) -> Option<&'static str> {
+        if !self.is_open {
+            return None;
+        }
+
+        // As setas e o Enter são tratados aqui, antes que o campo de texto os use
+        let (up, down, enter, escape) = ctx.input_mut(|i| (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+        ));
+        if escape {
+            self.is_open = false;
+            return None;
+        }
+
+        let mut chosen = None;
+        egui::Window::new("Paleta de comandos")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 40.0))
+            .fixed_size(egui::vec2(520.0, 0.0))
+            .show(ctx, |ui| {
+                let previous_query = self.query.clone();
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("Digite o nome de um comando..."),
+                );
+                if self.request_focus {
+                    response.request_focus();
+                    self.request_focus = false;
+                }
+                if self.query != previous_query {
+                    self.selected = 0;
+                }
+
+                let matcher = SkimMatcherV2::default().ignore_case();
+                let query = self.query.trim();
+                let mut ranked: Vec<(i64, usize)> = registry.all().iter().enumerate()
+                    .filter_map(|(idx, command)| {
+                        if query.is_empty() {
+                            return Some((0, idx));
+                        }
+                        let by_title = matcher.fuzzy_match(command.title, query);
+                        let by_id = matcher.fuzzy_match(command.id, query);
+                        Some((by_title.max(by_id)?, idx))
+                    })
+                    .collect();
+                // Sem consulta, a ordem do registro (agrupada por área) é mantida
+                ranked.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+                if ranked.is_empty() {
+                    ui.weak("Nenhum comando encontrado.");
+                    return;
+                }
+                self.selected = self.selected.min(ranked.len() - 1);
+                if up {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+                if down && self.selected + 1 < ranked.len() {
+                    self.selected += 1;
+                }
+
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui_scroll| {
+                    for (position, (_, idx)) in ranked.iter().enumerate() {
+                        let command = &registry.all()[*idx];
+                        let is_selected = position == self.selected;
+                        let response = ui_scroll.horizontal(|ui_row| {
+                            let response = ui_row.selectable_label(is_selected, command.title).on_hover_text(command.id);
+                            if let Some(shortcut) = keymap.shortcut_label(ctx, command.id) {
+                                ui_row.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui_right| {
+                                    ui_right.weak(shortcut);
+                                });
+                            }
+                            response
+                        }).inner;
+                        if is_selected && (up || down) {
+                            response.scroll_to_me(None);
+                        }
+                        if response.clicked() {
+                            chosen = Some(command.id);
+                        }
+                    }
+                });
+                if enter {
+                    chosen = Some(registry.all()[ranked[self.selected].1].id);
+                }
+            });
+
+        if chosen.is_some() {
+            self.is_open = false;
+        }
+        chosen
+    }
+}
@@ -2,17 +2,21 @@
 
 use eframe::egui;
 use ropey::Rope;
-use crate::core::editor::{Cursor, TextEditor, Selection};
+use crate::core::editor::{Cursor, TextEditor};
+use crate::core::line_ending::strip_line_break;
+use crate::core::search::SearchQuery;
+use crate::core::settings::Settings;
 use crate::syntax_highlighting::highlighter::SyntaxHighlighter;
+use crate::syntax_highlighting::incremental::{splice_line_cache, LineSpans};
+use crate::syntax_highlighting::worker::BackgroundHighlighter;
+use crate::tasks::problems::Diagnostic;
 use egui::text::LayoutJob;
 use egui::TextWrapMode;
 use std::path::PathBuf;
-use std::ops::Deref;
+use std::borrow::Cow;
+use std::ops::{Deref, Range};
 use std::sync::Arc;
 
-const LINE_HEIGHT: f32 = 16.0;
-const LINE_NUMBER_GUTTER_WIDTH: f32 = 60.0;
-
 pub struct EditorPanel<'a> {
     pub content: &'a mut Rope,
     pub editor_state: &'a mut TextEditor,
@@ -20,7 +24,13 @@ pub struct EditorPanel<'a> {
     pub highlighter: &'a SyntaxHighlighter,
     pub is_modified: &'a mut bool,
     pub galley_cache: &'a mut Vec<Option<Arc<egui::Galley>>>,
-    pub last_content_len: &'a mut usize,
+    pub highlight: &'a mut BackgroundHighlighter, // Realce deste documento, feito em outra thread
+    pub diagnostics: &'a [&'a Diagnostic], // Problemas das tarefas neste arquivo
+    pub search: Option<&'a SearchQuery>, // Busca aberta: as ocorrências visíveis são destacadas
+    pub block_cursor: bool, // Cursor em bloco sobre o caractere (modo normal do Vim)
+    pub font_size: f32,
+    pub line_height: f32,
+    pub gutter_width: f32, // Largura da coluna de números de linha
 }
 
 impl<'a> EditorPanel<'a> {
@@ -31,8 +41,9 @@ impl<'a> EditorPanel<'a> {
         highlighter: &'a SyntaxHighlighter,
         is_modified: &'a mut bool,
         galley_cache: &'a mut Vec<Option<Arc<egui::Galley>>>,
-        last_content_len: &'a mut usize,
+        highlight: &'a mut BackgroundHighlighter,
     ) -> Self {
+        let defaults = Settings::default();
         Self {
             content,
             editor_state,
@@ -40,87 +51,184 @@ impl<'a> EditorPanel<'a> {
             highlighter,
             is_modified,
             galley_cache,
-            last_content_len,
+            highlight,
+            diagnostics: &[],
+            search: None,
+            block_cursor: false,
+            font_size: defaults.font_size,
+            line_height: defaults.line_height,
+            gutter_width: defaults.line_number_gutter_width,
         }
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui) {
-        let text_style = egui::TextStyle::Monospace;
-        let row_height = ui.text_style_height(&text_style);
+    /// Define os diagnósticos desenhados como sublinhados ondulados.
+    pub fn with_diagnostics(mut self, diagnostics: &'a [&'a Diagnostic]) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+
+    /// Define a busca cujas ocorrências são destacadas.
+    pub fn with_search(mut self, search: Option<&'a SearchQuery>) -> Self {
+        self.search = search;
+        self
+    }
+
+    /// Desenha o cursor como um bloco sobre o caractere em vez de uma barra.
+    pub fn with_block_cursor(mut self, block_cursor: bool) -> Self {
+        self.block_cursor = block_cursor;
+        self
+    }
+
+    /// Usa a fonte, a altura de linha e a largura da coluna de números das configurações.
+    pub fn with_settings(mut self, settings: &Settings) -> Self {
+        self.font_size = settings.font_size;
+        self.line_height = settings.line_height;
+        self.gutter_width = settings.line_number_gutter_width;
+        self
+    }
+
+    /// Desenha o editor e trata a digitação. Retorna se o texto está com o foco.
+    pub fn show(&mut self, ui: &mut egui::Ui) -> bool {
+        let row_height = self.line_height;
+        self.apply_text_changes();
         let total_lines = self.content.len_lines();
 
-        if total_lines != *self.last_content_len {
+        if total_lines != self.galley_cache.len() {
             self.galley_cache.resize_with(total_lines, || None);
-            *self.last_content_len = total_lines;
+        }
+
+        // Resultados da thread de realce: só as linhas cujo realce mudou perdem o galley
+        for line_idx in self.highlight.receive(total_lines) {
+            self.galley_cache[line_idx] = None;
         }
 
         let mut scroll_area = egui::ScrollArea::vertical()
             .id_salt("editor_scroll_area");
 
+        // Saltos vindos de fora do painel (ex.: links do terminal) centralizam o cursor
+        if self.editor_state.scroll_to_cursor {
+            self.editor_state.scroll_to_cursor = false;
+            let cursor_y = self.editor_state.cursor.line as f32 * row_height;
+            self.editor_state.scroll_offset.y = (cursor_y - ui.available_height() / 2.0).max(0.0);
+        }
         scroll_area = scroll_area.scroll_offset(self.editor_state.scroll_offset);
 
         let scroll_response = scroll_area.show_rows(ui, row_height, total_lines, |ui_scroll_area, row_range| {
             ui_scroll_area.horizontal(|ui_horizontal| {
                 ui_horizontal.vertical(|ui_vertical_numbers| {
-                    ui_vertical_numbers.set_width(LINE_NUMBER_GUTTER_WIDTH);
+                    ui_vertical_numbers.set_width(self.gutter_width);
                     ui_vertical_numbers.spacing_mut().item_spacing.y = 0.0;
                     ui_vertical_numbers.style_mut().wrap_mode = Some(TextWrapMode::Extend);
 
+                    // Cada número ocupa exatamente uma linha, para acompanhar o texto
+                    let number_font = egui::FontId::monospace(self.font_size);
+                    let number_color = ui_vertical_numbers.visuals().text_color();
                     for i in row_range.start..row_range.end {
-                        ui_vertical_numbers.monospace(format!("{:>4}", i + 1));
+                        let (rect, _) = ui_vertical_numbers.allocate_exact_size(egui::vec2(self.gutter_width, row_height), egui::Sense::hover());
+                        ui_vertical_numbers.painter().text(rect.left_center(), egui::Align2::LEFT_CENTER, format!("{:>4}", i + 1), number_font.clone(), number_color);
                     }
                 });
 
                 ui_horizontal.add_space(ui_horizontal.available_width() * 0.01);
                 
-                let editor_interaction_response = ui_horizontal.vertical(|ui_editor_content| {
+                let (editor_interaction_response, visible_lines) = ui_horizontal.vertical(|ui_editor_content| {
                     ui_editor_content.set_width(ui_editor_content.available_width());
                     ui_editor_content.spacing_mut().item_spacing.y = 0.0;
 
+                    // Linhas ainda sem realce saem na cor padrão até o resultado chegar
+                    let engine = self.highlighter.engine_for(self.path, self.content);
+                    self.highlight.request(ui_editor_content.ctx(), engine, self.content, row_range.clone());
+                    let default_color = self.highlighter.default_foreground();
+                    let id = ui_editor_content.id().with("full_editor_interaction_area");
+                    let has_focus = ui_editor_content.memory(|m| m.has_focus(id));
+                    let mut visible_lines = Vec::with_capacity(row_range.len());
+                    let search_matches = self.search
+                        .map(|search| search.find_in_lines(self.content, row_range.clone()))
+                        .unwrap_or_default();
+
                     for line_idx in row_range.start..row_range.end {
                         let galley_to_render = self.galley_cache[line_idx].clone().unwrap_or_else(|| {
-                            let line_content = self.content.line(line_idx);
-                            let line_str = line_content.as_str().unwrap_or("");
+                            let line_str: Cow<str> = self.content.line(line_idx).into();
                             
-                            let mut job = LayoutJob::default();
-                            job.halign = egui::Align::LEFT;
-
-                            let highlighted_chunks = self.highlighter.highlight_line(line_str, self.path);
-                            for (style, text) in highlighted_chunks {
-                                let egui_color = SyntaxHighlighter::syntect_color_to_egui_color(style.foreground);
-                                job.append(
-                                    text,
-                                    0.0,
-                                    egui::TextFormat {
-                                        font_id: egui::FontId::monospace(row_height * 0.9),
-                                        color: egui_color,
-                                        ..Default::default()
-                                    },
-                                );
-                            }
+                            let mut job = LayoutJob {
+                                halign: egui::Align::LEFT,
+                                ..Default::default()
+                            };
+
+                            let format = egui::TextFormat {
+                                font_id: egui::FontId::monospace(self.font_size),
+                                color: default_color,
+                                ..Default::default()
+                            };
+                            append_highlighted(&mut job, strip_line_break(&line_str), self.highlight.spans(line_idx), format);
                             let new_galley = ui_editor_content.fonts(|f| f.layout_job(job));
                             self.galley_cache[line_idx] = Some(new_galley.clone());
                             new_galley
                         });
                         
-                        let line_response = ui_editor_content.label(galley_to_render.clone());
-                        self.draw_selection_on_line(ui_editor_content, line_idx, &galley_to_render, &line_response.rect);
+                        // A linha tem a altura configurada; o texto fica centralizado nela
+                        let (line_rect, _) = ui_editor_content.allocate_exact_size(
+                            egui::vec2(galley_to_render.size().x, row_height),
+                            egui::Sense::hover(),
+                        );
+                        let text_top = line_rect.top() + (row_height - galley_to_render.size().y) / 2.0;
+                        ui_editor_content.painter().galley(egui::pos2(line_rect.left(), text_top), galley_to_render.clone(), default_color);
+                        self.draw_search_matches_on_line(ui_editor_content, line_idx, &galley_to_render, &line_rect, &search_matches);
+                        self.draw_selection_on_line(ui_editor_content, line_idx, &galley_to_render, &line_rect);
+                        self.draw_diagnostics_on_line(ui_editor_content, line_idx, &galley_to_render, &line_rect);
+                        if has_focus {
+                            self.draw_carets_on_line(ui_editor_content, line_idx, &galley_to_render, &line_rect);
+                        }
+                        visible_lines.push((line_idx, line_rect, galley_to_render));
                     }
 
-                    let full_editor_rect = ui_editor_content.available_rect_before_wrap();
-                    let id = ui_editor_content.id().with("full_editor_interaction_area");
-                    ui_editor_content.interact(full_editor_rect, id, egui::Sense::click_and_drag())
-                }).response;
+                    // A área de interação cobre as linhas e o espaço abaixo delas, por cima dos labels
+                    let full_editor_rect = ui_editor_content.min_rect().union(ui_editor_content.available_rect_before_wrap());
+                    let response = ui_editor_content.interact(full_editor_rect, id, egui::Sense::click_and_drag());
+                    (response, visible_lines)
+                }).inner;
                 
                 // Correção aqui: Passar ui_horizontal como o &mut Ui
-                self.handle_input_and_draw_cursor(ui_horizontal, &editor_interaction_response, row_height);
-            });
+                self.handle_pointer(ui_horizontal, &editor_interaction_response, &visible_lines);
+                self.handle_input(ui_horizontal, &editor_interaction_response);
+                editor_interaction_response.has_focus()
+            }).inner
         });
 
         self.editor_state.scroll_offset = scroll_response.state.offset;
+        scroll_response.inner
+    }
+
+    /// Cliques e arrastos: clique posiciona o cursor, Alt+clique acrescenta um cursor e
+    /// Alt+arrastar faz uma seleção em bloco.
+    fn handle_pointer(&mut self, ui: &mut egui::Ui, response: &egui::Response, visible_lines: &[(usize, egui::Rect, Arc<egui::Galley>)]) {
+        let Some(pointer) = response.interact_pointer_pos() else {
+            return;
+        };
+        let Some(position) = cursor_at(pointer, visible_lines) else {
+            return;
+        };
+        let alt = ui.input(|i| i.modifiers.alt);
+        let anchor_id = response.id.with("box_selection_anchor");
+
+        if response.drag_started() && alt {
+            ui.data_mut(|d| d.insert_temp(anchor_id, position));
+        } else if response.dragged() && alt {
+            if let Some(anchor) = ui.data(|d| d.get_temp::<Cursor>(anchor_id)) {
+                self.editor_state.set_box_selection(self.content, anchor, position);
+            }
+        } else if response.clicked() {
+            if alt {
+                self.editor_state.add_caret(self.content, position.line, position.char_idx);
+            } else {
+                self.editor_state.place_cursor(self.content, position.line, position.char_idx);
+            }
+        }
     }
 
-    fn handle_input_and_draw_cursor(&mut self, ui: &mut egui::Ui, editor_area_response: &egui::Response, row_height: f32) {
+    /// Digitação e colagem. Atalhos (setas, apagar, desfazer, copiar...) são comandos
+    /// do registro, executados pela aplicação quando o editor tem o foco.
+    fn handle_input(&mut self, ui: &mut egui::Ui, editor_area_response: &egui::Response) {
         let ctx = ui.ctx();
         if editor_area_response.clicked() {
             eprintln!("Editor area clicked!");
@@ -128,7 +236,13 @@ impl<'a> EditorPanel<'a> {
         }
 
         if editor_area_response.has_focus() {
-            eprintln!("Editor has focus. Processing input.");
+            // Setas e Esc são comandos do editor, não navegação de foco do egui
+            ui.memory_mut(|m| m.set_focus_lock_filter(editor_area_response.id, egui::EventFilter {
+                tab: false,
+                horizontal_arrows: true,
+                vertical_arrows: true,
+                escape: true,
+            }));
             ctx.input(|i| {
                 for event in &i.events {
                     match event {
@@ -138,159 +252,30 @@ impl<'a> EditorPanel<'a> {
                                 for ch in text.chars() {
                                     self.editor_state.insert_char(self.content, ch);
                                     *self.is_modified = true;
-                                    self.invalidate_cache_from_line(self.editor_state.cursor.line);
                                 }
                                 ctx.request_repaint();
                             }
                         },
-                        egui::Event::Key { key, pressed, modifiers, .. } => {
-                            if *pressed {
-                                eprintln!("Key pressed: {:?} (Modifiers: {:?})", key, modifiers);
-                                let mut handled = true;
-                                if modifiers.command || modifiers.ctrl {
-                                    match key {
-                                        egui::Key::Z => {
-                                            if modifiers.shift {
-                                                if self.editor_state.redo(self.content) {
-                                                    *self.is_modified = true;
-                                                    self.invalidate_cache_from_line(self.editor_state.cursor.line);
-                                                }
-                                            } else {
-                                                if self.editor_state.undo(self.content) {
-                                                    *self.is_modified = true;
-                                                    self.invalidate_cache_from_line(self.editor_state.cursor.line);
-                                                }
-                                            }
-                                        },
-                                        egui::Key::C => {
-                                            if let Some(selection) = self.editor_state.selection {
-                                                let normalized = selection.normalized();
-                                                let start_char_idx = self.content.line_to_char(normalized.start.line) + normalized.start.char_idx;
-                                                let end_char_idx = self.content.line_to_char(normalized.end.line) + normalized.end.char_idx;
-                                                let selected_text = self.content.slice(start_char_idx..end_char_idx).to_string();
-                                                ctx.copy_text(selected_text);
-                                            }
-                                        },
-                                        egui::Key::X => {
-                                            if let Some(selection) = self.editor_state.selection {
-                                                let normalized = selection.normalized();
-                                                let start_char_idx = self.content.line_to_char(normalized.start.line) + normalized.start.char_idx;
-                                                let end_char_idx = self.content.line_to_char(normalized.end.line) + normalized.end.char_idx;
-                                                let selected_text = self.content.slice(start_char_idx..end_char_idx).to_string();
-                                                ctx.copy_text(selected_text);
-                                                self.editor_state.delete_selected_text(self.content);
-                                                *self.is_modified = true;
-                                                self.invalidate_cache_from_line(self.editor_state.cursor.line);
-                                            }
-                                        },
-                                        egui::Key::V => {
-                                            if let Some(pasted_text) = i.raw.events.iter().filter_map(|event| {
-                                                if let egui::Event::Paste(s) = event { Some(s.clone()) } else { None }
-                                            }).last() {
-                                                self.editor_state.insert_text(self.content, &pasted_text);
-                                                *self.is_modified = true;
-                                                self.invalidate_cache_from_line(self.editor_state.cursor.line.saturating_sub(pasted_text.matches('\n').count()));
-                                            }
-                                        },
-                                        _ => handled = false,
-                                    }
-                                } else {
-                                    match key {
-                                        egui::Key::ArrowLeft => {
-                                            self.editor_state.move_cursor_left(self.content);
-                                            if modifiers.shift { self.editor_state.extend_selection(); } else { self.editor_state.clear_selection(); }
-                                        },
-                                        egui::Key::ArrowRight => {
-                                            self.editor_state.move_cursor_right(self.content);
-                                            if modifiers.shift { self.editor_state.extend_selection(); } else { self.editor_state.clear_selection(); }
-                                        },
-                                        egui::Key::ArrowUp => {
-                                            self.editor_state.move_cursor_up(self.content);
-                                            if modifiers.shift { self.editor_state.extend_selection(); } else { self.editor_state.clear_selection(); }
-                                        },
-                                        egui::Key::ArrowDown => {
-                                            self.editor_state.move_cursor_down(self.content);
-                                            if modifiers.shift { self.editor_state.extend_selection(); } else { self.editor_state.clear_selection(); }
-                                        },
-                                        egui::Key::Backspace => {
-                                            self.editor_state.delete_char_before_cursor(self.content);
-                                            *self.is_modified = true;
-                                            self.invalidate_cache_from_line(self.editor_state.cursor.line);
-                                        },
-                                        egui::Key::Delete => {
-                                            self.editor_state.delete_char_after_cursor(self.content);
-                                            *self.is_modified = true;
-                                            self.invalidate_cache_from_line(self.editor_state.cursor.line);
-                                        },
-                                        egui::Key::Enter => {
-                                            self.editor_state.new_line(self.content);
-                                            *self.is_modified = true;
-                                            self.invalidate_cache_from_line(self.editor_state.cursor.line.saturating_sub(1));
-                                        },
-                                        _ => handled = false,
-                                    }
-                                }
-                                if handled {
-                                    ctx.request_repaint();
-                                }
+                        egui::Event::Paste(pasted_text) => {
+                            // Uma linha por cursor quando os números batem
+                            if self.editor_state.has_multiple_carets() {
+                                let lines: Vec<String> = pasted_text.lines().map(str::to_string).collect();
+                                self.editor_state.insert_texts(self.content, &lines);
+                            } else {
+                                self.editor_state.insert_text(self.content, pasted_text);
                             }
-                        }
+                            *self.is_modified = true;
+                            ctx.request_repaint();
+                        },
                         _ => {}
                     }
                 }
             });
-        } else {
-            eprintln!("Editor does NOT have focus.");
-        }
-        
-        let painter = ui.painter();
-        let editor_rect = editor_area_response.rect;
-
-        let font_id = egui::FontId::monospace(row_height * 0.9);
-        
-        let cursor_line_content = self.content.line(self.editor_state.cursor.line).to_string();
-        let cursor_line_galley = ui.fonts(|f| f.layout_job(egui::text::LayoutJob::simple(
-            cursor_line_content,
-            font_id.clone(),
-            ui.style().visuals.text_color(),
-            ui.available_width()
-        )));
-
-        let galley_ref_cursor: &egui::Galley = &*cursor_line_galley;
-        let cursor_x_offset_in_line = galley_ref_cursor.rows.get(0)
-            .and_then(|row| {
-                row.glyphs.get(self.editor_state.cursor.char_idx)
-                    .map(|glyph_info| glyph_info.pos.x)
-                    .or_else(|| {
-                        row.glyphs.last()
-                           .map(|glyph_info| glyph_info.pos.x + glyph_info.advance_width)
-                    })
-            })
-            .unwrap_or(0.0);
-
-        let cursor_x = editor_rect.left() + cursor_x_offset_in_line;
-        let cursor_y_relative_to_scroll = self.editor_state.cursor.line as f32 * row_height;
-        let cursor_y_on_screen = editor_rect.top() + cursor_y_relative_to_scroll - self.editor_state.scroll_offset.y;
-
-        if editor_area_response.has_focus() {
-            let cursor_color = ui.style().visuals.text_color();
-            let cursor_width = 2.0;
-            let cursor_height = row_height;
-            
-            let cursor_visual_rect = egui::Rect::from_min_size(
-                egui::pos2(cursor_x, cursor_y_on_screen),
-                egui::vec2(cursor_width, cursor_height)
-            );
-
-            if editor_rect.intersects(cursor_visual_rect) {
-                painter.rect_filled(cursor_visual_rect, 0.0, cursor_color);
-            }
-            ctx.request_repaint();
         }
     }
 
     fn draw_selection_on_line(&self, ui: &mut egui::Ui, line_idx: usize, galley: &std::sync::Arc<egui::Galley>, line_rect: &egui::Rect) {
-        if let Some(selection) = self.editor_state.selection {
+        for selection in self.editor_state.carets().iter().filter_map(|caret| caret.selection) {
             let normalized_selection = selection.normalized();
 
             let selection_starts_on_this_line = normalized_selection.start.line == line_idx;
@@ -312,12 +297,12 @@ impl<'a> EditorPanel<'a> {
 
                 let galley_ref_selection: &egui::Galley = galley.deref();
 
-                let x_start = galley_ref_selection.rows.get(0)
+                let x_start = galley_ref_selection.rows.first()
                     .and_then(|row| row.glyphs.get(start_char_in_line))
                     .map(|glyph_info| glyph_info.pos.x)
                     .unwrap_or(0.0);
 
-                let x_end = galley_ref_selection.rows.get(0)
+                let x_end = galley_ref_selection.rows.first()
                     .and_then(|row| {
                         if end_char_in_line == row.glyphs.len() {
                             Some(row.rect.width())
@@ -342,9 +327,160 @@ impl<'a> EditorPanel<'a> {
         }
     }
 
-    fn invalidate_cache_from_line(&mut self, line_idx: usize) {
-        for i in line_idx..self.galley_cache.len() {
-            self.galley_cache[i] = None;
+    /// Destaca as ocorrências da busca nesta linha; a ocorrência selecionada tem cor
+    /// mais forte.
+    fn draw_search_matches_on_line(&self, ui: &mut egui::Ui, line_idx: usize, galley: &Arc<egui::Galley>, line_rect: &egui::Rect, matches: &[Range<usize>]) {
+        let Some(row) = galley.rows.first() else {
+            return;
+        };
+        let line_start = self.content.line_to_char(line_idx);
+        let line_end = line_start + self.content.line(line_idx).len_chars();
+        let glyph_x = |char_idx: usize| row.glyphs.get(char_idx).map(|g| g.pos.x).unwrap_or(row.rect.width());
+        let selected = self.editor_state.selection_range(self.content);
+
+        for range in matches.iter().filter(|range| range.start >= line_start && range.start < line_end) {
+            let color = if *range == selected {
+                egui::Color32::from_rgba_unmultiplied(255, 165, 0, 110)
+            } else {
+                egui::Color32::from_rgba_unmultiplied(255, 255, 0, 60)
+            };
+            let match_rect = egui::Rect::from_min_max(
+                egui::pos2(line_rect.left() + glyph_x(range.start - line_start), line_rect.top()),
+                egui::pos2(line_rect.left() + glyph_x(range.end - line_start), line_rect.bottom()),
+            );
+            ui.painter().rect_filled(match_rect, 0.0, color);
+        }
+    }
+
+    /// Desenha os cursores (o principal e os extras) que estão nesta linha.
+    fn draw_carets_on_line(&self, ui: &mut egui::Ui, line_idx: usize, galley: &Arc<egui::Galley>, line_rect: &egui::Rect) {
+        let Some(row) = galley.rows.first() else {
+            return;
+        };
+        let cursor_color = ui.visuals().text_cursor.stroke.color;
+        // Largura do bloco quando o cursor está depois do último caractere
+        let fallback_width = row.glyphs.first().map_or(8.0, |glyph| glyph.advance_width);
+        for caret in self.editor_state.carets().iter().filter(|caret| caret.cursor.line == line_idx) {
+            let glyph = row.glyphs.get(caret.cursor.char_idx);
+            let x = glyph
+                .map(|glyph| glyph.pos.x)
+                .or_else(|| row.glyphs.last().map(|glyph| glyph.pos.x + glyph.advance_width))
+                .unwrap_or(0.0);
+            let (width, color) = if self.block_cursor {
+                let width = glyph.map_or(fallback_width, |glyph| glyph.advance_width);
+                (width, cursor_color.gamma_multiply(0.5))
+            } else {
+                (2.0, cursor_color)
+            };
+            let caret_rect = egui::Rect::from_min_size(
+                egui::pos2(line_rect.left() + x, line_rect.top()),
+                egui::vec2(width, line_rect.height()),
+            );
+            ui.painter().rect_filled(caret_rect, 0.0, color);
+        }
+    }
+
+    /// Sublinha com uma linha ondulada os trechos apontados pelos diagnósticos, a partir
+    /// da coluna reportada até o fim da palavra (ou a linha inteira, sem coluna).
+    fn draw_diagnostics_on_line(&self, ui: &mut egui::Ui, line_idx: usize, galley: &Arc<egui::Galley>, line_rect: &egui::Rect) {
+        let Some(row) = galley.rows.first() else {
+            return;
+        };
+        let line_chars: Vec<char> = self.content.line(line_idx).chars().collect();
+        let glyph_x = |char_idx: usize| row.glyphs.get(char_idx).map(|g| g.pos.x).unwrap_or(row.rect.width());
+
+        for diagnostic in self.diagnostics.iter().filter(|d| d.line == line_idx + 1) {
+            let (start, end) = match diagnostic.column {
+                Some(column) => {
+                    let start = column.saturating_sub(1).min(line_chars.len());
+                    let word_len = line_chars[start..].iter().take_while(|c| c.is_alphanumeric() || **c == '_').count();
+                    (start, start + word_len.max(1))
+                },
+                None => {
+                    let indent = line_chars.iter().take_while(|c| c.is_whitespace()).count();
+                    let trimmed_len = line_chars.iter().rposition(|c| !c.is_whitespace()).map_or(indent, |i| i + 1);
+                    (indent, trimmed_len.max(indent + 1))
+                },
+            };
+            let x_start = line_rect.left() + glyph_x(start);
+            let x_end = (line_rect.left() + glyph_x(end)).max(x_start + 6.0);
+            let y = line_rect.bottom() - 2.0;
+
+            // Zigue-zague com amplitude de 1.5pt
+            let mut points = Vec::new();
+            let mut x = x_start;
+            let mut up = true;
+            while x < x_end {
+                points.push(egui::pos2(x, if up { y - 1.5 } else { y + 1.5 }));
+                x += 3.0;
+                up = !up;
+            }
+            points.push(egui::pos2(x_end, y));
+            let color = diagnostic.severity.color();
+            ui.painter().add(egui::Shape::line(points, egui::Stroke::new(1.0, color)));
+
+            let squiggle_rect = egui::Rect::from_min_max(egui::pos2(x_start, line_rect.top()), egui::pos2(x_end, line_rect.bottom()));
+            if ui.rect_contains_pointer(squiggle_rect) {
+                let id = ui.id().with(("diagnostic", line_idx, start));
+                egui::show_tooltip_at_pointer(ui.ctx(), ui.layer_id(), id, |ui_tooltip| {
+                    ui_tooltip.colored_label(color, format!("{}: {}", diagnostic.severity.label(), diagnostic.message));
+                    ui_tooltip.weak(&diagnostic.source);
+                });
+            }
+        }
+    }
+
+    /// Repassa as edições feitas desde o último frame aos caches por linha. As linhas
+    /// fora da edição mantêm galley e realce; só o que realmente mudou é refeito.
+    fn apply_text_changes(&mut self) {
+        for change in self.editor_state.take_changes() {
+            let first_line = change.first_line();
+            // O cache de galleys pode ter sido limpo (troca de tema): o tamanho vem do texto.
+            splice_line_cache(self.galley_cache, first_line, change.total_lines);
+            if let Some(galley) = self.galley_cache.get_mut(first_line) {
+                *galley = None;
+            }
+            self.highlight.edit(&change);
         }
     }
-}
\ No newline at end of file
+}
+
+/// Posição do texto sob o ponteiro, entre as linhas desenhadas neste frame. Abaixo
+/// da última linha vale a última.
+fn cursor_at(pointer: egui::Pos2, visible_lines: &[(usize, egui::Rect, Arc<egui::Galley>)]) -> Option<Cursor> {
+    let (line, rect, galley) = visible_lines.iter()
+        .find(|(_, rect, _)| pointer.y < rect.bottom())
+        .or_else(|| visible_lines.last())?;
+    let char_idx = galley.cursor_from_pos(egui::vec2(pointer.x - rect.left(), 0.0)).ccursor.index;
+    Some(Cursor { line: *line, char_idx })
+}
+
+/// Acrescenta a linha ao `job` com as cores do realce. Depois de uma edição o realce
+/// pode ser da versão anterior da linha: os trechos são ajustados ao texto atual e o
+/// que sobrar sai na cor padrão de `format`.
+fn append_highlighted(job: &mut LayoutJob, line: &str, spans: Option<&LineSpans>, format: egui::TextFormat) {
+    let floor_boundary = |mut idx: usize| {
+        idx = idx.min(line.len());
+        while !line.is_char_boundary(idx) {
+            idx -= 1;
+        }
+        idx
+    };
+
+    let mut covered = 0;
+    for (style, range) in spans.map(|s| s.as_slice()).unwrap_or_default() {
+        let start = floor_boundary(range.start).max(covered);
+        let end = floor_boundary(range.end);
+        if start >= end {
+            continue;
+        }
+        job.append(&line[start..end], 0.0, egui::TextFormat {
+            color: SyntaxHighlighter::syntect_color_to_egui_color(style.foreground),
+            ..format.clone()
+        });
+        covered = end;
+    }
+    if covered < line.len() {
+        job.append(&line[covered..], 0.0, format);
+    }
+}
@@ -0,0 +1,71 @@
+// src/ui/encoding_picker.rs
+
+use eframe::egui;
+
+use crate::core::encoding::{FileEncoding, ENCODING_CHOICES};
+
+/// O que fazer com a codificação escolhida.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodingPickerMode {
+    Reopen, // Ler o arquivo de novo com a codificação
+    Save,   // Converter e gravar o arquivo na codificação
+}
+
+/// Janela aberta pelos comandos "Reabrir com codificação" e "Salvar com codificação".
+#[derive(Debug, Default)]
+pub struct EncodingPicker {
+    pub mode: Option<EncodingPickerMode>,
+}
+
+impl EncodingPicker {
+    pub fn open(&mut self, mode: EncodingPickerMode) {
+        self.mode = Some(mode);
+    }
+
+    /// Desenha a janela para a aba atual. Retorna a escolha, que fecha a janela.
+    pub fn ui(&mut self, ctx: &egui::Context, current: FileEncoding, is_modified: bool) -> Option<(EncodingPickerMode, FileEncoding)> {
+        let mode = self.mode?;
+        let title = match mode {
+            EncodingPickerMode::Reopen => "Reabrir com codificação",
+            EncodingPickerMode::Save => "Salvar com codificação",
+        };
+
+        let mut chosen = None;
+        let mut is_open = true;
+        egui::Window::new(title)
+            .open(&mut is_open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 40.0))
+            .show(ctx, |ui| {
+                if mode == EncodingPickerMode::Reopen && is_modified {
+                    ui.weak("Salve ou desfaça as alterações antes de reabrir o arquivo.");
+                    return;
+                }
+                egui::ScrollArea::vertical().max_height(360.0).show(ui, |ui_scroll| {
+                    chosen = encoding_list(ui_scroll, current);
+                });
+            });
+
+        if let Some(encoding) = chosen {
+            self.mode = None;
+            return Some((mode, encoding));
+        }
+        if !is_open || ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.mode = None;
+        }
+        None
+    }
+}
+
+/// Lista das codificações, com a atual marcada. Também usada nos menus da barra de
+/// status.
+pub fn encoding_list(ui: &mut egui::Ui, current: FileEncoding) -> Option<FileEncoding> {
+    let mut chosen = None;
+    for encoding in ENCODING_CHOICES {
+        if ui.selectable_label(encoding == current, encoding.label()).clicked() {
+            chosen = Some(encoding);
+        }
+    }
+    chosen
+}
@@ -0,0 +1,208 @@
+// src/ui/find_bar.rs
+
+use eframe::egui;
+use ropey::Rope;
+
+use crate::core::editor::TextEditor;
+use crate::core::search::{SearchOptions, SearchQuery};
+
+/// Estado da barra de busca/substituição de uma aba do editor.
+#[derive(Debug, Default)]
+pub struct FindBar {
+    pub is_open: bool,
+    pub show_replace: bool,
+    pub query: String,
+    pub replacement: String,
+    pub options: SearchOptions,
+    pub error: Option<String>,
+    compiled: Option<SearchQuery>,
+    compiled_for: Option<(String, SearchOptions)>, // Consulta/opções usadas para compilar `compiled`
+    request_focus: bool,
+    field_ids: [Option<egui::Id>; 2], // Campos de busca e de substituição desenhados no último frame
+    not_found: bool, // A última navegação não encontrou nenhuma ocorrência
+    replaced: Option<usize>, // Quantas ocorrências o último "Substituir tudo" trocou
+}
+
+/// Ação pedida pela barra neste frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindAction {
+    None,
+    Next,
+    Previous,
+    Replace,
+    ReplaceAll,
+    Close,
+}
+
+impl FindBar {
+    /// Abre a barra (com o campo de substituição se `replace`). Uma seleção de uma
+    /// linha só vira a consulta.
+    pub fn open(&mut self, replace: bool, content: &Rope, editor: &TextEditor) {
+        self.is_open = true;
+        self.show_replace = replace;
+        self.request_focus = true;
+        let selected = editor.selection_range(content);
+        if !selected.is_empty() && content.char_to_line(selected.start) == content.char_to_line(selected.end) {
+            self.query = content.slice(selected).to_string();
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+    }
+
+    /// A barra está aberta e um dos campos dela tem o foco (atalhos da barra de busca).
+    pub fn has_focus(&self, ctx: &egui::Context) -> bool {
+        self.is_open && ctx.memory(|m| self.field_ids.iter().flatten().any(|id| m.has_focus(*id)))
+    }
+
+    /// Consulta compilada, se a barra está aberta e a consulta é válida. Recompila se a
+    /// consulta ou as opções mudaram.
+    pub fn search_query(&mut self) -> Option<&SearchQuery> {
+        if !self.is_open {
+            return None;
+        }
+        let key = (self.query.clone(), self.options);
+        if self.compiled_for.as_ref() != Some(&key) {
+            self.compiled_for = Some(key);
+            self.error = None;
+            self.not_found = false;
+            self.replaced = None;
+            self.compiled = if self.query.is_empty() {
+                None
+            } else {
+                SearchQuery::new(&self.query, self.options)
+                    .map_err(|e| self.error = Some(e))
+                    .ok()
+            };
+        }
+        self.compiled.as_ref()
+    }
+
+    /// Executa a ação no documento. Retorna `true` se o texto foi alterado.
+    pub fn apply(&mut self, action: FindAction, content: &mut Rope, editor: &mut TextEditor) -> bool {
+        if action == FindAction::Close {
+            self.close();
+            return false;
+        }
+        let replacement = self.replacement.clone();
+        let Some(query) = self.search_query().cloned() else {
+            return false;
+        };
+        let selected = editor.selection_range(content);
+
+        let mut modified = false;
+        let found = match action {
+            FindAction::Next => query.find_next(content, selected.end),
+            FindAction::Previous => query.find_previous(content, selected.start),
+            FindAction::Replace => {
+                // Troca a ocorrência selecionada (se for uma) e vai para a próxima
+                let mut from = selected.start;
+                if query.is_match(content, &selected) {
+                    let text = query.replacement_for(content, &selected, &replacement);
+                    from = editor.replace_ranges(content, &[(selected, text)])[0].end;
+                    modified = true;
+                }
+                query.find_next(content, from)
+            }
+            FindAction::ReplaceAll => {
+                let replacements: Vec<_> = query.find_all(content).into_iter()
+                    .map(|range| {
+                        let text = query.replacement_for(content, &range, &replacement);
+                        (range, text)
+                    })
+                    .collect();
+                if !replacements.is_empty() {
+                    editor.replace_ranges(content, &replacements);
+                    modified = true;
+                }
+                self.replaced = Some(replacements.len());
+                None
+            }
+            FindAction::None | FindAction::Close => return false,
+        };
+
+        if action != FindAction::ReplaceAll {
+            self.not_found = found.is_none();
+            self.replaced = None;
+        }
+        if let Some(range) = found {
+            editor.select_range(content, range);
+        }
+        modified
+    }
+
+    /// Desenha a barra e retorna a ação escolhida pelo usuário.
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> FindAction {
+        let mut action = FindAction::None;
+        ui.horizontal(|ui_bar| {
+            let toggle = if self.show_replace { "▾" } else { "▸" };
+            if ui_bar.small_button(toggle).on_hover_text("Substituir (Ctrl+H)").clicked() {
+                self.show_replace = !self.show_replace;
+            }
+            let response = ui_bar.add(
+                egui::TextEdit::singleline(&mut self.query)
+                    .desired_width(220.0)
+                    .hint_text("Buscar..."),
+            );
+            self.field_ids = [Some(response.id), None];
+            if self.request_focus {
+                response.request_focus();
+                self.request_focus = false;
+            }
+            if response.lost_focus() && ui_bar.input(|i| i.key_pressed(egui::Key::Enter)) {
+                action = if ui_bar.input(|i| i.modifiers.shift) { FindAction::Previous } else { FindAction::Next };
+                response.request_focus();
+            }
+            // O TextEdit perde o foco ao receber Esc, então o frame atual ainda conta.
+            if (response.has_focus() || response.lost_focus()) && ui_bar.input(|i| i.key_pressed(egui::Key::Escape)) {
+                action = FindAction::Close;
+            }
+
+            ui_bar.checkbox(&mut self.options.use_regex, ".*").on_hover_text("Expressão regular");
+            ui_bar.checkbox(&mut self.options.case_sensitive, "Aa").on_hover_text("Diferenciar maiúsculas/minúsculas");
+            ui_bar.checkbox(&mut self.options.whole_word, "ab").on_hover_text("Palavra inteira");
+            if ui_bar.button("↑").on_hover_text("Anterior (Shift+Enter)").clicked() {
+                action = FindAction::Previous;
+            }
+            if ui_bar.button("↓").on_hover_text("Próxima (Enter)").clicked() {
+                action = FindAction::Next;
+            }
+            if ui_bar.add(egui::Button::new("x").small()).clicked() {
+                action = FindAction::Close;
+            }
+
+            if let Some(error) = &self.error {
+                ui_bar.colored_label(ui_bar.visuals().error_fg_color, error.lines().next().unwrap_or_default());
+            } else if self.not_found {
+                ui_bar.weak("Sem resultados");
+            }
+        });
+
+        if self.show_replace {
+            ui.horizontal(|ui_bar| {
+                ui_bar.add_space(ui_bar.spacing().icon_width + ui_bar.spacing().item_spacing.x);
+                let response = ui_bar.add(
+                    egui::TextEdit::singleline(&mut self.replacement)
+                        .desired_width(220.0)
+                        .hint_text("Substituir por..."),
+                );
+                self.field_ids[1] = Some(response.id);
+                if response.lost_focus() && ui_bar.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    action = FindAction::Replace;
+                    response.request_focus();
+                }
+                if ui_bar.button("Substituir").clicked() {
+                    action = FindAction::Replace;
+                }
+                if ui_bar.button("Substituir tudo").clicked() {
+                    action = FindAction::ReplaceAll;
+                }
+                if let Some(count) = self.replaced {
+                    ui_bar.weak(format!("{} substituída(s)", count));
+                }
+            });
+        }
+        action
+    }
+}
@@ -1,4 +1,11 @@
 // src/ui/mod.rs
 
 pub mod app; // A struct principal da aplicação
-pub mod editor_ui;
\ No newline at end of file
+pub mod editor_ui;
+pub mod problems_panel; // Lista de diagnósticos das tarefas
+pub mod find_bar; // Barra de busca/substituição do editor
+pub mod search_panel; // Busca e substituição em todos os arquivos do projeto
+pub mod command_palette; // Paleta de comandos (Ctrl+Shift+P)
+pub mod settings_window; // Tela de configurações (Ctrl+,)
+pub mod recovery_dialog; // Recuperação dos arquivos de swap após uma queda
+pub mod encoding_picker; // Reabrir/salvar o arquivo em outra codificação
\ No newline at end of file
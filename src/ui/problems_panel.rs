@@ -0,0 +1,63 @@
+// src/ui/problems_panel.rs
+
+use std::path::Path;
+
+use eframe::egui;
+
+use crate::tasks::problems::{Diagnostic, Severity};
+use crate::terminal::links::FileLocation;
+
+/// Lista os diagnósticos das tarefas, agrupados por arquivo. Retorna o local
+/// clicado pelo usuário, para ser aberto no editor.
+pub fn show_problems_panel(ui: &mut egui::Ui, diagnostics: &[Diagnostic], project_dir: Option<&Path>) -> Option<FileLocation> {
+    let count = |severity: Severity| diagnostics.iter().filter(|d| d.severity == severity).count();
+    ui.horizontal(|ui_header| {
+        ui_header.strong("Problemas");
+        ui_header.colored_label(Severity::Error.color(), format!("{} erros", count(Severity::Error)));
+        ui_header.colored_label(Severity::Warning.color(), format!("{} avisos", count(Severity::Warning)));
+    });
+    ui.separator();
+
+    if diagnostics.is_empty() {
+        ui.label("Nenhum problema encontrado.");
+        return None;
+    }
+
+    let mut sorted: Vec<&Diagnostic> = diagnostics.iter().collect();
+    sorted.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+
+    let mut clicked = None;
+    egui::ScrollArea::vertical().id_salt("problems_scroll_area").show(ui, |ui_scroll| {
+        for (path, group) in sorted.chunk_by(|a, b| a.path == b.path).map(|g| (&g[0].path, g)) {
+            let display_path = project_dir
+                .and_then(|dir| path.strip_prefix(dir).ok())
+                .unwrap_or(path);
+            egui::CollapsingHeader::new(format!("{} ({})", display_path.display(), group.len()))
+                .id_salt(path)
+                .default_open(true)
+                .show(ui_scroll, |ui_file| {
+                    for diagnostic in group {
+                        let text = egui::RichText::new(format!(
+                            "[{}] {} ({}:{})",
+                            diagnostic.severity.label(),
+                            diagnostic.message,
+                            diagnostic.line,
+                            diagnostic.column.unwrap_or(1),
+                        ))
+                        .color(diagnostic.severity.color());
+                        let response = ui_file
+                            .add(egui::Label::new(text).sense(egui::Sense::click()).truncate())
+                            .on_hover_text(format!("{}\n{}", diagnostic.source, diagnostic.message));
+                        if response.clicked() {
+                            clicked = Some(FileLocation {
+                                path: diagnostic.path.clone(),
+                                line: Some(diagnostic.line),
+                                column: diagnostic.column,
+                            });
+                        }
+                    }
+                });
+        }
+    });
+    clicked
+}
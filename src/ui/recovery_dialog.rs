@@ -0,0 +1,177 @@
+// src/ui/recovery_dialog.rs
+
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use eframe::egui;
+
+use crate::core::diff::{diff_lines, DiffLine};
+use crate::core::swap::RecoveredSwap;
+
+/// Linhas iguais mantidas em volta de cada trecho alterado na comparação.
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// O que o usuário escolheu para um arquivo recuperado.
+#[derive(Debug, Clone)]
+pub enum RecoveryAction {
+    Compare(usize), // Mostrar a diferença para o conteúdo atual do arquivo
+    Restore(RecoveredSwap),
+    Discard(RecoveredSwap),
+}
+
+/// Linha já pronta para desenhar na comparação.
+#[derive(Debug, Clone)]
+struct DiffRow {
+    marker: char, // '+', '-' ou ' '; '…' para linhas iguais omitidas
+    text: String,
+}
+
+/// Diálogo mostrado ao iniciar quando há arquivos de swap de uma execução que não
+/// terminou normalmente: para cada um, comparar, restaurar ou descartar.
+#[derive(Debug, Default)]
+pub struct RecoveryDialog {
+    pub entries: Vec<RecoveredSwap>,
+    comparison: Option<(PathBuf, Vec<DiffRow>)>, // Swap comparado e as linhas da diferença
+}
+
+impl RecoveryDialog {
+    pub fn is_open(&self) -> bool {
+        !self.entries.is_empty()
+    }
+
+    /// Calcula a diferença entre o conteúdo atual do arquivo e o recuperado.
+    pub fn show_comparison(&mut self, idx: usize, current: &str) {
+        let Some(entry) = self.entries.get(idx) else {
+            return;
+        };
+        let rows = collapse_unchanged(&diff_lines(current, &entry.content));
+        self.comparison = Some((entry.swap_path.clone(), rows));
+    }
+
+    /// Desenha o diálogo. A entrada restaurada ou descartada sai da lista.
+    pub fn ui(&mut self, ctx: &egui::Context) -> Option<RecoveryAction> {
+        if !self.is_open() {
+            return None;
+        }
+
+        let mut action = None;
+        egui::Window::new("Recuperar alterações")
+            .collapsible(false)
+            .resizable(true)
+            .default_width(640.0)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("O lcode não foi fechado normalmente. Foram encontradas alterações não salvas nestes arquivos:");
+                ui.separator();
+                for (idx, entry) in self.entries.iter().enumerate() {
+                    let comparing = self.comparison.as_ref().is_some_and(|(swap_path, _)| *swap_path == entry.swap_path);
+                    ui.horizontal(|ui_row| {
+                        ui_row.monospace(entry.path.display().to_string());
+                        ui_row.weak(format_age(entry.saved_at));
+                        ui_row.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui_buttons| {
+                            if ui_buttons.button("Descartar").clicked() {
+                                action = Some(RecoveryAction::Discard(entry.clone()));
+                            }
+                            if ui_buttons.button("Restaurar").clicked() {
+                                action = Some(RecoveryAction::Restore(entry.clone()));
+                            }
+                            if ui_buttons.selectable_label(comparing, "Comparar").clicked() {
+                                action = Some(RecoveryAction::Compare(idx));
+                            }
+                        });
+                    });
+                    if comparing && let Some((_, rows)) = &self.comparison {
+                        show_diff(ui, rows);
+                    }
+                }
+            });
+
+        match &action {
+            Some(RecoveryAction::Restore(entry) | RecoveryAction::Discard(entry)) => {
+                self.entries.retain(|other| other.swap_path != entry.swap_path);
+                self.comparison = None;
+            },
+            Some(RecoveryAction::Compare(idx)) => {
+                // Um segundo clique fecha a comparação
+                let swap_path = &self.entries[*idx].swap_path;
+                if self.comparison.as_ref().is_some_and(|(compared, _)| compared == swap_path) {
+                    self.comparison = None;
+                    return None;
+                }
+            },
+            None => {}
+        }
+        action
+    }
+}
+
+fn show_diff(ui: &mut egui::Ui, rows: &[DiffRow]) {
+    egui::Frame::group(ui.style()).show(ui, |ui_frame| {
+        if rows.iter().all(|row| row.marker == ' ' || row.marker == '…') {
+            ui_frame.weak("O conteúdo recuperado é igual ao atual.");
+            return;
+        }
+        egui::ScrollArea::both().max_height(300.0).show(ui_frame, |ui_scroll| {
+            let added = egui::Color32::from_rgb(80, 170, 90);
+            let removed = ui_scroll.visuals().error_fg_color;
+            for row in rows {
+                let text = egui::RichText::new(format!("{} {}", row.marker, row.text)).monospace();
+                match row.marker {
+                    '+' => ui_scroll.label(text.color(added)),
+                    '-' => ui_scroll.label(text.color(removed)),
+                    '…' => ui_scroll.weak(text),
+                    _ => ui_scroll.label(text),
+                };
+            }
+        });
+    });
+}
+
+/// Mantém só as linhas alteradas e algumas iguais em volta delas; os trechos iguais
+/// maiores viram uma linha "… N linhas iguais".
+fn collapse_unchanged(diff: &[DiffLine]) -> Vec<DiffRow> {
+    let changed: Vec<usize> = diff.iter().enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(idx, _)| idx)
+        .collect();
+    let near_change = |idx: usize| {
+        let next = changed.partition_point(|&changed_idx| changed_idx < idx);
+        let after = changed.get(next).is_some_and(|&changed_idx| changed_idx - idx <= DIFF_CONTEXT_LINES);
+        let before = next > 0 && idx - changed[next - 1] <= DIFF_CONTEXT_LINES;
+        after || before
+    };
+
+    let mut rows = Vec::new();
+    let mut omitted = 0;
+    for (idx, line) in diff.iter().enumerate() {
+        let (marker, text) = match line {
+            DiffLine::Same(_) if !near_change(idx) => {
+                omitted += 1;
+                continue;
+            },
+            DiffLine::Same(text) => (' ', text),
+            DiffLine::Removed(text) => ('-', text),
+            DiffLine::Added(text) => ('+', text),
+        };
+        if omitted > 0 {
+            rows.push(DiffRow { marker: '…', text: format!("{} linhas iguais", omitted) });
+            omitted = 0;
+        }
+        rows.push(DiffRow { marker, text: text.to_string() });
+    }
+    if omitted > 0 {
+        rows.push(DiffRow { marker: '…', text: format!("{} linhas iguais", omitted) });
+    }
+    rows
+}
+
+/// "há 5 min", "há 2 h"...
+fn format_age(saved_at: SystemTime) -> String {
+    let seconds = saved_at.elapsed().map_or(0, |elapsed| elapsed.as_secs());
+    match seconds {
+        0..60 => "há menos de 1 min".to_string(),
+        60..3600 => format!("há {} min", seconds / 60),
+        3600..86400 => format!("há {} h", seconds / 3600),
+        _ => format!("há {} dia(s)", seconds / 86400),
+    }
+}
@@ -0,0 +1,178 @@
+// src/ui/search_panel.rs
+
+use std::path::Path;
+
+use eframe::egui;
+use egui::text::LayoutJob;
+
+use crate::core::project_search::ProjectSearch;
+use crate::terminal::links::FileLocation;
+
+/// Ação pedida pelo painel de busca no projeto.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchPanelAction {
+    Open(FileLocation),
+    PreviewReplace,
+    ApplyReplace,
+}
+
+/// Painel "Buscar em arquivos": campos da busca, resultados agrupados por arquivo e a
+/// prévia da substituição antes de aplicá-la.
+pub fn show_search_panel(ui: &mut egui::Ui, search: &mut ProjectSearch, project_dir: Option<&Path>) -> Option<SearchPanelAction> {
+    let mut action = None;
+    let mut start = false;
+
+    ui.horizontal(|ui_bar| {
+        ui_bar.strong("Buscar em arquivos");
+        let response = ui_bar.add(
+            egui::TextEdit::singleline(&mut search.query)
+                .desired_width(220.0)
+                .hint_text("Buscar..."),
+        );
+        if response.lost_focus() && ui_bar.input(|i| i.key_pressed(egui::Key::Enter)) {
+            start = true;
+        }
+        ui_bar.checkbox(&mut search.options.use_regex, ".*").on_hover_text("Expressão regular");
+        ui_bar.checkbox(&mut search.options.case_sensitive, "Aa").on_hover_text("Diferenciar maiúsculas/minúsculas");
+        ui_bar.checkbox(&mut search.options.whole_word, "ab").on_hover_text("Palavra inteira");
+        if search.running {
+            if ui_bar.button("Parar").clicked() {
+                search.cancel();
+            }
+            ui_bar.spinner();
+        } else if ui_bar.button("Buscar").clicked() {
+            start = true;
+        }
+
+        ui_bar.separator();
+        ui_bar.add(
+            egui::TextEdit::singleline(&mut search.replacement)
+                .desired_width(180.0)
+                .hint_text("Substituir por..."),
+        );
+        let can_replace = !search.running && search.results.iter().any(|file| file.selected);
+        if ui_bar.add_enabled(can_replace, egui::Button::new("Substituir..."))
+            .on_hover_text("Mostra as alterações nos arquivos marcados antes de aplicar")
+            .clicked()
+        {
+            action = Some(SearchPanelAction::PreviewReplace);
+        }
+    });
+
+    if start {
+        match project_dir {
+            Some(dir) if !search.query.is_empty() => search.start(ui.ctx(), dir),
+            Some(_) => {}
+            None => search.error = Some("Nenhum diretório aberto.".to_string()),
+        }
+    }
+
+    ui.horizontal(|ui_status| {
+        if let Some(error) = &search.error {
+            ui_status.colored_label(ui_status.visuals().error_fg_color, error.lines().next().unwrap_or_default());
+        } else {
+            ui_status.weak(format!("{} ocorrências em {} arquivos", search.hit_count(), search.results.len()));
+            if search.truncated {
+                ui_status.weak("(limite atingido; refine a busca)");
+            }
+        }
+    });
+    ui.separator();
+
+    if search.preview.is_some() {
+        return show_preview(ui, search, project_dir).or(action);
+    }
+
+    let weak_color = ui.visuals().weak_text_color();
+    let text_color = ui.visuals().text_color();
+    let highlight_color = egui::Color32::from_rgba_unmultiplied(255, 255, 0, 60);
+
+    egui::ScrollArea::vertical().id_salt("project_search_scroll_area").show(ui, |ui_scroll| {
+        for file in &mut search.results {
+            let display_path = project_dir
+                .and_then(|dir| file.path.strip_prefix(dir).ok())
+                .unwrap_or(&file.path)
+                .display()
+                .to_string();
+            let id = ui_scroll.make_persistent_id(&file.path);
+            egui::collapsing_header::CollapsingState::load_with_default_open(ui_scroll.ctx(), id, true)
+                .show_header(ui_scroll, |ui_header| {
+                    ui_header.checkbox(&mut file.selected, "").on_hover_text("Incluir na substituição");
+                    ui_header.label(format!("{} ({})", display_path, file.hits.len()));
+                })
+                .body(|ui_file| {
+                    for hit in &file.hits {
+                        let mut job = LayoutJob::default();
+                        let font_id = egui::TextStyle::Monospace.resolve(ui_file.style());
+                        let plain = egui::TextFormat::simple(font_id.clone(), text_color);
+                        job.append(&format!("{:>5}: ", hit.line), 0.0, egui::TextFormat::simple(font_id, weak_color));
+                        job.append(&hit.preview[..hit.preview_match.start], 0.0, plain.clone());
+                        job.append(&hit.preview[hit.preview_match.clone()], 0.0, egui::TextFormat {
+                            background: highlight_color,
+                            ..plain.clone()
+                        });
+                        job.append(&hit.preview[hit.preview_match.end..], 0.0, plain);
+
+                        let response = ui_file.add(egui::Label::new(job).sense(egui::Sense::click()).truncate());
+                        if response.clicked() {
+                            action = Some(SearchPanelAction::Open(FileLocation {
+                                path: file.path.clone(),
+                                line: Some(hit.line),
+                                column: Some(hit.column),
+                            }));
+                        }
+                    }
+                });
+        }
+    });
+    action
+}
+
+/// Linhas alteradas em cada arquivo (antes em vermelho, depois em verde), com os
+/// botões para aplicar ou desistir.
+fn show_preview(ui: &mut egui::Ui, search: &mut ProjectSearch, project_dir: Option<&Path>) -> Option<SearchPanelAction> {
+    let mut action = None;
+    let Some(preview) = &search.preview else {
+        return None;
+    };
+    let change_count: usize = preview.files.iter().map(|file| file.changes.len()).sum();
+
+    let mut cancel = false;
+    ui.horizontal(|ui_buttons| {
+        ui_buttons.label(format!("{} linhas alteradas em {} arquivos", change_count, preview.files.len()));
+        if ui_buttons.button("Aplicar").clicked() {
+            action = Some(SearchPanelAction::ApplyReplace);
+        }
+        if ui_buttons.button("Cancelar").clicked() {
+            cancel = true;
+        }
+    });
+
+    let removed_color = ui.visuals().error_fg_color;
+    let added_color = egui::Color32::from_rgb(80, 200, 120);
+    egui::ScrollArea::vertical().id_salt("project_replace_preview").show(ui, |ui_scroll| {
+        for file in &preview.files {
+            let display_path = project_dir
+                .and_then(|dir| file.path.strip_prefix(dir).ok())
+                .unwrap_or(&file.path);
+            egui::CollapsingHeader::new(format!("{} ({})", display_path.display(), file.changes.len()))
+                .id_salt(("preview", &file.path))
+                .default_open(true)
+                .show(ui_scroll, |ui_file| {
+                    for change in &file.changes {
+                        ui_file.add(egui::Label::new(
+                            egui::RichText::new(format!("{:>5} - {}", change.line, change.before)).monospace().color(removed_color),
+                        ).truncate());
+                        ui_file.add(egui::Label::new(
+                            egui::RichText::new(format!("{:>5} + {}", change.line, change.after)).monospace().color(added_color),
+                        ).truncate());
+                    }
+                });
+        }
+    });
+
+    if cancel {
+        search.preview = None;
+    }
+    action
+}
@@ -0,0 +1,132 @@
+// src/ui/settings_window.rs
+
+use eframe::egui;
+
+use crate::core::settings::{
+    Settings, SettingsStore, FONT_SIZE_RANGE, GUTTER_WIDTH_RANGE, LINE_HEIGHT_RANGE,
+    SIDE_PANEL_WIDTH_RANGE, TERMINAL_SCROLLBACK_RANGE,
+};
+
+/// O que o usuário pediu na tela de configurações.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingsAction {
+    Save(Settings), // Gravar no arquivo do usuário
+    OpenUserFile,
+    OpenWorkspaceFile,
+}
+
+/// Tela de configurações: edita uma cópia das configurações do usuário, gravada só ao
+/// salvar. Chaves sobrescritas pelo projeto e erros dos arquivos aparecem junto.
+#[derive(Debug, Default)]
+pub struct SettingsWindow {
+    pub is_open: bool,
+    draft: Settings,
+}
+
+impl SettingsWindow {
+    /// Abre a tela com os valores atuais do arquivo do usuário.
+    pub fn open(&mut self, store: &SettingsStore) {
+        self.is_open = true;
+        self.draft = store.user.clone();
+    }
+
+    /// Desenha a janela. Retorna a ação escolhida, se houver.
+    pub fn ui(&mut self, ctx: &egui::Context, store: &SettingsStore, themes: &[String]) -> Option<SettingsAction> {
+        if !self.is_open {
+            return None;
+        }
+
+        let mut action = None;
+        let mut is_open = self.is_open;
+        egui::Window::new("Configurações")
+            .open(&mut is_open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let draft = &mut self.draft;
+                egui::Grid::new("settings_grid").num_columns(2).spacing([12.0, 6.0]).show(ui, |ui_grid| {
+                    let row = |ui: &mut egui::Ui, label: &str, key: &str, add: &mut dyn FnMut(&mut egui::Ui)| {
+                        let response = ui.label(label).on_hover_text(key);
+                        if store.overridden.iter().any(|overridden| overridden == key) {
+                            response.on_hover_text("Sobrescrito pelo arquivo do projeto");
+                            ui.horizontal(|ui_row| {
+                                add(ui_row);
+                                ui_row.weak("(projeto)");
+                            });
+                        } else {
+                            add(ui);
+                        }
+                        ui.end_row();
+                    };
+
+                    row(ui_grid, "Tamanho da fonte", "font_size", &mut |ui| {
+                        ui.add(egui::DragValue::new(&mut draft.font_size).range(FONT_SIZE_RANGE).speed(0.1));
+                    });
+                    row(ui_grid, "Altura da linha", "line_height", &mut |ui| {
+                        ui.add(egui::DragValue::new(&mut draft.line_height).range(LINE_HEIGHT_RANGE).speed(0.1));
+                    });
+                    row(ui_grid, "Largura dos números de linha", "line_number_gutter_width", &mut |ui| {
+                        ui.add(egui::DragValue::new(&mut draft.line_number_gutter_width).range(GUTTER_WIDTH_RANGE));
+                    });
+                    row(ui_grid, "Largura do explorador", "side_panel_width", &mut |ui| {
+                        ui.add(egui::DragValue::new(&mut draft.side_panel_width).range(SIDE_PANEL_WIDTH_RANGE));
+                    });
+                    row(ui_grid, "Tema", "theme", &mut |ui| {
+                        egui::ComboBox::from_id_salt("settings_theme")
+                            .selected_text(&draft.theme)
+                            .show_ui(ui, |ui_combo| {
+                                for name in themes {
+                                    ui_combo.selectable_value(&mut draft.theme, name.clone(), name);
+                                }
+                            });
+                    });
+                    row(ui_grid, "Usar mmap a partir de (KB)", "mmap_threshold", &mut |ui| {
+                        let mut kilobytes = draft.mmap_threshold / 1024;
+                        if ui.add(egui::DragValue::new(&mut kilobytes).speed(16.0)).changed() {
+                            draft.mmap_threshold = kilobytes * 1024;
+                        }
+                    });
+                    row(ui_grid, "Histórico do terminal (linhas)", "terminal_scrollback", &mut |ui| {
+                        ui.add(egui::DragValue::new(&mut draft.terminal_scrollback).range(TERMINAL_SCROLLBACK_RANGE).speed(100.0));
+                    });
+                    row(ui_grid, "Modo Vim", "vim_mode", &mut |ui| {
+                        ui.checkbox(&mut draft.vim_mode, "Iniciar no modo Vim");
+                    });
+                });
+
+                if !store.errors.is_empty() {
+                    ui.separator();
+                    for error in &store.errors {
+                        ui.colored_label(ui.visuals().error_fg_color, error);
+                    }
+                }
+
+                ui.separator();
+                ui.horizontal(|ui_buttons| {
+                    let modified = *draft != store.user;
+                    if ui_buttons.add_enabled(modified, egui::Button::new("Salvar")).clicked() {
+                        action = Some(SettingsAction::Save(draft.clone()));
+                    }
+                    if ui_buttons.add_enabled(modified, egui::Button::new("Descartar")).clicked() {
+                        *draft = store.user.clone();
+                    }
+                    if ui_buttons.button("Restaurar padrões").clicked() {
+                        *draft = Settings::default();
+                    }
+                });
+                ui.horizontal(|ui_files| {
+                    let user_file = store.user_path().map(|path| path.display().to_string()).unwrap_or_default();
+                    if ui_files.link("Abrir settings.toml").on_hover_text(user_file).clicked() {
+                        action = Some(SettingsAction::OpenUserFile);
+                    }
+                    if let Some(path) = store.workspace_path()
+                        && ui_files.link("Abrir configurações do projeto").on_hover_text(path.display().to_string()).clicked()
+                    {
+                        action = Some(SettingsAction::OpenWorkspaceFile);
+                    }
+                });
+            });
+        self.is_open = is_open;
+        action
+    }
+}